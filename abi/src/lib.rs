@@ -0,0 +1,432 @@
+//! The types, constants and function signatures MEGS and its
+//! modules agree on.
+//!
+//! The host generates its [`Contract`] from the signatures below
+//! and guests (e.g. through `megs-sdk`) use the same [`State`], so
+//! both sides can't drift apart.
+//!
+//! [`Contract`]: https://docs.rs/megs/latest/megs/core/contract/struct.Contract.html
+#![no_std]
+
+use core::fmt;
+use core::ops::{Not, BitAnd, BitOr, BitXor};
+
+/// The state of a pin, passed as `i32` across the module boundary.
+#[repr(C)]
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Default)]
+pub enum State {
+    #[default]
+    Undefined,
+    Low,
+    High,
+    /// Not driven at all, e.g. a disabled tri-state output.
+    HighImpedance,
+    /// Driven to different levels at the same time.
+    Conflict,
+}
+
+impl State {
+    /// Get the symbol used to represent the state in tables
+    /// and other textual output.
+    pub fn symbol(&self) -> char {
+        match self {
+            State::Undefined => 'U',
+            State::Low => '0',
+            State::High => '1',
+            State::HighImpedance => 'Z',
+            State::Conflict => 'X',
+        }
+    }
+
+    /// Resolve the state of a net driven by `self` and `other`.
+    ///
+    /// [`State::HighImpedance`] yields to any other driver, while
+    /// drivers of different levels result in a [`State::Conflict`].
+    pub fn resolve(self, other: State) -> State {
+        match (self, other) {
+            (State::HighImpedance, s) | (s, State::HighImpedance) => s,
+            (State::Conflict, _) | (_, State::Conflict) => State::Conflict,
+            (State::Undefined, _) | (_, State::Undefined) => State::Undefined,
+            (a, b) if a == b => a,
+            _ => State::Conflict,
+        }
+    }
+
+    /// Check if the state is [`State::Low`] or [`State::High`].
+    pub fn is_level(self) -> bool {
+        matches!(self, State::Low | State::High)
+    }
+
+    /// Get the state a gate sees at one of its inputs: a floating
+    /// input is just as undefined as an uninitialised one.
+    fn level(self) -> State {
+        match self {
+            State::HighImpedance => State::Undefined,
+            s => s,
+        }
+    }
+}
+
+impl From<i32> for State {
+    fn from(v: i32) -> Self {
+        match v {
+            1 => State::Low,
+            2 => State::High,
+            3 => State::HighImpedance,
+            4 => State::Conflict,
+            _ => State::Undefined,
+        }
+    }
+}
+
+impl From<State> for i32 {
+    fn from(s: State) -> Self {
+        s as i32
+    }
+}
+
+/// Negation; [`State::Undefined`] stays undefined, [`State::Conflict`]
+/// propagates and [`State::HighImpedance`] reads as undefined.
+impl Not for State {
+    type Output = State;
+
+    fn not(self) -> State {
+        match self.level() {
+            State::Low => State::High,
+            State::High => State::Low,
+            s => s,
+        }
+    }
+}
+
+/// Conjunction; a single [`State::Low`] dominates.
+impl BitAnd for State {
+    type Output = State;
+
+    fn bitand(self, rhs: State) -> State {
+        match (self.level(), rhs.level()) {
+            (State::Low, _) | (_, State::Low) => State::Low,
+            (State::High, State::High) => State::High,
+            (State::Conflict, _) | (_, State::Conflict) => State::Conflict,
+            _ => State::Undefined,
+        }
+    }
+}
+
+/// Disjunction; a single [`State::High`] dominates.
+impl BitOr for State {
+    type Output = State;
+
+    fn bitor(self, rhs: State) -> State {
+        match (self.level(), rhs.level()) {
+            (State::High, _) | (_, State::High) => State::High,
+            (State::Low, State::Low) => State::Low,
+            (State::Conflict, _) | (_, State::Conflict) => State::Conflict,
+            _ => State::Undefined,
+        }
+    }
+}
+
+/// Exclusive or; only defined if both operands are.
+impl BitXor for State {
+    type Output = State;
+
+    fn bitxor(self, rhs: State) -> State {
+        match (self.level(), rhs.level()) {
+            (State::Conflict, _) | (_, State::Conflict) => State::Conflict,
+            (State::Undefined, _) | (_, State::Undefined) => State::Undefined,
+            (a, b) => State::from(a != b),
+        }
+    }
+}
+
+impl From<bool> for State {
+    fn from(b: bool) -> Self {
+        if b { State::High } else { State::Low }
+    }
+}
+
+/// The largest supported width of a bus.
+///
+/// Bus values are passed as `i64`: the low 32 bits hold the level of
+/// every bit, the high 32 bits mark the bits without a level.
+pub const MAX_WIDTH: usize = 32;
+
+/// A packed multi-bit value, e.g. the state of a bus, where
+/// each bit has a [`State`] of its own.
+///
+/// Bit `0` is the least significant bit.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub struct Bits {
+    /// The number of bits.
+    width: usize,
+    /// The values of all bits at a defined level.
+    value: u32,
+    /// A mask of all undefined bits.
+    undef: u32,
+    /// A mask of all high impedance bits.
+    highz: u32,
+    /// A mask of all bits in conflict.
+    conflict: u32,
+}
+
+impl Bits {
+    fn mask(width: usize) -> u32 {
+        if width >= MAX_WIDTH { u32::MAX } else { (1 << width) - 1 }
+    }
+
+    /// Create a value of `width` undefined bits.
+    ///
+    /// The width is clamped to `1..=MAX_WIDTH`.
+    pub fn undefined(width: usize) -> Self {
+        let width = width.clamp(1, MAX_WIDTH);
+        Self { width, value: 0, undef: Self::mask(width), highz: 0, conflict: 0 }
+    }
+
+    /// Create a fully defined value of `width` bits.
+    pub fn from_u32(width: usize, value: u32) -> Self {
+        let width = width.clamp(1, MAX_WIDTH);
+        Self { width, value: value & Self::mask(width), undef: 0, highz: 0, conflict: 0 }
+    }
+
+    /// Decode a value passed across the module boundary: the low
+    /// 32 bits hold the levels, the high 32 bits mark bits without
+    /// a level. Those are high impedance if their level bit is set
+    /// and undefined otherwise.
+    pub fn from_i64(width: usize, v: i64) -> Self {
+        let width = width.clamp(1, MAX_WIDTH);
+        let mask = Self::mask(width);
+        let (levels, special) = (v as u32 & mask, (v >> 32) as u32 & mask);
+        Self {
+            width,
+            value: levels & !special,
+            undef: special & !levels,
+            highz: special & levels,
+            conflict: 0,
+        }
+    }
+
+    /// Encode the value for the module boundary, see [`Bits::from_i64`].
+    ///
+    /// Modules can't tell conflicts from undefined bits.
+    pub fn to_i64(&self) -> i64 {
+        let special = self.undef | self.highz | self.conflict;
+        ((special as i64) << 32) | (self.value | self.highz) as i64
+    }
+
+    /// Get the number of bits.
+    pub fn width(&self) -> usize {
+        self.width
+    }
+
+    /// Get the state of bit `i`, [`State::Undefined`] if out of range.
+    pub fn get(&self, i: usize) -> State {
+        let bit = 1 << i;
+        if i >= self.width || self.undef & bit != 0 {
+            State::Undefined
+        } else if self.highz & bit != 0 {
+            State::HighImpedance
+        } else if self.conflict & bit != 0 {
+            State::Conflict
+        } else {
+            State::from(self.value & bit != 0)
+        }
+    }
+
+    /// Set bit `i` to `state`, bits out of range are ignored.
+    pub fn set(&mut self, i: usize, state: State) {
+        if i >= self.width {
+            return;
+        }
+
+        let bit = 1 << i;
+        self.value &= !bit;
+        self.undef &= !bit;
+        self.highz &= !bit;
+        self.conflict &= !bit;
+        match state {
+            State::High => self.value |= bit,
+            State::Low => {},
+            State::Undefined => self.undef |= bit,
+            State::HighImpedance => self.highz |= bit,
+            State::Conflict => self.conflict |= bit,
+        }
+    }
+
+    /// Get the value as an unsigned number, `None` unless all bits
+    /// are at a defined level.
+    pub fn value(&self) -> Option<u32> {
+        if self.undef | self.highz | self.conflict == 0 { Some(self.value) } else { None }
+    }
+
+    /// Check if any bit is in conflict.
+    pub fn has_conflict(&self) -> bool {
+        self.conflict != 0
+    }
+
+    /// Resolve the value of a net driven by `self` and `other`
+    /// bit by bit, see [`State::resolve`].
+    pub fn resolve(&self, other: &Bits) -> Bits {
+        let mut bits = Bits::undefined(self.width.max(other.width));
+        for i in 0..bits.width {
+            let s = match (i < self.width, i < other.width) {
+                (true, true) => self.get(i).resolve(other.get(i)),
+                (true, false) => self.get(i),
+                _ => other.get(i),
+            };
+            bits.set(i, s);
+        }
+        bits
+    }
+}
+
+impl From<State> for Bits {
+    fn from(s: State) -> Self {
+        let mut bits = Bits::undefined(1);
+        bits.set(0, s);
+        bits
+    }
+}
+
+/// Shows the symbols of all bits, the most significant bit first.
+impl fmt::Display for Bits {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for i in (0..self.width).rev() {
+            write!(f, "{}", self.get(i).symbol())?;
+        }
+        Ok(())
+    }
+}
+
+/// An opaque color, every component within `0..=1`.
+#[repr(C)]
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub struct Color {
+    pub r: f32,
+    pub g: f32,
+    pub b: f32,
+}
+
+impl Color {
+    pub const BLACK: Color = Color::new(0.0, 0.0, 0.0);
+    pub const WHITE: Color = Color::new(1.0, 1.0, 1.0);
+    pub const GRAY: Color = Color::new(0.6, 0.6, 0.6);
+    pub const RED: Color = Color::new(0.9, 0.1, 0.1);
+    pub const GREEN: Color = Color::new(0.2, 0.8, 0.2);
+
+    pub const fn new(r: f32, g: f32, b: f32) -> Self {
+        Self { r, g, b }
+    }
+}
+
+/// A WebAssembly value type.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum ValType {
+    I32,
+    I64,
+    F32,
+    F64,
+}
+
+/// The name and type of an imported or exported function.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub struct Signature {
+    pub name: &'static str,
+    pub params: &'static [ValType],
+    pub results: &'static [ValType],
+}
+
+const fn sig(name: &'static str, params: &'static [ValType], results: &'static [ValType]) -> Signature {
+    Signature { name, params, results }
+}
+
+use ValType::{I32, I64, F32};
+
+/// The module all host functions are imported from.
+pub const IMPORT_MODULE: &str = "env";
+
+/// The functions the host provides to all modules.
+pub const IMPORTS: [Signature; 5] = [
+    sig("draw_rectangle", &[F32, F32, F32, F32, F32, F32, F32], &[]),
+    sig("draw_circle", &[F32, F32, F32, F32, F32, F32], &[]),
+    sig("draw_circle_lines", &[F32, F32, F32, F32, F32, F32, F32], &[]),
+    sig("draw_line", &[F32, F32, F32, F32, F32, F32, F32, F32], &[]),
+    sig("read_picked_file", &[I32, I32], &[I32]),
+];
+
+/// The functions every module must export.
+pub const EXPORTS: [Signature; 3] = [
+    sig("width", &[], &[F32]),
+    sig("height", &[], &[F32]),
+    sig("draw", &[F32, F32, F32], &[]),
+];
+
+/// The functions a module may export to talk to the simulator,
+/// see the `Pins` section of the README.
+pub const PIN_EXPORTS: [Signature; 24] = [
+    sig("get_inputs_nr", &[], &[I32]),
+    sig("set_inputs_nr", &[I32], &[]),
+    sig("get_outputs_nr", &[], &[I32]),
+    sig("set_input", &[I32, I32], &[]),
+    sig("get_output", &[I32], &[I32]),
+    sig("get_input_width", &[I32], &[I32]),
+    sig("get_output_width", &[I32], &[I32]),
+    sig("set_input_bits", &[I32, I64], &[]),
+    sig("get_output_bits", &[I32], &[I64]),
+    sig("update", &[], &[]),
+    sig("get_delay", &[], &[I32]),
+    sig("get_period", &[], &[I32]),
+    sig("rising_edge", &[I32], &[]),
+    sig("tick", &[I64], &[]),
+    sig("on_press", &[F32, F32], &[]),
+    sig("on_release", &[F32, F32], &[]),
+    sig("set_bits", &[I32], &[]),
+    sig("get_bits", &[], &[I32]),
+    sig("set_data_bits", &[I32], &[]),
+    sig("get_data_bits", &[], &[I32]),
+    sig("mem_size", &[], &[I32]),
+    sig("mem_read", &[I32], &[I32]),
+    sig("mem_write", &[I32, I32], &[]),
+    sig("load_contents", &[], &[I32]),
+];
+
+/// The module the optional WASI imports are provided by.
+pub const WASI_MODULE: &str = "wasi_snapshot_preview1";
+
+/// The subset of WASI the host provides, enough for the start-up code
+/// of C, Zig or AssemblyScript modules.
+///
+/// There are no preopened directories and no sockets, so modules
+/// can't access the file system or the network.
+pub const WASI_IMPORTS: [Signature; 15] = [
+    sig("args_get", &[I32, I32], &[I32]),
+    sig("args_sizes_get", &[I32, I32], &[I32]),
+    sig("environ_get", &[I32, I32], &[I32]),
+    sig("environ_sizes_get", &[I32, I32], &[I32]),
+    sig("clock_time_get", &[I32, I64, I32], &[I32]),
+    sig("fd_close", &[I32], &[I32]),
+    sig("fd_fdstat_get", &[I32, I32], &[I32]),
+    sig("fd_prestat_get", &[I32, I32], &[I32]),
+    sig("fd_prestat_dir_name", &[I32, I32, I32], &[I32]),
+    sig("fd_read", &[I32, I32, I32, I32], &[I32]),
+    sig("fd_seek", &[I32, I64, I32, I32], &[I32]),
+    sig("fd_write", &[I32, I32, I32, I32], &[I32]),
+    sig("proc_exit", &[I32], &[]),
+    sig("random_get", &[I32, I32], &[I32]),
+    sig("sched_yield", &[], &[I32]),
+];
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn state_abi_test() {
+        for s in [State::Undefined, State::Low, State::High, State::HighImpedance, State::Conflict] {
+            assert_eq!(s, State::from(i32::from(s)));
+        }
+        assert_eq!(2, i32::from(State::High));
+        assert_eq!(State::Undefined, State::from(42));
+        assert!(PIN_EXPORTS.iter().chain(EXPORTS.iter()).all(|e| !IMPORTS.contains(e)));
+    }
+}
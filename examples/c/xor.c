@@ -0,0 +1,71 @@
+// An XOR gate written in C.
+//
+// Build it with the WASI SDK (https://github.com/WebAssembly/wasi-sdk)
+// as a reactor, so the C runtime is set up by `_initialize`:
+//
+//     clang --target=wasm32-wasi -mexec-model=reactor -O2 -o xor_c.wasm examples/c/xor.c
+//
+// The C library imports a few `wasi_snapshot_preview1` functions,
+// which the host provides in a sandbox (see `src/wasi.rs`).
+
+#include <stdint.h>
+#include <stdio.h>
+
+#define IMPORT(name) __attribute__((import_module("env"), import_name(#name)))
+#define EXPORT(name) __attribute__((export_name(#name)))
+
+IMPORT(draw_rectangle) void draw_rectangle(float x, float y, float w, float h, float r, float g, float b);
+IMPORT(draw_circle) void draw_circle(float x, float y, float rad, float r, float g, float b);
+
+enum State { UNDEFINED, LOW, HIGH, HIGH_IMPEDANCE, CONFLICT };
+
+#define H1 10.0f
+
+static int32_t inputs[2] = { UNDEFINED, UNDEFINED };
+static int32_t output = UNDEFINED;
+
+EXPORT(width) float width(void) { return 8.0f * H1; }
+EXPORT(height) float height(void) { return 6.5f * H1; }
+
+EXPORT(draw) void draw(float x, float y, float rotation) {
+    (void) rotation;
+    draw_rectangle(x, y, 4.75f * H1, 6.5f * H1, 0.0f, 0.0f, 0.0f);
+    draw_circle(x + 4.75f * H1, y + 3.25f * H1, 3.25f * H1, 0.0f, 0.0f, 0.0f);
+    draw_rectangle(x + 0.5f * H1, y + 0.5f * H1, 3.75f * H1, 5.5f * H1, 1.0f, 1.0f, 1.0f);
+    draw_circle(x + 4.75f * H1, y + 3.25f * H1, 2.75f * H1, 1.0f, 1.0f, 1.0f);
+}
+
+EXPORT(get_inputs_nr) int32_t get_inputs_nr(void) { return 2; }
+EXPORT(get_outputs_nr) int32_t get_outputs_nr(void) { return 1; }
+
+EXPORT(set_input) void set_input(int32_t i, int32_t s) {
+    if (i >= 0 && i < 2) {
+        inputs[i] = s;
+    }
+}
+
+EXPORT(get_output) int32_t get_output(int32_t i) {
+    return i == 0 ? output : UNDEFINED;
+}
+
+// Same as the XOR of `megs-abi` (and `modules/xor`): a conflict at
+// either input propagates, a floating (high impedance) input reads
+// as undefined.
+EXPORT(update) void update(void) {
+    int32_t a = inputs[0], b = inputs[1];
+
+    if (a == CONFLICT || b == CONFLICT) {
+        output = CONFLICT;
+    } else if ((a == LOW || a == HIGH) && (b == LOW || b == HIGH)) {
+        output = a != b ? HIGH : LOW;
+    } else {
+        output = UNDEFINED;
+    }
+}
+
+EXPORT(get_delay) int32_t get_delay(void) { return 1; }
+
+EXPORT(on_press) void on_press(float x, float y) {
+    // Ends up on the host's stderr, prefixed with the module name.
+    printf("xor: pressed at (%.0f, %.0f), output is %d\n", x, y, output);
+}
@@ -0,0 +1,65 @@
+use megs_sdk::{Component, Layout, Metadata, Parameterised, Pins, draw};
+
+/// The smallest and largest supported width.
+pub const BITS_MIN: usize = 1;
+pub const BITS_MAX: usize = 16;
+
+/// A ripple carry adder of two `n` bit operands.
+///
+/// Inputs: `0..n` = A, `n..2n` = B, `2n` = carry in.
+/// Outputs: `0..n` = sum, `n` = carry out.
+pub struct Adder {
+    bits: usize,
+}
+
+impl Default for Adder {
+    fn default() -> Self {
+        Self { bits: 4 }
+    }
+}
+
+impl Component for Adder {
+    fn metadata() -> Metadata {
+        Metadata::default()
+    }
+
+    fn layout(&self, _inputs: usize) -> Layout {
+        let n = self.bits;
+        Layout::new(draw::BLOCK_WIDTH, draw::block_height(2 * n + 1, n + 1), 2 * n + 1, n + 1)
+    }
+
+    fn update(&mut self, pins: &mut Pins) {
+        let n = self.bits;
+        // Add bit by bit, so the lower bits of the sum stay defined
+        // even if a higher bit of an operand is undefined.
+        let mut carry = pins.input_value(2 * n..2 * n + 1);
+        for i in 0..n {
+            let s = pins.input_value(i..i + 1)
+                .zip(pins.input_value(n + i..n + i + 1))
+                .zip(carry)
+                .map(|((a, b), c)| a + b + c);
+            pins.set_output_value(i..i + 1, s);
+            carry = s.map(|s| s >> 1);
+        }
+        pins.set_output_value(n..n + 1, carry);
+    }
+
+    /// Draw a box with one stub per pin.
+    fn draw(&self, pins: &Pins, x: f32, y: f32, _rotation: f32) {
+        draw::block(pins, x, y, None);
+    }
+}
+
+impl Parameterised for Adder {
+    fn bits(&self) -> usize {
+        self.bits
+    }
+
+    fn set_bits(&mut self, n: usize) {
+        if (BITS_MIN..=BITS_MAX).contains(&n) {
+            self.bits = n;
+        }
+    }
+}
+
+megs_sdk::export_component!(Adder, bits);
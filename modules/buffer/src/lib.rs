@@ -0,0 +1,25 @@
+use megs_sdk::{Component, Metadata, Pins, State, draw};
+
+/// A buffer, passing its input through after its delay.
+#[derive(Default)]
+pub struct Buffer;
+
+impl Component for Buffer {
+    fn metadata() -> Metadata {
+        Metadata {
+            width: draw::GATE_WIDTH,
+            height: draw::GATE_HEIGHT,
+            ..Metadata::default()
+        }
+    }
+
+    fn evaluate(&mut self, inputs: &[State], outputs: &mut [State]) {
+        outputs[0] = inputs[0];
+    }
+
+    fn draw(&self, pins: &Pins, x: f32, y: f32, _rotation: f32) {
+        draw::gate(pins, x, y, "1", false);
+    }
+}
+
+megs_sdk::export_component!(Buffer);
@@ -0,0 +1,85 @@
+use megs_sdk::{Clock, Component, Metadata, Pins, State, draw::{self, Color}};
+
+pub const H1: f32 = 10.0;
+pub const WIDTH: f32 = H1 * 6.0;
+pub const HEIGHT: f32 = H1 * 4.0;
+
+/// The shortest and longest supported period in units of simulation time.
+pub const PERIOD_MIN: u32 = 2;
+pub const PERIOD_MAX: u32 = 1024;
+
+/// A clock, `Low` during the first and `High` during the second half
+/// of each period.
+pub struct Oscillator {
+    /// The length of a full Low/High cycle, i.e. `1 / frequency`.
+    period: u32,
+    /// The current simulation time as reported by the host.
+    now: u64,
+}
+
+impl Default for Oscillator {
+    fn default() -> Self {
+        Self { period: 16, now: 0 }
+    }
+}
+
+impl Component for Oscillator {
+    fn metadata() -> Metadata {
+        Metadata {
+            width: WIDTH,
+            height: HEIGHT,
+            inputs: (0, 0),
+            outputs: 1,
+            ..Metadata::default()
+        }
+    }
+
+    fn update(&mut self, pins: &mut Pins) {
+        let half = (self.period / 2) as u64;
+        pins.set_output(0, State::from((self.now / half) % 2 == 1));
+    }
+
+    fn tick(&mut self, pins: &mut Pins, now: u64) {
+        self.now = now;
+        self.update(pins);
+    }
+
+    /// Double the period on every click, starting over at the
+    /// shortest period once the longest one is exceeded.
+    fn on_click(&mut self, pins: &mut Pins, _x: f32, _y: f32) {
+        self.period = if self.period * 2 > PERIOD_MAX { PERIOD_MIN } else { self.period * 2 };
+        self.update(pins);
+    }
+
+    fn draw(&self, pins: &Pins, x: f32, y: f32, _rotation: f32) {
+        draw::rectangle(x, y, WIDTH, HEIGHT, Color::BLACK);
+        draw::rectangle(x + 0.5 * H1, y + 0.5 * H1, WIDTH - H1, HEIGHT - H1, Color::WHITE);
+
+        // A square wave, the bar below shows the period on a log scale.
+        let (lo, hi) = (y + 2.5 * H1, y + 1.2 * H1);
+        let xs = [x + H1, x + 2.0 * H1, x + 3.0 * H1, x + 4.0 * H1, x + 5.0 * H1];
+        for i in 0..4 {
+            let level = if i % 2 == 0 { lo } else { hi };
+            draw::line(xs[i], level, xs[i + 1], level, 1.5, Color::BLACK);
+            if i < 3 {
+                draw::line(xs[i + 1], lo, xs[i + 1], hi, 1.5, Color::BLACK);
+            }
+        }
+
+        let steps = (self.period.trailing_zeros() as f32) / (PERIOD_MAX.trailing_zeros() as f32);
+        draw::rectangle(x + H1, y + 3.0 * H1, steps * (WIDTH - 2.0 * H1), 0.3 * H1, Color::new(0.2, 0.2, 0.8));
+
+        if pins.output(0) == State::High {
+            draw::circle(x + WIDTH - H1, y + H1, 0.3 * H1, Color::GREEN);
+        }
+        draw::line(x + WIDTH, y + 2.0 * H1, x + WIDTH + H1, y + 2.0 * H1, 2.0, Color::BLACK);
+    }
+}
+
+impl Clock for Oscillator {
+    fn period(&self) -> u32 {
+        self.period
+    }
+}
+
+megs_sdk::export_component!(Oscillator, clock);
@@ -0,0 +1,63 @@
+use core::cmp::Ordering;
+
+use megs_sdk::{Component, Layout, Metadata, Parameterised, Pins, State, draw};
+
+/// The smallest and largest supported width.
+pub const BITS_MIN: usize = 1;
+pub const BITS_MAX: usize = 16;
+
+/// A magnitude comparator for two unsigned `n` bit numbers.
+///
+/// Inputs: `0..n` = A, `n..2n` = B.
+/// Outputs: `0` = A < B, `1` = A = B, `2` = A > B.
+pub struct Comparator {
+    bits: usize,
+}
+
+impl Default for Comparator {
+    fn default() -> Self {
+        Self { bits: 4 }
+    }
+}
+
+impl Component for Comparator {
+    fn metadata() -> Metadata {
+        Metadata::default()
+    }
+
+    fn layout(&self, _inputs: usize) -> Layout {
+        let n = self.bits;
+        Layout::new(draw::BLOCK_WIDTH, draw::block_height(2 * n, 3), 2 * n, 3)
+    }
+
+    fn update(&mut self, pins: &mut Pins) {
+        let n = self.bits;
+        let r = pins.input_value(0..n).zip(pins.input_value(n..2 * n)).map(|(a, b)| a.cmp(&b));
+        for (i, o) in [Ordering::Less, Ordering::Equal, Ordering::Greater].into_iter().enumerate() {
+            let state = match r {
+                Some(r) => State::from(r == o),
+                None => State::Undefined,
+            };
+            pins.set_output(i, state);
+        }
+    }
+
+    /// Draw a box with one stub per pin.
+    fn draw(&self, pins: &Pins, x: f32, y: f32, _rotation: f32) {
+        draw::block(pins, x, y, None);
+    }
+}
+
+impl Parameterised for Comparator {
+    fn bits(&self) -> usize {
+        self.bits
+    }
+
+    fn set_bits(&mut self, n: usize) {
+        if (BITS_MIN..=BITS_MAX).contains(&n) {
+            self.bits = n;
+        }
+    }
+}
+
+megs_sdk::export_component!(Comparator, bits);
@@ -0,0 +1,80 @@
+use megs_sdk::{Component, Layout, Metadata, Parameterised, Pins, State, draw};
+
+/// The smallest and largest supported width.
+pub const BITS_MIN: usize = 1;
+pub const BITS_MAX: usize = 16;
+
+/// A binary counter of `n` bits.
+///
+/// Inputs: `0` = EN, `1` = CLK, `2` = R (asynchronous reset).
+/// Outputs: `0..n` = Q (`0` is the least significant bit), `n` = carry,
+/// which is High while all bits are set and EN is High.
+pub struct Counter {
+    bits: usize,
+    /// The current count, `None` if undefined.
+    count: Option<u32>,
+}
+
+impl Default for Counter {
+    fn default() -> Self {
+        Self { bits: 4, count: None }
+    }
+}
+
+impl Component for Counter {
+    fn metadata() -> Metadata {
+        Metadata::default()
+    }
+
+    fn layout(&self, _inputs: usize) -> Layout {
+        let n = self.bits;
+        Layout::new(draw::BLOCK_WIDTH, draw::block_height(3, n + 1), 3, n + 1)
+    }
+
+    /// Count up on every rising clock edge while EN is High.
+    fn rising_edge(&mut self, pins: &mut Pins, input: usize) {
+        if input != 1 || pins.input(2) != State::Low {
+            return;
+        }
+        self.count = match (pins.input(0), self.count) {
+            (State::Low, c) => c,
+            (State::High, Some(c)) => Some((c + 1) % (1 << self.bits)),
+            _ => None,
+        };
+    }
+
+    fn update(&mut self, pins: &mut Pins) {
+        let n = self.bits;
+        match pins.input(2) {
+            State::High => self.count = Some(0),
+            State::Low => {},
+            _ => self.count = None,
+        }
+        pins.set_output_value(0..n, self.count);
+        let full = match self.count {
+            Some(c) => State::from(c == (1 << n) - 1),
+            None => State::Undefined,
+        };
+        pins.set_output(n, full & pins.input(0));
+    }
+
+    fn draw(&self, pins: &Pins, x: f32, y: f32, _rotation: f32) {
+        draw::block(pins, x, y, Some(1));
+    }
+}
+
+impl Parameterised for Counter {
+    fn bits(&self) -> usize {
+        self.bits
+    }
+
+    /// Change the width, the count is cut to the new width.
+    fn set_bits(&mut self, n: usize) {
+        if (BITS_MIN..=BITS_MAX).contains(&n) {
+            self.bits = n;
+            self.count = self.count.map(|c| c % (1 << n));
+        }
+    }
+}
+
+megs_sdk::export_component!(Counter, bits);
@@ -0,0 +1,47 @@
+use megs_sdk::{Component, Metadata, Pins, State, draw};
+
+/// Inputs: `0` = D, `1` = E (enable).
+/// Outputs: `0` = Q, `1` = !Q.
+pub const INPUTS_NR: usize = 2;
+pub const OUTPUTS_NR: usize = 2;
+
+/// A gated D latch.
+#[derive(Default)]
+pub struct DLatch {
+    /// The stored bit.
+    q: State,
+}
+
+impl Component for DLatch {
+    fn metadata() -> Metadata {
+        Metadata {
+            width: draw::BLOCK_WIDTH,
+            height: draw::block_height(INPUTS_NR, OUTPUTS_NR),
+            inputs: (INPUTS_NR, INPUTS_NR),
+            outputs: OUTPUTS_NR,
+            ..Metadata::default()
+        }
+    }
+
+    /// Transparent while E is High, holds the state while E is Low.
+    fn update(&mut self, pins: &mut Pins) {
+        let d = if pins.input(0).is_level() { pins.input(0) } else { State::Undefined };
+        match pins.input(1) {
+            State::High => self.q = d,
+            State::Low => {},
+            _ => {
+                if d != self.q {
+                    self.q = State::Undefined;
+                }
+            },
+        }
+        pins.set_output(0, self.q);
+        pins.set_output(1, !self.q);
+    }
+
+    fn draw(&self, pins: &Pins, x: f32, y: f32, _rotation: f32) {
+        draw::block(pins, x, y, None);
+    }
+}
+
+megs_sdk::export_component!(DLatch);
@@ -0,0 +1,62 @@
+use megs_sdk::{Component, Layout, Metadata, Parameterised, Pins, State, draw};
+
+/// The smallest and largest supported width.
+pub const BITS_MIN: usize = 1;
+pub const BITS_MAX: usize = 4;
+
+/// A decoder of `n` address bits.
+///
+/// Inputs: `0..n` = address, `n` = enable.
+/// Outputs: `0..2^n`, only the addressed one is High while enabled.
+pub struct Decoder {
+    bits: usize,
+}
+
+impl Default for Decoder {
+    fn default() -> Self {
+        Self { bits: 2 }
+    }
+}
+
+impl Component for Decoder {
+    fn metadata() -> Metadata {
+        Metadata::default()
+    }
+
+    fn layout(&self, _inputs: usize) -> Layout {
+        let n = self.bits;
+        Layout::new(draw::BLOCK_WIDTH, draw::block_height(n + 1, 1 << n), n + 1, 1 << n)
+    }
+
+    fn update(&mut self, pins: &mut Pins) {
+        let n = self.bits;
+        let a = pins.input_value(0..n);
+        for i in 0..pins.outputs_nr() {
+            let state = match (pins.input(n), a) {
+                (State::Low, _) => State::Low,
+                (State::High, Some(a)) => State::from(a as usize == i),
+                _ => State::Undefined,
+            };
+            pins.set_output(i, state);
+        }
+    }
+
+    /// Draw a box with one stub per pin.
+    fn draw(&self, pins: &Pins, x: f32, y: f32, _rotation: f32) {
+        draw::block(pins, x, y, None);
+    }
+}
+
+impl Parameterised for Decoder {
+    fn bits(&self) -> usize {
+        self.bits
+    }
+
+    fn set_bits(&mut self, n: usize) {
+        if (BITS_MIN..=BITS_MAX).contains(&n) {
+            self.bits = n;
+        }
+    }
+}
+
+megs_sdk::export_component!(Decoder, bits);
@@ -0,0 +1,61 @@
+use megs_sdk::{Component, Layout, Metadata, Parameterised, Pins, State, draw};
+
+/// The smallest and largest supported width.
+pub const BITS_MIN: usize = 1;
+pub const BITS_MAX: usize = 4;
+
+/// A demultiplexer with `n` select inputs.
+///
+/// Inputs: `0` = data, `1..=n` = select.
+/// Outputs: `0..2^n`, all Low except the selected one, which follows the data input.
+pub struct Demux {
+    bits: usize,
+}
+
+impl Default for Demux {
+    fn default() -> Self {
+        Self { bits: 1 }
+    }
+}
+
+impl Component for Demux {
+    fn metadata() -> Metadata {
+        Metadata::default()
+    }
+
+    fn layout(&self, _inputs: usize) -> Layout {
+        let n = self.bits;
+        Layout::new(draw::BLOCK_WIDTH, draw::block_height(1 + n, 1 << n), 1 + n, 1 << n)
+    }
+
+    fn update(&mut self, pins: &mut Pins) {
+        let s = pins.input_value(1..1 + self.bits);
+        for i in 0..pins.outputs_nr() {
+            let state = match s {
+                Some(s) if s as usize == i => pins.input(0),
+                Some(_) => State::Low,
+                None => State::Undefined,
+            };
+            pins.set_output(i, state);
+        }
+    }
+
+    /// Draw a box with one stub per pin.
+    fn draw(&self, pins: &Pins, x: f32, y: f32, _rotation: f32) {
+        draw::block(pins, x, y, None);
+    }
+}
+
+impl Parameterised for Demux {
+    fn bits(&self) -> usize {
+        self.bits
+    }
+
+    fn set_bits(&mut self, n: usize) {
+        if (BITS_MIN..=BITS_MAX).contains(&n) {
+            self.bits = n;
+        }
+    }
+}
+
+megs_sdk::export_component!(Demux, bits);
@@ -0,0 +1,49 @@
+use megs_sdk::{Component, Metadata, Pins, State, draw, set_reset};
+
+/// Inputs: `0` = D, `1` = CLK, `2` = S, `3` = R.
+/// Outputs: `0` = Q, `1` = !Q.
+///
+/// S and R are asynchronous and active High.
+pub const INPUTS_NR: usize = 4;
+pub const OUTPUTS_NR: usize = 2;
+
+/// A D flip-flop, latching D on the rising edge of CLK.
+#[derive(Default)]
+pub struct Dff {
+    /// The stored bit.
+    q: State,
+}
+
+impl Component for Dff {
+    fn metadata() -> Metadata {
+        Metadata {
+            width: draw::BLOCK_WIDTH,
+            height: draw::block_height(INPUTS_NR, OUTPUTS_NR),
+            inputs: (INPUTS_NR, INPUTS_NR),
+            outputs: OUTPUTS_NR,
+            ..Metadata::default()
+        }
+    }
+
+    fn rising_edge(&mut self, pins: &mut Pins, input: usize) {
+        if input != 1 || set_reset(pins.input(2), pins.input(3)).is_some() {
+            return;
+        }
+        let d = pins.input(0);
+        self.q = if d.is_level() { d } else { State::Undefined };
+    }
+
+    fn update(&mut self, pins: &mut Pins) {
+        if let Some(q) = set_reset(pins.input(2), pins.input(3)) {
+            self.q = q;
+        }
+        pins.set_output(0, self.q);
+        pins.set_output(1, !self.q);
+    }
+
+    fn draw(&self, pins: &Pins, x: f32, y: f32, _rotation: f32) {
+        draw::block(pins, x, y, Some(1));
+    }
+}
+
+megs_sdk::export_component!(Dff);
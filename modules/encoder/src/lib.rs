@@ -0,0 +1,75 @@
+use megs_sdk::{Component, Layout, Metadata, Parameterised, Pins, State, draw};
+
+/// The smallest and largest supported width.
+pub const BITS_MIN: usize = 1;
+pub const BITS_MAX: usize = 4;
+
+/// A priority encoder of `2^n` inputs.
+///
+/// Inputs: `0..2^n`.
+/// Outputs: `0..n` = index of the highest High input, `n` = valid,
+/// which is High if any input is High.
+pub struct Encoder {
+    bits: usize,
+}
+
+impl Default for Encoder {
+    fn default() -> Self {
+        Self { bits: 2 }
+    }
+}
+
+impl Component for Encoder {
+    fn metadata() -> Metadata {
+        Metadata::default()
+    }
+
+    fn layout(&self, _inputs: usize) -> Layout {
+        let n = self.bits;
+        Layout::new(draw::BLOCK_WIDTH, draw::block_height(1 << n, n + 1), 1 << n, n + 1)
+    }
+
+    fn update(&mut self, pins: &mut Pins) {
+        let n = self.bits;
+        // The index of the highest High input, `Some(None)` if all are Low.
+        let mut index = Some(None);
+        for i in (0..pins.inputs_nr()).rev() {
+            match pins.input(i) {
+                State::High => {
+                    index = Some(Some(i as u32));
+                    break;
+                },
+                State::Low => {},
+                _ => {
+                    index = None;
+                    break;
+                },
+            }
+        }
+        pins.set_output_value(0..n, index.map(|v| v.unwrap_or(0)));
+        let valid = match index {
+            Some(i) => State::from(i.is_some()),
+            None => State::Undefined,
+        };
+        pins.set_output(n, valid);
+    }
+
+    /// Draw a box with one stub per pin.
+    fn draw(&self, pins: &Pins, x: f32, y: f32, _rotation: f32) {
+        draw::block(pins, x, y, None);
+    }
+}
+
+impl Parameterised for Encoder {
+    fn bits(&self) -> usize {
+        self.bits
+    }
+
+    fn set_bits(&mut self, n: usize) {
+        if (BITS_MIN..=BITS_MAX).contains(&n) {
+            self.bits = n;
+        }
+    }
+}
+
+megs_sdk::export_component!(Encoder, bits);
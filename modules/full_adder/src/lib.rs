@@ -0,0 +1,37 @@
+use megs_sdk::{Component, Metadata, Pins, draw};
+
+/// Inputs: `0` = A, `1` = B, `2` = carry in.
+/// Outputs: `0` = sum, `1` = carry out.
+pub const INPUTS_NR: usize = 3;
+pub const OUTPUTS_NR: usize = 2;
+
+/// A full adder, adding two bits and a carry.
+#[derive(Default)]
+pub struct FullAdder;
+
+impl Component for FullAdder {
+    fn metadata() -> Metadata {
+        Metadata {
+            width: draw::BLOCK_WIDTH,
+            height: draw::block_height(INPUTS_NR, OUTPUTS_NR),
+            inputs: (INPUTS_NR, INPUTS_NR),
+            outputs: OUTPUTS_NR,
+            ..Metadata::default()
+        }
+    }
+
+    fn update(&mut self, pins: &mut Pins) {
+        let mut sum = Some(0);
+        for i in 0..INPUTS_NR {
+            sum = sum.zip(pins.input_value(i..i + 1)).map(|(s, v)| s + v);
+        }
+        pins.set_output_value(0..2, sum);
+    }
+
+    /// Draw a box with one stub per pin.
+    fn draw(&self, pins: &Pins, x: f32, y: f32, _rotation: f32) {
+        draw::block(pins, x, y, None);
+    }
+}
+
+megs_sdk::export_component!(FullAdder);
@@ -0,0 +1,34 @@
+use megs_sdk::{Component, Metadata, Pins, draw};
+
+/// Inputs: `0` = A, `1` = B.
+/// Outputs: `0` = sum, `1` = carry.
+pub const INPUTS_NR: usize = 2;
+pub const OUTPUTS_NR: usize = 2;
+
+/// A half adder, adding two bits.
+#[derive(Default)]
+pub struct HalfAdder;
+
+impl Component for HalfAdder {
+    fn metadata() -> Metadata {
+        Metadata {
+            width: draw::BLOCK_WIDTH,
+            height: draw::block_height(INPUTS_NR, OUTPUTS_NR),
+            inputs: (INPUTS_NR, INPUTS_NR),
+            outputs: OUTPUTS_NR,
+            ..Metadata::default()
+        }
+    }
+
+    fn update(&mut self, pins: &mut Pins) {
+        let sum = pins.input_value(0..1).zip(pins.input_value(1..2)).map(|(a, b)| a + b);
+        pins.set_output_value(0..2, sum);
+    }
+
+    /// Draw a box with one stub per pin.
+    fn draw(&self, pins: &Pins, x: f32, y: f32, _rotation: f32) {
+        draw::block(pins, x, y, None);
+    }
+}
+
+megs_sdk::export_component!(HalfAdder);
@@ -0,0 +1,34 @@
+use megs_sdk::{Component, Metadata, Pins, draw::{self, Color}};
+
+pub const H1: f32 = 10.0;
+pub const WIDTH: f32 = H1 * 4.0;
+pub const HEIGHT: f32 = H1 * 6.0;
+
+/// Shows a 4 bit number as hexadecimal digit, input `0` is the least
+/// significant bit.
+#[derive(Default)]
+pub struct HexDisplay;
+
+impl Component for HexDisplay {
+    fn metadata() -> Metadata {
+        Metadata {
+            width: WIDTH,
+            height: HEIGHT,
+            inputs: (4, 4),
+            outputs: 0,
+            ..Metadata::default()
+        }
+    }
+
+    fn draw(&self, pins: &Pins, x: f32, y: f32, _rotation: f32) {
+        draw::rectangle(x, y, WIDTH, HEIGHT, Color::BLACK);
+        draw::rectangle(x + 0.5 * H1, y + 0.5 * H1, WIDTH - H1, HEIGHT - H1, Color::WHITE);
+
+        match pins.input_value(0..pins.inputs_nr()) {
+            Some(value) => draw::digit(draw::DIGITS[value as usize & 0xf], x + H1, y + H1, false),
+            None => draw::digit(draw::DASH, x + H1, y + H1, true),
+        }
+    }
+}
+
+megs_sdk::export_component!(HexDisplay);
@@ -0,0 +1,54 @@
+use megs_sdk::{Component, Metadata, Pins, State, draw, set_reset};
+
+/// Inputs: `0` = J, `1` = K, `2` = CLK, `3` = S, `4` = R.
+/// Outputs: `0` = Q, `1` = !Q.
+///
+/// S and R are asynchronous and active High.
+pub const INPUTS_NR: usize = 5;
+pub const OUTPUTS_NR: usize = 2;
+
+/// A JK flip-flop.
+#[derive(Default)]
+pub struct Jkff {
+    /// The stored bit.
+    q: State,
+}
+
+impl Component for Jkff {
+    fn metadata() -> Metadata {
+        Metadata {
+            width: draw::BLOCK_WIDTH,
+            height: draw::block_height(INPUTS_NR, OUTPUTS_NR),
+            inputs: (INPUTS_NR, INPUTS_NR),
+            outputs: OUTPUTS_NR,
+            ..Metadata::default()
+        }
+    }
+
+    fn rising_edge(&mut self, pins: &mut Pins, input: usize) {
+        if input != 2 || set_reset(pins.input(3), pins.input(4)).is_some() {
+            return;
+        }
+        self.q = match (pins.input(0), pins.input(1)) {
+            (State::Low, State::Low) => self.q,
+            (State::High, State::Low) => State::High,
+            (State::Low, State::High) => State::Low,
+            (State::High, State::High) => !self.q,
+            _ => State::Undefined,
+        };
+    }
+
+    fn update(&mut self, pins: &mut Pins) {
+        if let Some(q) = set_reset(pins.input(3), pins.input(4)) {
+            self.q = q;
+        }
+        pins.set_output(0, self.q);
+        pins.set_output(1, !self.q);
+    }
+
+    fn draw(&self, pins: &Pins, x: f32, y: f32, _rotation: f32) {
+        draw::block(pins, x, y, Some(2));
+    }
+}
+
+megs_sdk::export_component!(Jkff);
@@ -0,0 +1,39 @@
+use megs_sdk::{Component, Metadata, Pins, State, draw::{self, Color}};
+
+pub const H1: f32 = 10.0;
+pub const WIDTH: f32 = H1 * 4.0;
+pub const HEIGHT: f32 = H1 * 4.0;
+
+/// A LED showing the state of its input.
+#[derive(Default)]
+pub struct Led;
+
+impl Component for Led {
+    fn metadata() -> Metadata {
+        Metadata {
+            width: WIDTH,
+            height: HEIGHT,
+            inputs: (1, 1),
+            outputs: 0,
+            ..Metadata::default()
+        }
+    }
+
+    fn draw(&self, pins: &Pins, x: f32, y: f32, _rotation: f32) {
+        let (cx, cy) = (x + 2.0 * H1, y + 2.0 * H1);
+        draw::line(x - H1, cy, x + 0.5 * H1, cy, 2.0, Color::BLACK);
+        match pins.input(0) {
+            State::High => draw::circle(cx, cy, 1.5 * H1, Color::new(1.0, 0.1, 0.1)),
+            State::Low => draw::circle(cx, cy, 1.5 * H1, Color::new(0.35, 0.05, 0.05)),
+            // Anything but a level is shown as a hatched, orange LED.
+            _ => {
+                draw::circle(cx, cy, 1.5 * H1, Color::new(1.0, 0.6, 0.0));
+                draw::line(cx - H1, cy - H1, cx + H1, cy + H1, 1.5, Color::BLACK);
+                draw::line(cx - H1, cy + H1, cx + H1, cy - H1, 1.5, Color::BLACK);
+            },
+        }
+        draw::circle_lines(cx, cy, 1.5 * H1, 2.0, Color::BLACK);
+    }
+}
+
+megs_sdk::export_component!(Led);
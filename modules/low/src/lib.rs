@@ -0,0 +1,35 @@
+use megs_sdk::{Component, Metadata, Pins, State, draw::{self, Color}};
+
+pub const H1: f32 = 10.0;
+pub const WIDTH: f32 = H1 * 4.0;
+pub const HEIGHT: f32 = H1 * 4.0;
+
+/// Always drives `Low`.
+#[derive(Default)]
+pub struct Low;
+
+impl Component for Low {
+    fn metadata() -> Metadata {
+        Metadata {
+            width: WIDTH,
+            height: HEIGHT,
+            inputs: (0, 0),
+            outputs: 1,
+            ..Metadata::default()
+        }
+    }
+
+    fn update(&mut self, pins: &mut Pins) {
+        pins.set_output(0, State::Low);
+    }
+
+    fn draw(&self, _pins: &Pins, x: f32, y: f32, _rotation: f32) {
+        draw::rectangle(x, y, WIDTH, HEIGHT, Color::BLACK);
+        draw::rectangle(x + 0.5 * H1, y + 0.5 * H1, WIDTH - H1, HEIGHT - H1, Color::WHITE);
+        // "0"
+        draw::circle_lines(x + 2.0 * H1, y + 2.0 * H1, H1, 2.0, Color::BLACK);
+        draw::line(x + WIDTH, y + 2.0 * H1, x + WIDTH + H1, y + 2.0 * H1, 2.0, Color::BLACK);
+    }
+}
+
+megs_sdk::export_component!(Low);
@@ -0,0 +1,73 @@
+use megs_sdk::{Component, Layout, Metadata, Parameterised, Pins, draw::{self, Color}};
+
+pub const H1: f32 = 10.0;
+
+/// The smallest and largest supported width of the bus.
+pub const BITS_MIN: usize = 2;
+pub const BITS_MAX: usize = 32;
+
+/// Merges single bits into a bus.
+///
+/// Inputs: `0..n` = the single bits, `0` is the least significant bit.
+/// Outputs: `0` = the bus.
+pub struct Merger {
+    bits: usize,
+}
+
+impl Default for Merger {
+    fn default() -> Self {
+        Self { bits: 8 }
+    }
+}
+
+impl Component for Merger {
+    fn metadata() -> Metadata {
+        Metadata::default()
+    }
+
+    fn layout(&self, _inputs: usize) -> Layout {
+        let n = self.bits;
+        Layout {
+            width: 4.0 * H1,
+            height: (n as f32 + 1.0) * H1,
+            inputs: vec![1; n],
+            outputs: vec![n],
+        }
+    }
+
+    fn update(&mut self, pins: &mut Pins) {
+        let mut bus = pins.output_bits(0);
+        for i in 0..self.bits {
+            bus.set(i, pins.input(i));
+        }
+        pins.set_output_bits(0, bus);
+    }
+
+    /// Draw one stub per bit on the left merging into the bus.
+    fn draw(&self, pins: &Pins, x: f32, y: f32, _rotation: f32) {
+        let h = (self.bits as f32 + 1.0) * H1;
+        let bus = pins.output_bits(0);
+        for i in 0..self.bits {
+            let iy = y + (i as f32 + 1.0) * H1;
+            draw::line(x, iy, x + 2.0 * H1, iy, 1.5, Color::BLACK);
+            draw::state(x + H1, iy, 0.2 * H1, bus.get(i));
+        }
+        draw::line(x + 2.0 * H1, y + H1, x + 2.0 * H1, y + h - H1, 4.0, Color::BLACK);
+        draw::line(x + 2.0 * H1, y + h / 2.0, x + 4.0 * H1, y + h / 2.0, 4.0, Color::BLACK);
+        draw::rectangle(x + 2.9 * H1, y + h / 2.0 - 0.3 * H1, 0.6 * H1, 0.6 * H1, Color::BLACK);
+    }
+}
+
+impl Parameterised for Merger {
+    fn bits(&self) -> usize {
+        self.bits
+    }
+
+    fn set_bits(&mut self, n: usize) {
+        if (BITS_MIN..=BITS_MAX).contains(&n) {
+            self.bits = n;
+        }
+    }
+}
+
+megs_sdk::export_component!(Merger, bits);
@@ -0,0 +1,58 @@
+use megs_sdk::{Component, Layout, Metadata, Parameterised, Pins, State, draw};
+
+/// The smallest and largest supported width.
+pub const BITS_MIN: usize = 1;
+pub const BITS_MAX: usize = 4;
+
+/// A multiplexer with `n` select inputs.
+///
+/// Inputs: `0..2^n` = data, `2^n..2^n + n` = select.
+/// Outputs: `0` = the selected data input.
+pub struct Mux {
+    bits: usize,
+}
+
+impl Default for Mux {
+    fn default() -> Self {
+        Self { bits: 1 }
+    }
+}
+
+impl Component for Mux {
+    fn metadata() -> Metadata {
+        Metadata::default()
+    }
+
+    fn layout(&self, _inputs: usize) -> Layout {
+        let n = self.bits;
+        Layout::new(draw::BLOCK_WIDTH, draw::block_height((1 << n) + n, 1), (1 << n) + n, 1)
+    }
+
+    fn update(&mut self, pins: &mut Pins) {
+        let d = 1 << self.bits;
+        let selected = match pins.input_value(d..d + self.bits) {
+            Some(s) => pins.input(s as usize),
+            None => State::Undefined,
+        };
+        pins.set_output(0, selected);
+    }
+
+    /// Draw a box with one stub per pin.
+    fn draw(&self, pins: &Pins, x: f32, y: f32, _rotation: f32) {
+        draw::block(pins, x, y, None);
+    }
+}
+
+impl Parameterised for Mux {
+    fn bits(&self) -> usize {
+        self.bits
+    }
+
+    fn set_bits(&mut self, n: usize) {
+        if (BITS_MIN..=BITS_MAX).contains(&n) {
+            self.bits = n;
+        }
+    }
+}
+
+megs_sdk::export_component!(Mux, bits);
@@ -0,0 +1,26 @@
+use megs_sdk::{Component, Metadata, Pins, State, draw};
+
+/// A NAND gate with two to eight inputs.
+#[derive(Default)]
+pub struct Nand;
+
+impl Component for Nand {
+    fn metadata() -> Metadata {
+        Metadata {
+            width: draw::GATE_WIDTH,
+            height: draw::GATE_HEIGHT,
+            inputs: (2, 8),
+            ..Metadata::default()
+        }
+    }
+
+    fn evaluate(&mut self, inputs: &[State], outputs: &mut [State]) {
+        outputs[0] = !inputs.iter().fold(State::High, |a, b| a & *b);
+    }
+
+    fn draw(&self, pins: &Pins, x: f32, y: f32, _rotation: f32) {
+        draw::gate(pins, x, y, "&", true);
+    }
+}
+
+megs_sdk::export_component!(Nand);
@@ -0,0 +1,66 @@
+use megs_sdk::{Component, Metadata, Pins, State, draw::{self, Color}};
+
+pub const H1: f32 = 10.0;
+pub const WIDTH: f32 = H1 * 10.0;
+pub const HEIGHT: f32 = H1 * 8.0;
+
+/// The largest supported number of inputs.
+pub const INPUTS_MAX: usize = 8;
+
+/// Shows up to eight inputs as number, input `0` is the least
+/// significant bit.
+#[derive(Default)]
+pub struct Probe;
+
+impl Component for Probe {
+    fn metadata() -> Metadata {
+        Metadata {
+            width: WIDTH,
+            height: HEIGHT,
+            inputs: (1, INPUTS_MAX),
+            initial_inputs: Some(4),
+            outputs: 0,
+            ..Metadata::default()
+        }
+    }
+
+    /// Shows the inputs as binary number (most significant bit left) and
+    /// as decimal number below. Undefined bits are orange and turn all
+    /// decimal digits into dashes.
+    fn draw(&self, pins: &Pins, x: f32, y: f32, _rotation: f32) {
+        draw::rectangle(x, y, WIDTH, HEIGHT, Color::BLACK);
+        draw::rectangle(x + 0.5 * H1, y + 0.5 * H1, WIDTH - H1, HEIGHT - H1, Color::WHITE);
+
+        let n = pins.inputs_nr();
+        for i in 0..n {
+            let bx = x + H1 + (INPUTS_MAX - 1 - i) as f32 * H1;
+            match pins.input(i) {
+                State::High => {
+                    draw::rectangle(bx, y + H1, 0.8 * H1, 1.5 * H1, Color::BLACK);
+                },
+                State::Low => {
+                    draw::rectangle(bx, y + H1, 0.8 * H1, 1.5 * H1, Color::BLACK);
+                    draw::rectangle(bx + 0.15 * H1, y + 1.15 * H1, 0.5 * H1, 1.2 * H1, Color::WHITE);
+                },
+                _ => {
+                    draw::rectangle(bx, y + H1, 0.8 * H1, 1.5 * H1, Color::new(1.0, 0.6, 0.0));
+                },
+            }
+        }
+
+        // Up to three decimal digits, right aligned.
+        let value = pins.input_value(0..n);
+        for d in 0..3 {
+            let dx = x + WIDTH - H1 - (d + 1) as f32 * (draw::DIGIT_WIDTH + 0.5 * H1);
+            match value {
+                None => draw::digit(draw::DASH, dx, y + 3.0 * H1, true),
+                Some(v) if d == 0 || v >= 10u32.pow(d as u32) => {
+                    draw::digit(draw::DIGITS[(v / 10u32.pow(d as u32) % 10) as usize], dx, y + 3.0 * H1, false);
+                },
+                Some(_) => {},
+            }
+        }
+    }
+}
+
+megs_sdk::export_component!(Probe);
@@ -0,0 +1,99 @@
+use megs_sdk::{Component, Layout, Memory, Metadata, Parameterised, Pins, State, draw};
+
+/// The smallest and largest supported number of address inputs.
+pub const BITS_MIN: usize = 1;
+pub const BITS_MAX: usize = 10;
+
+/// The smallest and largest supported number of bits per word.
+pub const DATA_BITS_MIN: usize = 1;
+pub const DATA_BITS_MAX: usize = 16;
+
+pub const WORDS_MAX: usize = 1 << BITS_MAX;
+
+/// A random-access memory.
+///
+/// Inputs: `0..n` = address, `n..n + m` = data, `n + m` = write enable,
+/// `n + m + 1` = CLK.
+/// Outputs: `0..m` = the addressed word.
+///
+/// The data is written on a rising clock edge while write enable is High.
+pub struct Ram {
+    bits: usize,
+    data_bits: usize,
+    /// The contents of the memory, one word per address.
+    contents: [u16; WORDS_MAX],
+}
+
+impl Default for Ram {
+    fn default() -> Self {
+        Self { bits: 4, data_bits: 8, contents: [0; WORDS_MAX] }
+    }
+}
+
+impl Component for Ram {
+    fn metadata() -> Metadata {
+        Metadata::default()
+    }
+
+    fn layout(&self, _inputs: usize) -> Layout {
+        let (n, m) = (self.bits, self.data_bits);
+        Layout::new(draw::BLOCK_WIDTH, draw::block_height(n + m + 2, m), n + m + 2, m)
+    }
+
+    fn rising_edge(&mut self, pins: &mut Pins, input: usize) {
+        let (n, m) = (self.bits, self.data_bits);
+        if input != n + m + 1 || pins.input(n + m) != State::High {
+            return;
+        }
+
+        // Without a defined address and data there is no way to tell
+        // which word changed and how, so the write is dropped.
+        if let (Some(a), Some(d)) = (pins.input_value(0..n), pins.input_value(n..n + m)) {
+            self.contents[a as usize] = d as u16;
+        }
+    }
+
+    fn update(&mut self, pins: &mut Pins) {
+        let n = self.bits;
+        let word = pins.input_value(0..n).map(|a| self.contents[a as usize] as u32);
+        pins.set_output_value(0..self.data_bits, word);
+    }
+
+    fn draw(&self, pins: &Pins, x: f32, y: f32, _rotation: f32) {
+        draw::block(pins, x, y, Some(self.bits + self.data_bits + 1));
+    }
+}
+
+impl Parameterised for Ram {
+    fn bits(&self) -> usize {
+        self.bits
+    }
+
+    fn set_bits(&mut self, n: usize) {
+        if (BITS_MIN..=BITS_MAX).contains(&n) {
+            self.bits = n;
+        }
+    }
+}
+
+impl Memory for Ram {
+    fn data_bits(&self) -> usize {
+        self.data_bits
+    }
+
+    fn set_data_bits(&mut self, n: usize) {
+        if (DATA_BITS_MIN..=DATA_BITS_MAX).contains(&n) {
+            self.data_bits = n;
+        }
+    }
+
+    fn words(&self) -> &[u16] {
+        &self.contents[..1 << self.bits]
+    }
+
+    fn words_mut(&mut self) -> &mut [u16] {
+        &mut self.contents[..1 << self.bits]
+    }
+}
+
+megs_sdk::export_component!(Ram, bits, memory);
@@ -0,0 +1,75 @@
+use megs_sdk::{Component, Layout, Metadata, Parameterised, Pins, State, draw};
+
+/// The smallest and largest supported width.
+pub const BITS_MIN: usize = 1;
+pub const BITS_MAX: usize = 16;
+
+/// A register of `n` bits.
+///
+/// Inputs: `0..n` = D, `n` = EN, `n + 1` = CLK, `n + 2` = R (asynchronous reset).
+/// Outputs: `0..n` = Q.
+pub struct Register {
+    bits: usize,
+}
+
+impl Default for Register {
+    fn default() -> Self {
+        Self { bits: 4 }
+    }
+}
+
+impl Component for Register {
+    fn metadata() -> Metadata {
+        Metadata::default()
+    }
+
+    fn layout(&self, _inputs: usize) -> Layout {
+        let n = self.bits;
+        Layout::new(draw::BLOCK_WIDTH, draw::block_height(n + 3, n), n + 3, n)
+    }
+
+    /// Load D into the register on a rising clock edge if EN is High.
+    fn rising_edge(&mut self, pins: &mut Pins, input: usize) {
+        let n = self.bits;
+        if input != n + 1 || pins.input(n + 2) != State::Low {
+            return;
+        }
+        match pins.input(n) {
+            State::High => {
+                for i in 0..n {
+                    let d = pins.input(i);
+                    pins.set_output(i, if d.is_level() { d } else { State::Undefined });
+                }
+            },
+            State::Low => {},
+            _ => pins.set_output_value(0..n, None),
+        }
+    }
+
+    fn update(&mut self, pins: &mut Pins) {
+        let n = self.bits;
+        match pins.input(n + 2) {
+            State::High => pins.set_output_value(0..n, Some(0)),
+            State::Low => {},
+            _ => pins.set_output_value(0..n, None),
+        }
+    }
+
+    fn draw(&self, pins: &Pins, x: f32, y: f32, _rotation: f32) {
+        draw::block(pins, x, y, Some(self.bits + 1));
+    }
+}
+
+impl Parameterised for Register {
+    fn bits(&self) -> usize {
+        self.bits
+    }
+
+    fn set_bits(&mut self, n: usize) {
+        if (BITS_MIN..=BITS_MAX).contains(&n) {
+            self.bits = n;
+        }
+    }
+}
+
+megs_sdk::export_component!(Register, bits);
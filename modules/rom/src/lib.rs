@@ -0,0 +1,88 @@
+use megs_sdk::{Component, Layout, Memory, Metadata, Parameterised, Pins, State, draw};
+
+/// The smallest and largest supported number of address inputs.
+pub const BITS_MIN: usize = 1;
+pub const BITS_MAX: usize = 10;
+
+/// The smallest and largest supported number of bits per word.
+pub const DATA_BITS_MIN: usize = 1;
+pub const DATA_BITS_MAX: usize = 16;
+
+pub const WORDS_MAX: usize = 1 << BITS_MAX;
+
+/// A read-only memory, its contents can only be changed by the user.
+///
+/// Inputs: `0..n` = address, `n` = output enable.
+/// Outputs: `0..m` = the addressed word, Low while not enabled.
+pub struct Rom {
+    bits: usize,
+    data_bits: usize,
+    /// The contents of the memory, one word per address.
+    contents: [u16; WORDS_MAX],
+}
+
+impl Default for Rom {
+    fn default() -> Self {
+        Self { bits: 4, data_bits: 8, contents: [0; WORDS_MAX] }
+    }
+}
+
+impl Component for Rom {
+    fn metadata() -> Metadata {
+        Metadata::default()
+    }
+
+    fn layout(&self, _inputs: usize) -> Layout {
+        let (n, m) = (self.bits, self.data_bits);
+        Layout::new(draw::BLOCK_WIDTH, draw::block_height(n + 1, m), n + 1, m)
+    }
+
+    fn update(&mut self, pins: &mut Pins) {
+        let n = self.bits;
+        let word = match pins.input(n) {
+            State::High => pins.input_value(0..n).map(|a| self.contents[a as usize] as u32),
+            State::Low => Some(0),
+            _ => None,
+        };
+        pins.set_output_value(0..self.data_bits, word);
+    }
+
+    /// Draw a box with one stub per pin.
+    fn draw(&self, pins: &Pins, x: f32, y: f32, _rotation: f32) {
+        draw::block(pins, x, y, None);
+    }
+}
+
+impl Parameterised for Rom {
+    fn bits(&self) -> usize {
+        self.bits
+    }
+
+    fn set_bits(&mut self, n: usize) {
+        if (BITS_MIN..=BITS_MAX).contains(&n) {
+            self.bits = n;
+        }
+    }
+}
+
+impl Memory for Rom {
+    fn data_bits(&self) -> usize {
+        self.data_bits
+    }
+
+    fn set_data_bits(&mut self, n: usize) {
+        if (DATA_BITS_MIN..=DATA_BITS_MAX).contains(&n) {
+            self.data_bits = n;
+        }
+    }
+
+    fn words(&self) -> &[u16] {
+        &self.contents[..1 << self.bits]
+    }
+
+    fn words_mut(&mut self) -> &mut [u16] {
+        &mut self.contents[..1 << self.bits]
+    }
+}
+
+megs_sdk::export_component!(Rom, bits, memory);
@@ -0,0 +1,31 @@
+use megs_sdk::{Component, Metadata, Pins, draw::{self, Color}};
+
+pub const H1: f32 = 10.0;
+pub const WIDTH: f32 = H1 * 4.0;
+pub const HEIGHT: f32 = H1 * 6.0;
+
+/// A seven-segment display with one input per segment, `0` = a, ..., `6` = g.
+#[derive(Default)]
+pub struct SevenSegment;
+
+impl Component for SevenSegment {
+    fn metadata() -> Metadata {
+        Metadata {
+            width: WIDTH,
+            height: HEIGHT,
+            inputs: (7, 7),
+            outputs: 0,
+            ..Metadata::default()
+        }
+    }
+
+    fn draw(&self, pins: &Pins, x: f32, y: f32, _rotation: f32) {
+        draw::rectangle(x, y, WIDTH, HEIGHT, Color::BLACK);
+        draw::rectangle(x + 0.5 * H1, y + 0.5 * H1, WIDTH - H1, HEIGHT - H1, Color::WHITE);
+        for i in 0..pins.inputs_nr() {
+            draw::segment(i, x + H1, y + H1, pins.input(i));
+        }
+    }
+}
+
+megs_sdk::export_component!(SevenSegment);
@@ -0,0 +1,71 @@
+use megs_sdk::{Component, Layout, Metadata, Parameterised, Pins, draw::{self, Color}};
+
+pub const H1: f32 = 10.0;
+
+/// The smallest and largest supported width of the bus.
+pub const BITS_MIN: usize = 2;
+pub const BITS_MAX: usize = 32;
+
+/// Splits a bus into its bits.
+///
+/// Inputs: `0` = the bus.
+/// Outputs: `0..n` = the single bits, `0` is the least significant bit.
+pub struct Splitter {
+    bits: usize,
+}
+
+impl Default for Splitter {
+    fn default() -> Self {
+        Self { bits: 8 }
+    }
+}
+
+impl Component for Splitter {
+    fn metadata() -> Metadata {
+        Metadata::default()
+    }
+
+    fn layout(&self, _inputs: usize) -> Layout {
+        let n = self.bits;
+        Layout {
+            width: 4.0 * H1,
+            height: (n as f32 + 1.0) * H1,
+            inputs: vec![n],
+            outputs: vec![1; n],
+        }
+    }
+
+    fn update(&mut self, pins: &mut Pins) {
+        let bus = pins.input_bits(0);
+        for i in 0..self.bits {
+            pins.set_output(i, bus.get(i));
+        }
+    }
+
+    /// Draw the bus on the left fanning out into one stub per bit.
+    fn draw(&self, pins: &Pins, x: f32, y: f32, _rotation: f32) {
+        let h = (self.bits as f32 + 1.0) * H1;
+        draw::line(x, y + h / 2.0, x + 2.0 * H1, y + h / 2.0, 4.0, Color::BLACK);
+        draw::line(x + 2.0 * H1, y + H1, x + 2.0 * H1, y + h - H1, 4.0, Color::BLACK);
+        for i in 0..self.bits {
+            let oy = y + (i as f32 + 1.0) * H1;
+            draw::line(x + 2.0 * H1, oy, x + 4.0 * H1, oy, 1.5, Color::BLACK);
+            draw::state(x + 3.0 * H1, oy, 0.2 * H1, pins.output(i));
+        }
+        draw::rectangle(x + 0.5 * H1, y + h / 2.0 - 0.3 * H1, 0.6 * H1, 0.6 * H1, Color::BLACK);
+    }
+}
+
+impl Parameterised for Splitter {
+    fn bits(&self) -> usize {
+        self.bits
+    }
+
+    fn set_bits(&mut self, n: usize) {
+        if (BITS_MIN..=BITS_MAX).contains(&n) {
+            self.bits = n;
+        }
+    }
+}
+
+megs_sdk::export_component!(Splitter, bits);
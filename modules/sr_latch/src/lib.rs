@@ -0,0 +1,49 @@
+use megs_sdk::{Component, Metadata, Pins, State, draw};
+
+/// Inputs: `0` = S, `1` = R.
+/// Outputs: `0` = Q, `1` = !Q.
+pub const INPUTS_NR: usize = 2;
+pub const OUTPUTS_NR: usize = 2;
+
+/// An SR latch.
+#[derive(Default)]
+pub struct SrLatch {
+    /// The stored bit.
+    q: State,
+}
+
+impl Component for SrLatch {
+    fn metadata() -> Metadata {
+        Metadata {
+            width: draw::BLOCK_WIDTH,
+            height: draw::block_height(INPUTS_NR, OUTPUTS_NR),
+            inputs: (INPUTS_NR, INPUTS_NR),
+            outputs: OUTPUTS_NR,
+            ..Metadata::default()
+        }
+    }
+
+    /// Level sensitive: S sets, R resets, neither holds the state.
+    /// Like a NOR latch both outputs are Low if S and R are High.
+    fn update(&mut self, pins: &mut Pins) {
+        match (pins.input(0), pins.input(1)) {
+            (State::Low, State::Low) => {},
+            (State::High, State::Low) => self.q = State::High,
+            (State::Low, State::High) => self.q = State::Low,
+            (State::High, State::High) => {
+                pins.set_output(0, State::Low);
+                pins.set_output(1, State::Low);
+                return;
+            },
+            _ => self.q = State::Undefined,
+        }
+        pins.set_output(0, self.q);
+        pins.set_output(1, !self.q);
+    }
+
+    fn draw(&self, pins: &Pins, x: f32, y: f32, _rotation: f32) {
+        draw::block(pins, x, y, None);
+    }
+}
+
+megs_sdk::export_component!(SrLatch);
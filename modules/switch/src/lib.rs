@@ -0,0 +1,51 @@
+use megs_sdk::{Component, Metadata, Pins, State, draw::{self, Color}};
+
+pub const H1: f32 = 10.0;
+pub const WIDTH: f32 = H1 * 6.0;
+pub const HEIGHT: f32 = H1 * 4.0;
+
+/// A switch, toggled by clicking it.
+#[derive(Default)]
+pub struct Switch {
+    on: bool,
+}
+
+impl Component for Switch {
+    fn metadata() -> Metadata {
+        Metadata {
+            width: WIDTH,
+            height: HEIGHT,
+            inputs: (0, 0),
+            outputs: 1,
+            ..Metadata::default()
+        }
+    }
+
+    fn update(&mut self, pins: &mut Pins) {
+        pins.set_output(0, State::from(self.on));
+    }
+
+    /// Toggle the switch.
+    fn on_click(&mut self, pins: &mut Pins, _x: f32, _y: f32) {
+        self.on = !self.on;
+        self.update(pins);
+    }
+
+    fn draw(&self, _pins: &Pins, x: f32, y: f32, _rotation: f32) {
+        let on = self.on;
+        draw::rectangle(x, y, WIDTH, HEIGHT, Color::BLACK);
+        draw::rectangle(x + 0.5 * H1, y + 0.5 * H1, WIDTH - H1, HEIGHT - H1, Color::WHITE);
+        // The track of the knob, green if the switch is on.
+        if on {
+            draw::rectangle(x + H1, y + 1.5 * H1, WIDTH - 2.0 * H1, H1, Color::GREEN);
+        } else {
+            draw::rectangle(x + H1, y + 1.5 * H1, WIDTH - 2.0 * H1, H1, Color::GRAY);
+        }
+        let knob = if on { x + WIDTH - 1.5 * H1 } else { x + 1.5 * H1 };
+        draw::circle(knob, y + 2.0 * H1, 0.9 * H1, Color::BLACK);
+        draw::circle_lines(knob, y + 2.0 * H1, 0.9 * H1, 1.0, Color::WHITE);
+        draw::line(x + WIDTH, y + 2.0 * H1, x + WIDTH + H1, y + 2.0 * H1, 2.0, Color::BLACK);
+    }
+}
+
+megs_sdk::export_component!(Switch);
@@ -0,0 +1,31 @@
+use megs_sdk::{Component, Metadata, Pins, State, draw};
+
+/// A tri-state buffer: input `0` is the data input, input `1` enables
+/// the output, which is high impedance while disabled.
+#[derive(Default)]
+pub struct Tristate;
+
+impl Component for Tristate {
+    fn metadata() -> Metadata {
+        Metadata {
+            width: draw::GATE_WIDTH,
+            height: draw::GATE_HEIGHT,
+            inputs: (2, 2),
+            ..Metadata::default()
+        }
+    }
+
+    fn evaluate(&mut self, inputs: &[State], outputs: &mut [State]) {
+        outputs[0] = match inputs[1] {
+            State::High => inputs[0],
+            State::Low => State::HighImpedance,
+            _ => State::Undefined,
+        };
+    }
+
+    fn draw(&self, pins: &Pins, x: f32, y: f32, _rotation: f32) {
+        draw::gate(pins, x, y, "1▽", false);
+    }
+}
+
+megs_sdk::export_component!(Tristate);
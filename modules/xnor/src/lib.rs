@@ -0,0 +1,27 @@
+use megs_sdk::{Component, Metadata, Pins, State, draw};
+
+/// An XNOR gate with two to eight inputs, high if an even number
+/// of inputs is high.
+#[derive(Default)]
+pub struct Xnor;
+
+impl Component for Xnor {
+    fn metadata() -> Metadata {
+        Metadata {
+            width: draw::GATE_WIDTH,
+            height: draw::GATE_HEIGHT,
+            inputs: (2, 8),
+            ..Metadata::default()
+        }
+    }
+
+    fn evaluate(&mut self, inputs: &[State], outputs: &mut [State]) {
+        outputs[0] = !inputs.iter().fold(State::Low, |a, b| a ^ *b);
+    }
+
+    fn draw(&self, pins: &Pins, x: f32, y: f32, _rotation: f32) {
+        draw::gate(pins, x, y, "=1", true);
+    }
+}
+
+megs_sdk::export_component!(Xnor);
@@ -0,0 +1,34 @@
+#!/bin/sh
+# Build every crate within `modules/` and copy the WebAssembly modules
+# into the directory of their category below `assets/modules`.
+#
+# Usage: scripts/build-modules.sh [<module>...]
+set -e
+
+cd "$(dirname "$0")/.."
+
+category() {
+    case "$1" in
+        and|or|not|nand|nor|xor|xnor|buffer|tristate) echo "Gates" ;;
+        switch|button|high|low|clock) echo "Input Controlls" ;;
+        led|seven_segment|hex_display|probe) echo "Output Controlls" ;;
+        sr_latch|d_latch|dff|jkff|tff|register|counter) echo "Sequential" ;;
+        mux|demux|decoder|encoder) echo "Plexers" ;;
+        half_adder|full_adder|adder|comparator) echo "Arithmetic" ;;
+        rom|ram) echo "Memory" ;;
+        splitter|merger) echo "Wiring" ;;
+        *) echo "unknown module '$1', add it to scripts/build-modules.sh" >&2; exit 1 ;;
+    esac
+}
+
+if [ $# -eq 0 ]; then
+    set -- $(ls modules)
+fi
+
+for module in "$@"; do
+    dir="assets/modules/$(category "$module")"
+    cargo build -p "$module" --release
+    mkdir -p "$dir"
+    cp "target/wasm32-unknown-unknown/release/$module.wasm" "$dir/"
+    echo "$module -> $dir"
+done
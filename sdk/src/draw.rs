@@ -0,0 +1,310 @@
+//! Safe wrappers around the functions the host provides.
+
+#[cfg(not(test))]
+mod host {
+    extern "C" {
+        pub fn draw_rectangle(x: f32, y: f32, w: f32, h: f32, r: f32, g: f32, b: f32);
+        pub fn draw_circle(x: f32, y: f32, rad: f32, r: f32, g: f32, b: f32);
+        pub fn draw_circle_lines(x: f32, y: f32, rad: f32, thick: f32, r: f32, g: f32, b: f32);
+        pub fn draw_line(x1: f32, y1: f32, x2: f32, y2: f32, thick: f32, r: f32, g: f32, b: f32);
+        pub fn read_picked_file(ptr: i32, len: i32) -> i32;
+    }
+}
+
+/// Records the lines drawn instead of calling the host, so tests can
+/// check the symbols.
+#[cfg(test)]
+mod host {
+    use std::cell::RefCell;
+
+    thread_local! {
+        /// The lines drawn, as `[x1, y1, x2, y2]`.
+        pub static LINES: RefCell<Vec<[f32; 4]>> = const { RefCell::new(Vec::new()) };
+    }
+
+    pub unsafe fn draw_rectangle(_x: f32, _y: f32, _w: f32, _h: f32, _r: f32, _g: f32, _b: f32) {}
+
+    pub unsafe fn draw_circle(_x: f32, _y: f32, _rad: f32, _r: f32, _g: f32, _b: f32) {}
+
+    pub unsafe fn draw_circle_lines(_x: f32, _y: f32, _rad: f32, _thick: f32, _r: f32, _g: f32, _b: f32) {}
+
+    #[allow(clippy::too_many_arguments)]
+    pub unsafe fn draw_line(x1: f32, y1: f32, x2: f32, y2: f32, _thick: f32, _r: f32, _g: f32, _b: f32) {
+        LINES.with(|lines| lines.borrow_mut().push([x1, y1, x2, y2]));
+    }
+
+    /// Pointers don't fit into an `i32` on the host, so no file is picked.
+    pub unsafe fn read_picked_file(_ptr: i32, _len: i32) -> i32 {
+        -1
+    }
+}
+
+pub use megs_abi::Color;
+
+use megs_abi::State;
+
+use crate::Pins;
+
+/// Draw a filled rectangle with its top left corner at `(x, y)`.
+pub fn rectangle(x: f32, y: f32, w: f32, h: f32, c: Color) {
+    unsafe { host::draw_rectangle(x, y, w, h, c.r, c.g, c.b) }
+}
+
+/// Draw a filled circle around `(x, y)`.
+pub fn circle(x: f32, y: f32, radius: f32, c: Color) {
+    unsafe { host::draw_circle(x, y, radius, c.r, c.g, c.b) }
+}
+
+/// Draw the outline of a circle around `(x, y)`.
+pub fn circle_lines(x: f32, y: f32, radius: f32, thickness: f32, c: Color) {
+    unsafe { host::draw_circle_lines(x, y, radius, thickness, c.r, c.g, c.b) }
+}
+
+/// Draw a line from `(x1, y1)` to `(x2, y2)`.
+pub fn line(x1: f32, y1: f32, x2: f32, y2: f32, thickness: f32, c: Color) {
+    unsafe { host::draw_line(x1, y1, x2, y2, thickness, c.r, c.g, c.b) }
+}
+
+/// Get the color used to show `state`, e.g. as dot next to an output.
+pub fn state_color(state: State) -> Color {
+    match state {
+        State::High => Color::new(1.0, 0.1, 0.1),
+        State::Low => Color::new(0.35, 0.05, 0.05),
+        State::Undefined => Color::new(1.0, 0.6, 0.0),
+        State::HighImpedance => Color::GRAY,
+        State::Conflict => Color::new(0.6, 0.0, 0.8),
+    }
+}
+
+/// Draw a dot of the color of `state` around `(x, y)`.
+pub fn state(x: f32, y: f32, radius: f32, state: State) {
+    circle(x, y, radius, state_color(state));
+}
+
+/// The unit of the block layout, see [`block`].
+const UNIT: f32 = 10.0;
+
+/// The width of a block, see [`block`].
+pub const BLOCK_WIDTH: f32 = 6.0 * UNIT;
+
+/// Get the height of a block with `inputs` inputs and `outputs` outputs.
+pub fn block_height(inputs: usize, outputs: usize) -> f32 {
+    (inputs.max(outputs) as f32 + 1.0) * 1.5 * UNIT
+}
+
+/// Get the vertical position of pin `i` out of `n` pins on one side
+/// of a component of height `h` drawn at `y`.
+///
+/// The simulator spreads the pins of each side evenly along the edge
+/// and attaches wires there, so stubs must be drawn at this position.
+pub fn pin_y(y: f32, h: f32, i: usize, n: usize) -> f32 {
+    y + h * (i as f32 + 1.0) / (n as f32 + 1.0)
+}
+
+/// Draw a box with one stub per pin and a dot showing the state of
+/// every output, the usual symbol of sequential and arithmetic
+/// components. The clock input, if any, is marked with a triangle.
+pub fn block(pins: &Pins, x: f32, y: f32, clock: Option<usize>) {
+    let h = block_height(pins.inputs_nr(), pins.outputs_nr());
+    rectangle(x + UNIT, y, 4.0 * UNIT, h, Color::BLACK);
+    rectangle(x + 1.5 * UNIT, y + 0.5 * UNIT, 3.0 * UNIT, h - UNIT, Color::WHITE);
+    for i in 0..pins.inputs_nr() {
+        let iy = pin_y(y, h, i, pins.inputs_nr());
+        line(x, iy, x + UNIT, iy, 2.0, Color::BLACK);
+        if Some(i) == clock {
+            line(x + 1.5 * UNIT, iy - 0.5 * UNIT, x + 2.2 * UNIT, iy, 1.5, Color::BLACK);
+            line(x + 1.5 * UNIT, iy + 0.5 * UNIT, x + 2.2 * UNIT, iy, 1.5, Color::BLACK);
+        }
+    }
+    for i in 0..pins.outputs_nr() {
+        let oy = pin_y(y, h, i, pins.outputs_nr());
+        line(x + 5.0 * UNIT, oy, x + 6.0 * UNIT, oy, 2.0, Color::BLACK);
+        state(x + 4.3 * UNIT, oy, 0.3 * UNIT, pins.output(i));
+    }
+}
+
+/// The width of a gate, see [`gate`].
+pub const GATE_WIDTH: f32 = 8.0 * UNIT;
+
+/// The height of a gate, see [`gate`].
+pub const GATE_HEIGHT: f32 = 6.5 * UNIT;
+
+/// Draw the character `c` of a gate label with its top left corner
+/// at `(x, y)`, unknown characters are skipped.
+fn glyph(c: char, x: f32, y: f32) {
+    let u = UNIT;
+    match c {
+        '1' => {
+            line(x + 0.5 * u, y, x + 0.5 * u, y + 2.5 * u, 2.0, Color::BLACK);
+            line(x, y + 0.5 * u, x + 0.5 * u, y, 2.0, Color::BLACK);
+        },
+        '&' => {
+            circle_lines(x + 0.5 * u, y + 0.5 * u, 0.5 * u, 2.0, Color::BLACK);
+            circle_lines(x + 0.5 * u, y + 1.7 * u, 0.7 * u, 2.0, Color::BLACK);
+            line(x + 0.8 * u, y + u, x + 1.2 * u, y + 2.5 * u, 2.0, Color::BLACK);
+        },
+        '≥' => {
+            line(x, y + 0.2 * u, x + u, y + 0.8 * u, 2.0, Color::BLACK);
+            line(x + u, y + 0.8 * u, x, y + 1.4 * u, 2.0, Color::BLACK);
+            line(x, y + 2.0 * u, x + u, y + 2.0 * u, 2.0, Color::BLACK);
+        },
+        '=' => {
+            line(x, y + 0.8 * u, x + u, y + 0.8 * u, 2.0, Color::BLACK);
+            line(x, y + 1.6 * u, x + u, y + 1.6 * u, 2.0, Color::BLACK);
+        },
+        '▽' => {
+            line(x, y + u, x + u, y + u, 1.5, Color::BLACK);
+            line(x, y + u, x + 0.5 * u, y + 1.8 * u, 1.5, Color::BLACK);
+            line(x + u, y + u, x + 0.5 * u, y + 1.8 * u, 1.5, Color::BLACK);
+        },
+        _ => {},
+    }
+}
+
+/// Draw the IEC 60617 symbol of a gate: a box with `label` written
+/// inside, one stub per input and a bubble on the output if it's
+/// `inverted`.
+///
+/// Labels are made of `1`, `&`, `≥`, `=` and `▽`, which marks an
+/// output that may be high impedance, e.g. `"≥1"` for an OR gate.
+pub fn gate(pins: &Pins, x: f32, y: f32, label: &str, inverted: bool) {
+    let n = pins.inputs_nr();
+    for i in 0..n {
+        let iy = pin_y(y, GATE_HEIGHT, i, n);
+        line(x, iy, x + UNIT, iy, 2.0, Color::BLACK);
+    }
+    rectangle(x + UNIT, y, 4.5 * UNIT, GATE_HEIGHT, Color::BLACK);
+    rectangle(x + 1.5 * UNIT, y + 0.5 * UNIT, 3.5 * UNIT, GATE_HEIGHT - UNIT, Color::WHITE);
+
+    // Every character is one unit wide, with half a unit in between.
+    let chars = label.chars().count() as f32;
+    let gx = x + 3.25 * UNIT - (chars * 1.5 - 0.5) * UNIT / 2.0;
+    for (i, c) in label.chars().enumerate() {
+        glyph(c, gx + i as f32 * 1.5 * UNIT, y + 2.0 * UNIT);
+    }
+
+    let oy = pin_y(y, GATE_HEIGHT, 0, 1);
+    if inverted {
+        line(x + 6.5 * UNIT, oy, x + GATE_WIDTH, oy, 2.0, Color::BLACK);
+        circle(x + 6.0 * UNIT, oy, 0.5 * UNIT, Color::WHITE);
+        circle_lines(x + 6.0 * UNIT, oy, 0.5 * UNIT, 2.0, Color::BLACK);
+    } else {
+        line(x + 5.5 * UNIT, oy, x + GATE_WIDTH, oy, 2.0, Color::BLACK);
+    }
+}
+
+/// The width of a seven-segment digit, see [`digit`].
+pub const DIGIT_WIDTH: f32 = 2.0 * UNIT;
+/// The height of a seven-segment digit, see [`digit`].
+pub const DIGIT_HEIGHT: f32 = 4.0 * UNIT;
+
+/// The segments (bit 0 = a, ..., bit 6 = g) of the hexadecimal digits.
+pub const DIGITS: [u8; 16] = [
+    0x3F, 0x06, 0x5B, 0x4F, 0x66, 0x6D, 0x7D, 0x07,
+    0x7F, 0x6F, 0x77, 0x7C, 0x39, 0x5E, 0x79, 0x71,
+];
+/// A dash, shown if a digit is undefined.
+pub const DASH: u8 = 0x40;
+
+/// Draw segment `i` (`0` = a, ..., `6` = g) of a seven-segment digit
+/// whose top left corner is at `(x, y)`.
+///
+/// Segments that are on are red, off segments are barely visible
+/// and undefined segments are orange.
+pub fn segment(i: usize, x: f32, y: f32, s: State) {
+    let t = 0.3 * UNIT;
+    let (w, h) = (DIGIT_WIDTH, DIGIT_HEIGHT);
+    let v = h / 2.0 - 1.5 * t;
+    let (sx, sy, sw, sh) = match i {
+        0 => (x + t, y, w - 2.0 * t, t),
+        1 => (x + w - t, y + t, t, v),
+        2 => (x + w - t, y + h / 2.0 + t / 2.0, t, v),
+        3 => (x + t, y + h - t, w - 2.0 * t, t),
+        4 => (x, y + h / 2.0 + t / 2.0, t, v),
+        5 => (x, y + t, t, v),
+        _ => (x + t, y + h / 2.0 - t / 2.0, w - 2.0 * t, t),
+    };
+
+    match s {
+        State::High => rectangle(sx, sy, sw, sh, Color::new(1.0, 0.0, 0.0)),
+        State::Low => rectangle(sx, sy, sw, sh, Color::new(0.92, 0.88, 0.88)),
+        _ => rectangle(sx, sy, sw, sh, Color::new(1.0, 0.6, 0.0)),
+    }
+}
+
+/// Draw a seven-segment digit with the given `segments`, e.g. one
+/// of [`DIGITS`], whose top left corner is at `(x, y)`.
+///
+/// The segments of an `undefined` digit are orange instead of red.
+pub fn digit(segments: u8, x: f32, y: f32, undefined: bool) {
+    for i in 0..7 {
+        let s = if segments & (1 << i) == 0 {
+            State::Low
+        } else if undefined {
+            State::Undefined
+        } else {
+            State::High
+        };
+        segment(i, x, y, s);
+    }
+}
+
+/// Copy the file the user picked into `buf`, e.g. from within
+/// `load_contents`.
+///
+/// Returns the number of bytes copied, or `None` if no file was picked.
+pub fn read_picked_file(buf: &mut [u8]) -> Option<usize> {
+    let n = unsafe { host::read_picked_file(buf.as_mut_ptr() as i32, buf.len() as i32) };
+    if n < 0 { None } else { Some(n as usize) }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::Layout;
+
+    /// Get the lines drawn by `f`.
+    pub(crate) fn record(f: impl FnOnce()) -> Vec<[f32; 4]> {
+        host::LINES.with(|lines| lines.borrow_mut().clear());
+        f();
+        host::LINES.with(|lines| lines.borrow_mut().drain(..).collect())
+    }
+
+    #[test]
+    fn block_stubs_test() {
+        // The positions `LogicInstance::input_pos` and `output_pos`
+        // attach wires to.
+        let input_pos = |y: f32, h: f32, i: usize, n: usize| y + h * (i as f32 + 1.0) / (n as f32 + 1.0);
+
+        for (inputs, outputs) in [(4, 2), (1, 3), (3, 3), (8, 1)] {
+            let mut pins = Pins::default();
+            pins.fit(&Layout::new(BLOCK_WIDTH, 0.0, inputs, outputs));
+            let (x, y) = (100.0, 50.0);
+            let h = block_height(inputs, outputs);
+            let lines = record(|| block(&pins, x, y, Some(0)));
+
+            let left: Vec<f32> = lines.iter().filter(|l| l[0] == x).map(|l| l[1]).collect();
+            let right: Vec<f32> = lines.iter().filter(|l| l[2] == x + BLOCK_WIDTH).map(|l| l[1]).collect();
+            let expected: Vec<f32> = (0..inputs).map(|i| input_pos(y, h, i, inputs)).collect();
+            assert_eq!(expected, left);
+            let expected: Vec<f32> = (0..outputs).map(|i| input_pos(y, h, i, outputs)).collect();
+            assert_eq!(expected, right);
+        }
+    }
+
+    #[test]
+    fn gate_stubs_test() {
+        for (inputs, inverted) in [(1, true), (2, false), (5, true), (8, false)] {
+            let mut pins = Pins::default();
+            pins.fit(&Layout::new(GATE_WIDTH, GATE_HEIGHT, inputs, 1));
+            let (x, y) = (20.0, 30.0);
+            let lines = record(|| gate(&pins, x, y, "≥1", inverted));
+
+            let left: Vec<f32> = lines.iter().filter(|l| l[0] == x).map(|l| l[1]).collect();
+            let expected: Vec<f32> = (0..inputs).map(|i| pin_y(y, GATE_HEIGHT, i, inputs)).collect();
+            assert_eq!(expected, left);
+            let right: Vec<f32> = lines.iter().filter(|l| l[2] == x + GATE_WIDTH).map(|l| l[1]).collect();
+            assert_eq!(vec![y + GATE_HEIGHT / 2.0], right);
+        }
+    }
+}
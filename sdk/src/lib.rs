@@ -0,0 +1,823 @@
+//! Write MEGS modules in safe Rust.
+//!
+//! Implement [`Component`] for a type and export it with
+//! [`export_component!`], which generates all the functions the
+//! simulator expects (see the `Pins` section of the README):
+//!
+//! ```ignore
+//! use megs_sdk::{Component, Metadata, Pins, State, draw::{self, Color}};
+//!
+//! #[derive(Default)]
+//! struct Not;
+//!
+//! impl Component for Not {
+//!     fn metadata() -> Metadata {
+//!         Metadata { width: 60.0, height: 40.0, ..Metadata::default() }
+//!     }
+//!
+//!     fn evaluate(&mut self, inputs: &[State], outputs: &mut [State]) {
+//!         outputs[0] = !inputs[0];
+//!     }
+//!
+//!     fn draw(&self, _pins: &Pins, x: f32, y: f32, _rotation: f32) {
+//!         draw::rectangle(x, y, 60.0, 40.0, Color::BLACK);
+//!     }
+//! }
+//!
+//! megs_sdk::export_component!(Not);
+//! ```
+//!
+//! Components with a configurable width, clocks and memories also
+//! implement [`Parameterised`], [`Clock`] or [`Memory`] and name the
+//! capability when exported, e.g. `export_component!(Ram, bits, memory)`.
+
+pub mod draw;
+
+use core::ops::Range;
+
+pub use megs_abi::{State, Bits, MAX_WIDTH};
+
+/// What the simulator needs to know about a component.
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub struct Metadata {
+    /// The size reported by `width()` and `height()`.
+    pub width: f32,
+    pub height: f32,
+    /// The smallest and largest supported number of inputs.
+    pub inputs: (usize, usize),
+    /// The number of inputs the component starts with, the
+    /// smallest if `None`.
+    pub initial_inputs: Option<usize>,
+    /// The number of outputs.
+    pub outputs: usize,
+    /// The propagation delay in time units.
+    pub delay: u32,
+}
+
+impl Default for Metadata {
+    fn default() -> Self {
+        Self { width: 80.0, height: 60.0, inputs: (1, 1), initial_inputs: None, outputs: 1, delay: 1 }
+    }
+}
+
+/// The current size of a component and the width of each of its pins.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Layout {
+    pub width: f32,
+    pub height: f32,
+    /// The number of bits of every input.
+    pub inputs: Vec<usize>,
+    /// The number of bits of every output.
+    pub outputs: Vec<usize>,
+}
+
+impl Layout {
+    /// Create a layout with `inputs` and `outputs` single-bit pins.
+    pub fn new(width: f32, height: f32, inputs: usize, outputs: usize) -> Self {
+        Self { width, height, inputs: vec![1; inputs], outputs: vec![1; outputs] }
+    }
+}
+
+/// The values of the pins of a component.
+///
+/// Single-bit pins are accessed as [`State`], buses as [`Bits`].
+/// Pins out of range read as undefined and are ignored when written.
+#[derive(Debug, Clone, Default)]
+pub struct Pins {
+    inputs: Vec<Bits>,
+    outputs: Vec<Bits>,
+}
+
+impl Pins {
+    /// Get the number of inputs.
+    pub fn inputs_nr(&self) -> usize {
+        self.inputs.len()
+    }
+
+    /// Get the number of outputs.
+    pub fn outputs_nr(&self) -> usize {
+        self.outputs.len()
+    }
+
+    /// Get the state of the single-bit input `i`.
+    pub fn input(&self, i: usize) -> State {
+        self.inputs.get(i).map(|b| b.get(0)).unwrap_or_default()
+    }
+
+    /// Get the value of the input `i`.
+    pub fn input_bits(&self, i: usize) -> Bits {
+        self.inputs.get(i).copied().unwrap_or_else(|| Bits::undefined(1))
+    }
+
+    /// Interpret the single-bit inputs `range` as an unsigned number,
+    /// the first one being the least significant bit.
+    ///
+    /// Returns `None` unless all of them are at a defined level.
+    pub fn input_value(&self, range: Range<usize>) -> Option<u32> {
+        let mut v = 0;
+        for (k, i) in range.enumerate() {
+            match self.input(i) {
+                State::High => v |= 1 << k,
+                State::Low => {},
+                _ => return None,
+            }
+        }
+        Some(v)
+    }
+
+    /// Get the state of the single-bit output `i`.
+    pub fn output(&self, i: usize) -> State {
+        self.outputs.get(i).map(|b| b.get(0)).unwrap_or_default()
+    }
+
+    /// Get the value of the output `i`.
+    pub fn output_bits(&self, i: usize) -> Bits {
+        self.outputs.get(i).copied().unwrap_or_else(|| Bits::undefined(1))
+    }
+
+    /// Set the single-bit output `i` to `state`.
+    pub fn set_output(&mut self, i: usize, state: State) {
+        if let Some(output) = self.outputs.get_mut(i) {
+            output.set(0, state);
+        }
+    }
+
+    /// Set the output `i` to `bits`, keeping the width of the output.
+    pub fn set_output_bits(&mut self, i: usize, bits: Bits) {
+        if let Some(output) = self.outputs.get_mut(i) {
+            for k in 0..output.width() {
+                output.set(k, bits.get(k));
+            }
+        }
+    }
+
+    /// Set the single-bit outputs `range` to the bits of `value`, the
+    /// first one being the least significant bit, or to undefined if `None`.
+    pub fn set_output_value(&mut self, range: Range<usize>, value: Option<u32>) {
+        for (k, i) in range.enumerate() {
+            let state = match value {
+                Some(v) => State::from(v.checked_shr(k as u32).unwrap_or(0) & 1 != 0),
+                None => State::Undefined,
+            };
+            self.set_output(i, state);
+        }
+    }
+
+    /// Fit the pins to `layout`, pins that changed their width
+    /// become undefined.
+    fn fit(&mut self, layout: &Layout) {
+        fn fit(pins: &mut Vec<Bits>, widths: &[usize]) {
+            pins.truncate(widths.len());
+            for (i, &w) in widths.iter().enumerate() {
+                let w = w.clamp(1, MAX_WIDTH);
+                match pins.get_mut(i) {
+                    Some(pin) if pin.width() == w => {},
+                    Some(pin) => *pin = Bits::undefined(w),
+                    None => pins.push(Bits::undefined(w)),
+                }
+            }
+        }
+
+        fit(&mut self.inputs, &layout.inputs);
+        fit(&mut self.outputs, &layout.outputs);
+    }
+}
+
+/// Get the state forced by the asynchronous set input `s` and reset
+/// input `r` of a flip-flop, both active High, or `None` if neither of
+/// them is active.
+///
+/// Both active at once, or either of them undefined, force an
+/// undefined state.
+pub fn set_reset(s: State, r: State) -> Option<State> {
+    match (s, r) {
+        (State::Low, State::Low) => None,
+        (State::High, State::Low) => Some(State::High),
+        (State::Low, State::High) => Some(State::Low),
+        _ => Some(State::Undefined),
+    }
+}
+
+/// A component of a circuit, see [`export_component!`].
+pub trait Component: Default {
+    /// Describe the size and pins the component starts with.
+    fn metadata() -> Metadata;
+
+    /// Get the current size and pins, given the number of inputs
+    /// chosen within [`Metadata::inputs`].
+    ///
+    /// Called again whenever the component was reconfigured, e.g. by
+    /// [`Parameterised::set_bits`].
+    fn layout(&self, inputs: usize) -> Layout {
+        let meta = Self::metadata();
+        Layout::new(meta.width, meta.height, inputs, meta.outputs)
+    }
+
+    /// Compute the single-bit outputs from the single-bit inputs,
+    /// called by the default [`Component::update`].
+    fn evaluate(&mut self, _inputs: &[State], _outputs: &mut [State]) {}
+
+    /// Compute the outputs from the inputs, called once per step
+    /// after all inputs have been set.
+    fn update(&mut self, pins: &mut Pins) {
+        let inputs: Vec<State> = (0..pins.inputs_nr()).map(|i| pins.input(i)).collect();
+        let mut outputs: Vec<State> = (0..pins.outputs_nr()).map(|i| pins.output(i)).collect();
+        self.evaluate(&inputs, &mut outputs);
+        for (i, s) in outputs.into_iter().enumerate() {
+            pins.set_output(i, s);
+        }
+    }
+
+    /// React to input `input` changing from low to high, called
+    /// after all inputs have been set and before [`Component::update`].
+    fn rising_edge(&mut self, _pins: &mut Pins, _input: usize) {}
+
+    /// React to the simulation time advancing to `now`, called
+    /// before every step.
+    fn tick(&mut self, _pins: &mut Pins, _now: u64) {}
+
+    /// React to a click at `(x, y)`, relative to the component, e.g.
+    /// to toggle a switch. Called by the default [`Component::on_press`].
+    fn on_click(&mut self, _pins: &mut Pins, _x: f32, _y: f32) {}
+
+    /// React to the mouse button being pressed at `(x, y)`,
+    /// relative to the component.
+    ///
+    /// The host reports presses and releases separately, so components
+    /// like buttons can act on both. Others only implement
+    /// [`Component::on_click`].
+    fn on_press(&mut self, pins: &mut Pins, x: f32, y: f32) {
+        self.on_click(pins, x, y);
+    }
+
+    /// React to the mouse button being released.
+    fn on_release(&mut self, _pins: &mut Pins, _x: f32, _y: f32) {}
+
+    /// Draw the component with its top left corner at `(x, y)`.
+    fn draw(&self, pins: &Pins, x: f32, y: f32, rotation: f32);
+}
+
+/// A component of configurable width, e.g. a register.
+///
+/// Export with `export_component!(T, bits)`.
+pub trait Parameterised: Component {
+    /// Get the current width.
+    fn bits(&self) -> usize;
+
+    /// Change the width, values the component doesn't support
+    /// must be ignored.
+    fn set_bits(&mut self, n: usize);
+}
+
+/// A clock the simulator can align its cycles to.
+///
+/// Export with `export_component!(T, clock)`.
+pub trait Clock: Component {
+    /// Get the period in time units.
+    fn period(&self) -> u32;
+}
+
+/// A memory the user can inspect, edit and load from a file.
+///
+/// Export with `export_component!(T, memory)`.
+pub trait Memory: Component {
+    /// Get the width of the words.
+    fn data_bits(&self) -> usize;
+
+    /// Change the width of the words, values the component doesn't
+    /// support must be ignored.
+    fn set_data_bits(&mut self, n: usize);
+
+    /// Get all addressable words.
+    fn words(&self) -> &[u16];
+
+    /// Get all addressable words, e.g. to change them.
+    fn words_mut(&mut self) -> &mut [u16];
+}
+
+/// Holds a component together with the values of its pins.
+///
+/// Used by [`export_component!`], there is no need to use it directly.
+#[derive(Debug)]
+pub struct Runtime<C: Component> {
+    pub component: C,
+    pub pins: Pins,
+    layout: Layout,
+    inputs_nr: usize,
+}
+
+impl<C: Component> Default for Runtime<C> {
+    fn default() -> Self {
+        let component = C::default();
+        let meta = C::metadata();
+        let inputs_nr = meta.initial_inputs.unwrap_or(meta.inputs.0);
+        let layout = component.layout(inputs_nr);
+        let mut pins = Pins::default();
+        pins.fit(&layout);
+
+        // Components without inputs, e.g. constants, drive their
+        // outputs right from the start.
+        let mut runtime = Self { component, pins, layout, inputs_nr };
+        runtime.update();
+        runtime
+    }
+}
+
+impl<C: Component> Runtime<C> {
+    /// Update the layout after the component has been reconfigured.
+    fn relayout(&mut self) {
+        self.layout = self.component.layout(self.inputs_nr);
+        self.pins.fit(&self.layout);
+    }
+
+    /// Get the current layout.
+    pub fn layout(&self) -> &Layout {
+        &self.layout
+    }
+
+    /// Change the number of inputs, counts out of range are ignored.
+    pub fn set_inputs_nr(&mut self, n: i32) {
+        let (min, max) = C::metadata().inputs;
+        if (min as i32..=max as i32).contains(&n) {
+            self.inputs_nr = n as usize;
+            self.relayout();
+        }
+    }
+
+    /// Get the number of bits of input `i`.
+    pub fn input_width(&self, i: i32) -> i32 {
+        self.layout.inputs.get(i as usize).copied().unwrap_or(1) as i32
+    }
+
+    /// Get the number of bits of output `i`.
+    pub fn output_width(&self, i: i32) -> i32 {
+        self.layout.outputs.get(i as usize).copied().unwrap_or(1) as i32
+    }
+
+    /// Set the single-bit input `i` to the state `s` passed by the host.
+    pub fn set_input(&mut self, i: i32, s: i32) {
+        if let Some(input) = self.pins.inputs.get_mut(i as usize) {
+            input.set(0, State::from(s));
+        }
+    }
+
+    /// Set the bus input `i` to the value `v` passed by the host.
+    pub fn set_input_bits(&mut self, i: i32, v: i64) {
+        if let Some(input) = self.pins.inputs.get_mut(i as usize) {
+            *input = Bits::from_i64(input.width(), v);
+        }
+    }
+
+    /// Get the state of output `i` to pass it to the host.
+    pub fn output(&self, i: i32) -> i32 {
+        self.pins.output(i as usize).into()
+    }
+
+    /// Get the value of the bus output `i` to pass it to the host.
+    pub fn output_bits(&self, i: i32) -> i64 {
+        self.pins.output_bits(i as usize).to_i64()
+    }
+
+    /// Evaluate the component.
+    pub fn update(&mut self) {
+        self.component.update(&mut self.pins);
+    }
+
+    pub fn rising_edge(&mut self, i: i32) {
+        self.component.rising_edge(&mut self.pins, i as usize);
+    }
+
+    pub fn tick(&mut self, now: i64) {
+        self.component.tick(&mut self.pins, now as u64);
+    }
+
+    pub fn on_press(&mut self, x: f32, y: f32) {
+        self.component.on_press(&mut self.pins, x, y);
+    }
+
+    pub fn on_release(&mut self, x: f32, y: f32) {
+        self.component.on_release(&mut self.pins, x, y);
+    }
+
+    pub fn draw(&self, x: f32, y: f32, r: f32) {
+        self.component.draw(&self.pins, x, y, r);
+    }
+}
+
+impl<C: Parameterised> Runtime<C> {
+    /// Change the width of the component, see [`Parameterised::set_bits`].
+    pub fn set_bits(&mut self, n: i32) {
+        if n > 0 {
+            self.component.set_bits(n as usize);
+            self.relayout();
+        }
+    }
+}
+
+impl<C: Memory> Runtime<C> {
+    fn mask(&self) -> u16 {
+        ((1u32 << self.component.data_bits().min(16)) - 1) as u16
+    }
+
+    /// Change the width of the words, see [`Memory::set_data_bits`].
+    ///
+    /// Words are cut to the new width.
+    pub fn set_data_bits(&mut self, n: i32) {
+        if n > 0 {
+            self.component.set_data_bits(n as usize);
+            let mask = self.mask();
+            self.component.words_mut().iter_mut().for_each(|w| *w &= mask);
+            self.relayout();
+        }
+    }
+
+    /// Get the word at `addr`, `0` if out of range.
+    pub fn mem_read(&self, addr: i32) -> i32 {
+        self.component.words().get(addr as usize).copied().unwrap_or(0) as i32
+    }
+
+    /// Overwrite the word at `addr`, addresses out of range are ignored.
+    pub fn mem_write(&mut self, addr: i32, v: i32) {
+        let mask = self.mask();
+        if let Some(word) = self.component.words_mut().get_mut(addr as usize) {
+            *word = v as u16 & mask;
+        }
+    }
+
+    /// Replace the contents with the file the user picked, one
+    /// little-endian 16-bit value per word.
+    ///
+    /// Returns the number of words loaded, the contents stay as they
+    /// are if no file was picked.
+    pub fn load_contents(&mut self) -> i32 {
+        let mask = self.mask();
+        let words = self.component.words_mut();
+        let mut buf = vec![0u8; words.len() * 2];
+        let n = match draw::read_picked_file(&mut buf) {
+            Some(n) => n,
+            None => return 0,
+        };
+        for (word, bytes) in words.iter_mut().zip(buf.chunks_exact(2)) {
+            *word = u16::from_le_bytes([bytes[0], bytes[1]]) & mask;
+        }
+        (n / 2) as i32
+    }
+}
+
+/// Export a [`Component`] as MEGS module.
+///
+/// Generates `width`, `height`, `draw`, `get_inputs_nr`, `set_inputs_nr`,
+/// `get_outputs_nr`, `get_input_width`, `get_output_width`, `set_input`,
+/// `set_input_bits`, `get_output`, `get_output_bits`, `update`,
+/// `get_delay`, `rising_edge`, `tick`, `on_press` and `on_release`.
+/// Must be used once per crate.
+///
+/// The simulator recognises components with optional capabilities by
+/// their exports, so these are only generated if named:
+///
+/// * `bits`: `set_bits` and `get_bits`, see [`Parameterised`].
+/// * `clock`: `get_period`, see [`Clock`].
+/// * `memory`: `set_data_bits`, `get_data_bits`, `mem_size`, `mem_read`,
+///   `mem_write` and `load_contents`, see [`Memory`].
+#[macro_export]
+macro_rules! export_component {
+    ($component:ty $(, $capability:ident)* $(,)?) => {
+        static mut __MEGS_RUNTIME: Option<$crate::Runtime<$component>> = None;
+
+        fn __megs_runtime() -> &'static mut $crate::Runtime<$component> {
+            // Modules are single threaded and never hold a reference
+            // across calls from the host.
+            unsafe { (*::core::ptr::addr_of_mut!(__MEGS_RUNTIME)).get_or_insert_with(Default::default) }
+        }
+
+        #[no_mangle]
+        pub extern "C" fn width() -> f32 {
+            __megs_runtime().layout().width
+        }
+
+        #[no_mangle]
+        pub extern "C" fn height() -> f32 {
+            __megs_runtime().layout().height
+        }
+
+        #[no_mangle]
+        pub extern "C" fn draw(x: f32, y: f32, r: f32) {
+            __megs_runtime().draw(x, y, r);
+        }
+
+        #[no_mangle]
+        pub extern "C" fn get_inputs_nr() -> i32 {
+            __megs_runtime().pins.inputs_nr() as i32
+        }
+
+        #[no_mangle]
+        pub extern "C" fn set_inputs_nr(n: i32) {
+            __megs_runtime().set_inputs_nr(n);
+        }
+
+        #[no_mangle]
+        pub extern "C" fn get_outputs_nr() -> i32 {
+            __megs_runtime().pins.outputs_nr() as i32
+        }
+
+        #[no_mangle]
+        pub extern "C" fn get_input_width(i: i32) -> i32 {
+            __megs_runtime().input_width(i)
+        }
+
+        #[no_mangle]
+        pub extern "C" fn get_output_width(i: i32) -> i32 {
+            __megs_runtime().output_width(i)
+        }
+
+        #[no_mangle]
+        pub extern "C" fn set_input(i: i32, s: i32) {
+            __megs_runtime().set_input(i, s);
+        }
+
+        #[no_mangle]
+        pub extern "C" fn set_input_bits(i: i32, v: i64) {
+            __megs_runtime().set_input_bits(i, v);
+        }
+
+        #[no_mangle]
+        pub extern "C" fn get_output(i: i32) -> i32 {
+            __megs_runtime().output(i)
+        }
+
+        #[no_mangle]
+        pub extern "C" fn get_output_bits(i: i32) -> i64 {
+            __megs_runtime().output_bits(i)
+        }
+
+        #[no_mangle]
+        pub extern "C" fn update() {
+            __megs_runtime().update();
+        }
+
+        #[no_mangle]
+        pub extern "C" fn get_delay() -> i32 {
+            <$component as $crate::Component>::metadata().delay as i32
+        }
+
+        #[no_mangle]
+        pub extern "C" fn rising_edge(i: i32) {
+            __megs_runtime().rising_edge(i);
+        }
+
+        #[no_mangle]
+        pub extern "C" fn tick(now: i64) {
+            __megs_runtime().tick(now);
+        }
+
+        #[no_mangle]
+        pub extern "C" fn on_press(x: f32, y: f32) {
+            __megs_runtime().on_press(x, y);
+        }
+
+        #[no_mangle]
+        pub extern "C" fn on_release(x: f32, y: f32) {
+            __megs_runtime().on_release(x, y);
+        }
+
+        $( $crate::__export_capability!($capability, $component); )*
+    };
+}
+
+/// Generate the exports of a capability, see [`export_component!`].
+#[doc(hidden)]
+#[macro_export]
+macro_rules! __export_capability {
+    (bits, $component:ty) => {
+        #[no_mangle]
+        pub extern "C" fn get_bits() -> i32 {
+            $crate::Parameterised::bits(&__megs_runtime().component) as i32
+        }
+
+        #[no_mangle]
+        pub extern "C" fn set_bits(n: i32) {
+            __megs_runtime().set_bits(n);
+        }
+    };
+    (clock, $component:ty) => {
+        #[no_mangle]
+        pub extern "C" fn get_period() -> i32 {
+            $crate::Clock::period(&__megs_runtime().component) as i32
+        }
+    };
+    (memory, $component:ty) => {
+        #[no_mangle]
+        pub extern "C" fn get_data_bits() -> i32 {
+            $crate::Memory::data_bits(&__megs_runtime().component) as i32
+        }
+
+        #[no_mangle]
+        pub extern "C" fn set_data_bits(n: i32) {
+            __megs_runtime().set_data_bits(n);
+        }
+
+        #[no_mangle]
+        pub extern "C" fn mem_size() -> i32 {
+            $crate::Memory::words(&__megs_runtime().component).len() as i32
+        }
+
+        #[no_mangle]
+        pub extern "C" fn mem_read(addr: i32) -> i32 {
+            __megs_runtime().mem_read(addr)
+        }
+
+        #[no_mangle]
+        pub extern "C" fn mem_write(addr: i32, v: i32) {
+            __megs_runtime().mem_write(addr, v);
+        }
+
+        #[no_mangle]
+        pub extern "C" fn load_contents() -> i32 {
+            __megs_runtime().load_contents()
+        }
+    };
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Inverts input `0`, counts clicks and has a bus output of
+    /// configurable width and four words of memory.
+    struct Test {
+        clicks: u32,
+        bits: usize,
+        data_bits: usize,
+        words: [u16; 4],
+    }
+
+    impl Default for Test {
+        fn default() -> Self {
+            Self { clicks: 0, bits: 1, data_bits: 8, words: [0; 4] }
+        }
+    }
+
+    impl Component for Test {
+        fn metadata() -> Metadata {
+            Metadata { inputs: (1, 3), initial_inputs: Some(2), outputs: 2, ..Metadata::default() }
+        }
+
+        fn layout(&self, inputs: usize) -> Layout {
+            let mut layout = Layout::new(80.0, 60.0, inputs, 2);
+            layout.outputs[1] = self.bits;
+            layout
+        }
+
+        fn evaluate(&mut self, inputs: &[State], outputs: &mut [State]) {
+            outputs[0] = !inputs[0];
+        }
+
+        fn on_click(&mut self, _pins: &mut Pins, _x: f32, _y: f32) {
+            self.clicks += 1;
+        }
+
+        fn draw(&self, _pins: &Pins, _x: f32, _y: f32, _rotation: f32) {}
+    }
+
+    impl Parameterised for Test {
+        fn bits(&self) -> usize {
+            self.bits
+        }
+
+        fn set_bits(&mut self, n: usize) {
+            if n <= 8 {
+                self.bits = n;
+            }
+        }
+    }
+
+    impl Memory for Test {
+        fn data_bits(&self) -> usize {
+            self.data_bits
+        }
+
+        fn set_data_bits(&mut self, n: usize) {
+            self.data_bits = n.min(16);
+        }
+
+        fn words(&self) -> &[u16] {
+            &self.words
+        }
+
+        fn words_mut(&mut self) -> &mut [u16] {
+            &mut self.words
+        }
+    }
+
+    mod exported {
+        use super::Test;
+
+        crate::export_component!(Test, bits, memory);
+    }
+
+    #[test]
+    fn pins_test() {
+        let mut pins = Pins::default();
+        pins.fit(&Layout::new(0.0, 0.0, 3, 4));
+        assert_eq!((3, 4), (pins.inputs_nr(), pins.outputs_nr()));
+        assert_eq!(State::Undefined, pins.input(7));
+
+        pins.inputs[0].set(0, State::High);
+        pins.inputs[1].set(0, State::Low);
+        assert_eq!(None, pins.input_value(0..3));
+        pins.inputs[2].set(0, State::High);
+        assert_eq!(Some(0b101), pins.input_value(0..3));
+
+        pins.set_output_value(0..4, Some(0b0110));
+        assert_eq!([State::Low, State::High, State::High, State::Low], [0, 1, 2, 3].map(|i| pins.output(i)));
+        pins.set_output_value(1..3, None);
+        assert_eq!(State::Undefined, pins.output(2));
+        pins.set_output(9, State::High);
+
+        // Pins that change their width become undefined.
+        let mut layout = Layout::new(0.0, 0.0, 3, 4);
+        layout.outputs[0] = 4;
+        pins.fit(&layout);
+        assert_eq!(Bits::undefined(4), pins.output_bits(0));
+        assert_eq!(State::Low, pins.output(3));
+    }
+
+    #[test]
+    fn runtime_test() {
+        let mut rt = Runtime::<Test>::default();
+        assert_eq!((2, 2), (rt.pins.inputs_nr(), rt.pins.outputs_nr()));
+        assert_eq!(i32::from(State::Undefined), rt.output(0));
+
+        rt.set_input(0, State::Low.into());
+        rt.update();
+        assert_eq!(i32::from(State::High), rt.output(0));
+
+        rt.set_inputs_nr(4);
+        assert_eq!(2, rt.pins.inputs_nr());
+        rt.set_inputs_nr(3);
+        assert_eq!(3, rt.pins.inputs_nr());
+        assert_eq!(1, rt.input_width(7));
+
+        rt.on_press(1.0, 2.0);
+        rt.on_release(1.0, 2.0);
+        assert_eq!(1, rt.component.clicks);
+    }
+
+    #[test]
+    fn parameterised_test() {
+        let mut rt = Runtime::<Test>::default();
+        rt.set_bits(4);
+        assert_eq!(4, rt.output_width(1));
+        rt.set_bits(0);
+        rt.set_bits(9);
+        assert_eq!(4, rt.output_width(1));
+        assert_eq!(Bits::undefined(4).to_i64(), rt.output_bits(1));
+    }
+
+    #[test]
+    fn memory_test() {
+        let mut rt = Runtime::<Test>::default();
+        rt.mem_write(1, 0x1ff);
+        rt.mem_write(9, 0x1);
+        assert_eq!(0xff, rt.mem_read(1));
+        assert_eq!(0, rt.mem_read(9));
+
+        // Words are cut to the new width.
+        rt.set_data_bits(4);
+        assert_eq!(0xf, rt.mem_read(1));
+        rt.mem_write(2, 0x12);
+        assert_eq!(0x2, rt.mem_read(2));
+
+        // Nothing was picked, so the contents are kept.
+        assert_eq!(0, rt.load_contents());
+        assert_eq!(0xf, rt.mem_read(1));
+    }
+
+    #[test]
+    fn export_component_test() {
+        assert_eq!(80.0, exported::width());
+        assert_eq!(2, exported::get_inputs_nr());
+        assert_eq!(2, exported::get_outputs_nr());
+        assert_eq!(1, exported::get_delay());
+
+        exported::set_input(0, State::High.into());
+        exported::update();
+        assert_eq!(i32::from(State::Low), exported::get_output(0));
+
+        exported::set_bits(3);
+        assert_eq!((3, 3), (exported::get_bits(), exported::get_output_width(1)));
+
+        exported::mem_write(3, 0x42);
+        assert_eq!((4, 0x42), (exported::mem_size(), exported::mem_read(3)));
+        assert_eq!(8, exported::get_data_bits());
+    }
+
+    #[test]
+    fn set_reset_test() {
+        assert_eq!(None, set_reset(State::Low, State::Low));
+        assert_eq!(Some(State::High), set_reset(State::High, State::Low));
+        assert_eq!(Some(State::Low), set_reset(State::Low, State::High));
+        assert_eq!(Some(State::Undefined), set_reset(State::High, State::High));
+        assert_eq!(Some(State::Undefined), set_reset(State::HighImpedance, State::Low));
+    }
+}
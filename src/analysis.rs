@@ -0,0 +1,2 @@
+pub mod truth_table;
+pub mod boolean;
@@ -0,0 +1,576 @@
+use std::{
+    collections::{HashMap, HashSet},
+    error::Error,
+    fmt,
+};
+use crate::core::module::ModuleEnv;
+use crate::core::sim::Probe;
+use crate::contract::State;
+use crate::analysis::truth_table::{TruthTable, AnalysisError, MAX_INPUTS};
+
+/// A Boolean expression.
+#[derive(Debug, Clone, PartialEq)]
+pub enum Expr {
+    /// A constant value.
+    Const(bool),
+    /// A variable, e.g. an input of a circuit.
+    Var(String),
+    /// The negation of an expression.
+    Not(Box<Expr>),
+    /// The conjunction of all operands.
+    And(Vec<Expr>),
+    /// The disjunction of all operands.
+    Or(Vec<Expr>),
+}
+
+impl Expr {
+    /// Evaluate the expression using three-valued logic.
+    ///
+    /// Variables not contained in `vars` are [`State::Undefined`].
+    pub fn eval(&self, vars: &HashMap<String, State>) -> State {
+        match self {
+            Expr::Const(b) => State::from(*b),
+            Expr::Var(name) => vars.get(name).copied().unwrap_or(State::Undefined),
+            Expr::Not(e) => !e.eval(vars),
+            Expr::And(v) => v.iter().fold(State::High, |acc, e| acc & e.eval(vars)),
+            Expr::Or(v) => v.iter().fold(State::Low, |acc, e| acc | e.eval(vars)),
+        }
+    }
+
+    /// Get the names of all variables used within the expression,
+    /// in order of their first appearance.
+    pub fn vars(&self) -> Vec<String> {
+        fn collect(e: &Expr, v: &mut Vec<String>) {
+            match e {
+                Expr::Const(_) => {},
+                Expr::Var(name) => {
+                    if !v.contains(name) {
+                        v.push(name.clone());
+                    }
+                },
+                Expr::Not(e) => collect(e, v),
+                Expr::And(es) | Expr::Or(es) => {
+                    for e in es {
+                        collect(e, v);
+                    }
+                },
+            }
+        }
+
+        let mut v = Vec::new();
+        collect(self, &mut v);
+        v
+    }
+
+    /// Build a conjunction, avoiding redundant nesting.
+    fn and(mut v: Vec<Expr>) -> Expr {
+        match v.len() {
+            0 => Expr::Const(true),
+            1 => v.remove(0),
+            _ => Expr::And(v),
+        }
+    }
+
+    /// Build a disjunction, avoiding redundant nesting.
+    fn or(mut v: Vec<Expr>) -> Expr {
+        match v.len() {
+            0 => Expr::Const(false),
+            1 => v.remove(0),
+            _ => Expr::Or(v),
+        }
+    }
+
+    fn fmt_operand(&self, f: &mut fmt::Formatter<'_>, parens: bool) -> fmt::Result {
+        if parens {
+            write!(f, "({})", self)
+        } else {
+            write!(f, "{}", self)
+        }
+    }
+}
+
+impl fmt::Display for Expr {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Expr::Const(b) => {
+                write!(f, "{}", if *b { 1 } else { 0 })
+            },
+            Expr::Var(name) => {
+                write!(f, "{}", name)
+            },
+            Expr::Not(e) => {
+                write!(f, "!")?;
+                e.fmt_operand(f, matches!(**e, Expr::And(_) | Expr::Or(_)))
+            },
+            Expr::And(v) => {
+                for (i, e) in v.iter().enumerate() {
+                    if i > 0 {
+                        write!(f, " & ")?;
+                    }
+                    e.fmt_operand(f, matches!(e, Expr::Or(_)))?;
+                }
+                Ok(())
+            },
+            Expr::Or(v) => {
+                for (i, e) in v.iter().enumerate() {
+                    if i > 0 {
+                        write!(f, " | ")?;
+                    }
+                    e.fmt_operand(f, false)?;
+                }
+                Ok(())
+            },
+        }
+    }
+}
+
+/// A product term of `n` variables.
+///
+/// Bit `n - 1 - i` corresponds to the `i`-th variable. Bits set
+/// in `mask` are don't cares, i.e. the variable doesn't appear
+/// within the term.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+struct Implicant {
+    bits: usize,
+    mask: usize,
+}
+
+impl Implicant {
+    fn covers(&self, minterm: usize) -> bool {
+        minterm & !self.mask == self.bits
+    }
+
+    /// Sort key that orders terms by their literals, variables
+    /// closer to the front coming first.
+    fn key(&self, n: usize) -> Vec<u8> {
+        (0..n)
+            .map(|i| {
+                let bit = 1 << (n - 1 - i);
+                if self.mask & bit != 0 { 2 } else if self.bits & bit != 0 { 0 } else { 1 }
+            })
+            .collect()
+    }
+
+    fn to_expr(self, vars: &[String]) -> Expr {
+        let n = vars.len();
+        let literals = vars
+            .iter()
+            .enumerate()
+            .filter(|(i, _)| self.mask & (1 << (n - 1 - i)) == 0)
+            .map(|(i, name)| {
+                if self.bits & (1 << (n - 1 - i)) != 0 {
+                    Expr::Var(name.clone())
+                } else {
+                    Expr::Not(Box::new(Expr::Var(name.clone())))
+                }
+            })
+            .collect();
+        Expr::and(literals)
+    }
+}
+
+/// Find all prime implicants of the given terms using the
+/// Quine-McCluskey method.
+fn prime_implicants(terms: &[usize]) -> Vec<Implicant> {
+    let mut current: Vec<Implicant> = terms
+        .iter()
+        .map(|t| Implicant { bits: *t, mask: 0 })
+        .collect();
+    let mut primes = Vec::new();
+
+    while !current.is_empty() {
+        let mut next = HashSet::new();
+        let mut used = vec![false; current.len()];
+
+        for i in 0..current.len() {
+            for j in (i + 1)..current.len() {
+                let (a, b) = (current[i], current[j]);
+                let diff = a.bits ^ b.bits;
+                if a.mask == b.mask && diff.count_ones() == 1 {
+                    next.insert(Implicant { bits: a.bits & !diff, mask: a.mask | diff });
+                    used[i] = true;
+                    used[j] = true;
+                }
+            }
+        }
+
+        for (imp, used) in current.iter().zip(used) {
+            if !used && !primes.contains(imp) {
+                primes.push(*imp);
+            }
+        }
+
+        current = next.into_iter().collect();
+        current.sort_by_key(|imp| (imp.mask, imp.bits));
+    }
+
+    primes
+}
+
+/// Select a set of prime implicants that covers all minterms.
+///
+/// Essential prime implicants are always selected, the remaining
+/// minterms are covered greedily.
+fn cover(primes: &[Implicant], minterms: &[usize]) -> Vec<Implicant> {
+    let mut chosen: Vec<Implicant> = Vec::new();
+
+    for m in minterms {
+        let covering: Vec<&Implicant> = primes.iter().filter(|p| p.covers(*m)).collect();
+        if covering.len() == 1 && !chosen.contains(covering[0]) {
+            chosen.push(*covering[0]);
+        }
+    }
+
+    let mut remaining: Vec<usize> = minterms
+        .iter()
+        .copied()
+        .filter(|m| !chosen.iter().any(|p| p.covers(*m)))
+        .collect();
+
+    while !remaining.is_empty() {
+        let best = primes
+            .iter()
+            .max_by_key(|p| {
+                (remaining.iter().filter(|m| p.covers(**m)).count(), p.mask.count_ones())
+            })
+            .copied()
+            .unwrap();
+        chosen.push(best);
+        remaining.retain(|m| !best.covers(*m));
+    }
+
+    chosen
+}
+
+/// The Boolean function of a single output.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Equation {
+    /// The name of the output.
+    pub output: String,
+    /// The canonical sum of products.
+    pub sop: Expr,
+    /// The minimised sum of products.
+    pub minimal: Expr,
+}
+
+impl Equation {
+    /// Derive one equation per output of the given truth table.
+    ///
+    /// Rows whose output isn't at a defined level (e.g. undefined
+    /// or high impedance) are treated as don't cares during minimisation.
+    pub fn from_table(table: &TruthTable) -> Vec<Equation> {
+        let vars = table.inputs();
+        let n = vars.len();
+        let index = |inputs: &[State]| {
+            inputs.iter().fold(0, |acc, s| (acc << 1) | (*s == State::High) as usize)
+        };
+
+        table.outputs()
+            .iter()
+            .enumerate()
+            .map(|(o, name)| {
+                let mut minterms = Vec::new();
+                let mut dont_cares = Vec::new();
+                for row in table.rows() {
+                    match row.outputs[o] {
+                        State::High => minterms.push(index(&row.inputs)),
+                        State::Low => {},
+                        _ => dont_cares.push(index(&row.inputs)),
+                    }
+                }
+
+                let sop = Expr::or(
+                    minterms
+                        .iter()
+                        .map(|m| Implicant { bits: *m, mask: 0 }.to_expr(vars))
+                        .collect()
+                );
+
+                let terms: Vec<usize> = minterms.iter().chain(dont_cares.iter()).copied().collect();
+                let mut implicants = cover(&prime_implicants(&terms), &minterms);
+                implicants.sort_by_key(|imp| imp.key(n));
+                let minimal = Expr::or(implicants.iter().map(|imp| imp.to_expr(vars)).collect());
+
+                Equation { output: name.clone(), sop, minimal }
+            })
+            .collect()
+    }
+
+    /// Derive the equations of a combinational circuit by generating
+    /// its truth table first (see [`TruthTable::generate`]).
+    pub fn from_circuit(
+        env: &mut ModuleEnv,
+        inputs: &[Probe],
+        outputs: &[Probe],
+    ) -> Result<Vec<Equation>, AnalysisError> {
+        Ok(Self::from_table(&TruthTable::generate(env, inputs, outputs, MAX_INPUTS)?))
+    }
+}
+
+impl fmt::Display for Equation {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{} = {}", self.output, self.minimal)
+    }
+}
+
+/// An error that occurred while parsing an expression.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ParseError {
+    /// The column (starting at 1) the error occurred at.
+    pub column: usize,
+    /// A description of the error.
+    pub message: String,
+}
+
+impl fmt::Display for ParseError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "column {}: {}", self.column, self.message)
+    }
+}
+
+impl Error for ParseError {
+
+}
+
+/// A recursive descent parser for Boolean expressions.
+///
+/// ```text
+/// or    := and ('|' and)*
+/// and   := unary ('&' unary)*
+/// unary := '!' unary | '(' or ')' | '0' | '1' | identifier
+/// ```
+struct Parser<'a> {
+    chars: Vec<char>,
+    pos: usize,
+    text: &'a str,
+}
+
+impl<'a> Parser<'a> {
+    fn new(text: &'a str) -> Self {
+        Self { chars: text.chars().collect(), pos: 0, text }
+    }
+
+    fn error<T>(&self, message: &str) -> Result<T, ParseError> {
+        Err(ParseError { column: self.pos + 1, message: message.to_string() })
+    }
+
+    fn peek(&mut self) -> Option<char> {
+        while self.pos < self.chars.len() && self.chars[self.pos].is_whitespace() {
+            self.pos += 1;
+        }
+        self.chars.get(self.pos).copied()
+    }
+
+    fn eat(&mut self, c: char) -> bool {
+        if self.peek() == Some(c) {
+            self.pos += 1;
+            true
+        } else {
+            false
+        }
+    }
+
+    fn identifier(&mut self) -> Option<String> {
+        let start = self.pos;
+        while self.pos < self.chars.len()
+            && (self.chars[self.pos].is_alphanumeric() || self.chars[self.pos] == '_') {
+            self.pos += 1;
+        }
+
+        if start == self.pos {
+            None
+        } else {
+            Some(self.chars[start..self.pos].iter().collect())
+        }
+    }
+
+    fn or(&mut self) -> Result<Expr, ParseError> {
+        let mut v = vec![self.and()?];
+        while self.eat('|') {
+            v.push(self.and()?);
+        }
+        Ok(Expr::or(v))
+    }
+
+    fn and(&mut self) -> Result<Expr, ParseError> {
+        let mut v = vec![self.unary()?];
+        while self.eat('&') {
+            v.push(self.unary()?);
+        }
+        Ok(Expr::and(v))
+    }
+
+    fn unary(&mut self) -> Result<Expr, ParseError> {
+        match self.peek() {
+            Some('!') => {
+                self.pos += 1;
+                Ok(Expr::Not(Box::new(self.unary()?)))
+            },
+            Some('(') => {
+                self.pos += 1;
+                let e = self.or()?;
+                if !self.eat(')') {
+                    return self.error("expected `)`");
+                }
+                Ok(e)
+            },
+            Some(c) if c.is_alphanumeric() || c == '_' => {
+                match self.identifier().unwrap().as_str() {
+                    "0" => Ok(Expr::Const(false)),
+                    "1" => Ok(Expr::Const(true)),
+                    name if name.starts_with(|c: char| c.is_ascii_digit()) => {
+                        self.error("identifiers must not start with a digit")
+                    },
+                    name => Ok(Expr::Var(name.to_string())),
+                }
+            },
+            Some(c) => self.error(&format!("unexpected `{}`", c)),
+            None => self.error("unexpected end of expression"),
+        }
+    }
+
+    fn parse(mut self) -> Result<Expr, ParseError> {
+        if self.text.trim().is_empty() {
+            return self.error("empty expression");
+        }
+
+        let e = self.or()?;
+        match self.peek() {
+            Some(c) => self.error(&format!("unexpected `{}`", c)),
+            None => Ok(e),
+        }
+    }
+}
+
+impl Expr {
+    /// Parse an expression like `(A & B) | !C`.
+    pub fn parse(text: &str) -> Result<Expr, ParseError> {
+        Parser::new(text).parse()
+    }
+}
+
+impl Equation {
+    /// Parse an equation like `Q = (A & B) | !C`.
+    ///
+    /// Both the canonical and the minimal form of the
+    /// resulting equation are the parsed expression.
+    pub fn parse(text: &str) -> Result<Equation, ParseError> {
+        let (output, expr) = match text.split_once('=') {
+            Some(parts) => parts,
+            None => return Err(ParseError { column: 1, message: "expected `=`".to_string() }),
+        };
+
+        let output = output.trim();
+        if output.is_empty() || !output.chars().all(|c| c.is_alphanumeric() || c == '_') {
+            return Err(ParseError { column: 1, message: "invalid output name".to_string() });
+        }
+
+        let expr = Expr::parse(expr).map_err(|mut e| {
+            e.column += text.find('=').unwrap() + 1;
+            e
+        })?;
+
+        Ok(Equation { output: output.to_string(), sop: expr.clone(), minimal: expr })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::analysis::truth_table::Row;
+    use crate::analysis::truth_table::tests::test_env;
+    use crate::core::sim::PinRef;
+    use crate::misc::Point;
+
+    fn table(inputs: &[&str], outputs: &[State]) -> TruthTable {
+        let n = inputs.len();
+        let rows = outputs
+            .iter()
+            .enumerate()
+            .map(|(r, o)| Row {
+                inputs: (0..n).map(|i| State::from((r >> (n - 1 - i)) & 1 == 1)).collect(),
+                outputs: vec![*o],
+            })
+            .collect();
+        TruthTable::new(inputs.iter().map(|s| s.to_string()).collect(), vec!["Q".to_string()], rows)
+    }
+
+    #[test]
+    fn majority_test() {
+        use State::*;
+        let eq = &Equation::from_table(&table(&["A", "B", "C"], &[Low, Low, Low, High, Low, High, High, High]))[0];
+        assert_eq!("!A & B & C | A & !B & C | A & B & !C | A & B & C", eq.sop.to_string());
+        assert_eq!("Q = A & B | A & C | B & C", eq.to_string());
+    }
+
+    #[test]
+    fn dont_care_test() {
+        use State::*;
+        let eq = &Equation::from_table(&table(&["A", "B"], &[Low, High, Undefined, High]))[0];
+        assert_eq!("!A & B | A & B", eq.sop.to_string());
+        assert_eq!("B", eq.minimal.to_string());
+    }
+
+    #[test]
+    fn constant_test() {
+        use State::*;
+        assert_eq!("0", Equation::from_table(&table(&["A"], &[Low, Low]))[0].minimal.to_string());
+        assert_eq!("1", Equation::from_table(&table(&["A"], &[High, High]))[0].minimal.to_string());
+    }
+
+    #[test]
+    fn eval_test() {
+        let e = Expr::Or(vec![
+            Expr::And(vec![Expr::Var("A".to_string()), Expr::Var("B".to_string())]),
+            Expr::Not(Box::new(Expr::Var("C".to_string()))),
+        ]);
+        let mut vars = HashMap::new();
+        vars.insert("A".to_string(), State::High);
+        vars.insert("C".to_string(), State::High);
+        assert_eq!(State::Undefined, e.eval(&vars));
+        vars.insert("B".to_string(), State::Low);
+        assert_eq!(State::Low, e.eval(&vars));
+        assert_eq!("A & B | !C", e.to_string());
+    }
+
+    #[test]
+    fn from_circuit_test() {
+        let mut env = test_env();
+        let a = env.instantiate("Test", "source", Point { x: 0.0, y: 0.0 }).unwrap();
+        let b = env.instantiate("Test", "source", Point { x: 0.0, y: 50.0 }).unwrap();
+        let q = env.instantiate("Test", "and", Point { x: 100.0, y: 25.0 }).unwrap();
+        env.connect(PinRef::new(a, 0), PinRef::new(q, 0));
+        env.connect(PinRef::new(b, 0), PinRef::new(q, 1));
+
+        let eqs = Equation::from_circuit(
+            &mut env,
+            &[Probe::new("A", PinRef::new(a, 0)), Probe::new("B", PinRef::new(b, 0))],
+            &[Probe::new("Q", PinRef::new(q, 0))],
+        ).unwrap();
+        assert_eq!("Q = A & B", eqs[0].to_string());
+    }
+
+    #[test]
+    fn parse_test() {
+        let eq = Equation::parse("Q = (A & B) | !C").unwrap();
+        assert_eq!("Q", eq.output);
+        assert_eq!("A & B | !C", eq.minimal.to_string());
+        assert_eq!(vec!["A", "B", "C"], eq.minimal.vars());
+
+        assert_eq!("!(A | B) & C", Expr::parse("!(A|B)&C").unwrap().to_string());
+        assert_eq!(Expr::Const(true), Expr::parse(" 1 ").unwrap());
+    }
+
+    #[test]
+    fn parse_error_test() {
+        assert_eq!(
+            Err(ParseError { column: 7, message: "expected `)`".to_string() }),
+            Expr::parse("(A & B")
+        );
+        assert_eq!(
+            Err(ParseError { column: 8, message: "unexpected end of expression".to_string() }),
+            Equation::parse("Q = A &").map(|_| ())
+        );
+        assert!(Equation::parse("A & B").is_err());
+    }
+}
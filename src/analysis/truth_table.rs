@@ -0,0 +1,418 @@
+use std::{
+    error::Error,
+    fmt,
+};
+use crate::core::module::ModuleEnv;
+use crate::core::sim::Probe;
+use crate::contract::State;
+
+/// The default upper bound for the number of inputs of a truth table.
+///
+/// A table with `n` inputs has `2^n` rows, each requiring the
+/// circuit to settle.
+pub const MAX_INPUTS: usize = 12;
+
+/// The number of steps a circuit gets to settle for each row.
+pub const SETTLE_STEPS: usize = 256;
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum AnalysisError {
+    /// The number of inputs exceeds the given cap.
+    TooManyInputs(usize, usize),
+    /// A probe refers to an instance that doesn't exist.
+    UnknownInstance(String),
+    /// A textual truth table is malformed.
+    InvalidTable(usize, String),
+}
+
+impl fmt::Display for AnalysisError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            AnalysisError::TooManyInputs(n, max) => {
+                write!(f, "too many inputs ({} > {})", n, max)
+            },
+            AnalysisError::UnknownInstance(name) => {
+                write!(f, "`{}` refers to an unknown instance", name)
+            },
+            AnalysisError::InvalidTable(line, e) => {
+                write!(f, "line {}: {}", line, e)
+            },
+        }
+    }
+}
+
+impl Error for AnalysisError {
+
+}
+
+/// A single row of a [`TruthTable`].
+#[derive(Debug, Clone, PartialEq)]
+pub struct Row {
+    /// The states applied to the inputs.
+    pub inputs: Vec<State>,
+    /// The resulting states of the outputs.
+    ///
+    /// Outputs of a circuit that didn't settle are [`State::Undefined`].
+    pub outputs: Vec<State>,
+}
+
+/// The truth table of a circuit.
+#[derive(Debug, Clone, PartialEq)]
+pub struct TruthTable {
+    /// The names of the inputs.
+    inputs: Vec<String>,
+    /// The names of the outputs.
+    outputs: Vec<String>,
+    /// One row for every input combination.
+    rows: Vec<Row>,
+}
+
+impl TruthTable {
+    /// Create a new truth table from its raw parts.
+    pub fn new(inputs: Vec<String>, outputs: Vec<String>, rows: Vec<Row>) -> Self {
+        Self { inputs, outputs, rows }
+    }
+
+    /// Generate the truth table for the circuit within `env`.
+    ///
+    /// The `inputs` are output pins (e.g. of a switch) that get forced
+    /// to every possible combination of [`State::Low`] and [`State::High`],
+    /// the first input being the most significant one. After the circuit
+    /// has settled the states of the `outputs` are sampled.
+    ///
+    /// The analysis runs [isolated](ModuleEnv::isolated), so pins the user
+    /// forced, the recorded history and the time stay as they were.
+    ///
+    /// Fails if there are more than `max_inputs` inputs.
+    pub fn generate(
+        env: &mut ModuleEnv,
+        inputs: &[Probe],
+        outputs: &[Probe],
+        max_inputs: usize,
+    ) -> Result<Self, AnalysisError> {
+        if inputs.len() > max_inputs {
+            return Err(AnalysisError::TooManyInputs(inputs.len(), max_inputs));
+        }
+
+        for probe in inputs.iter().chain(outputs.iter()) {
+            if !env.instances().contains_key(&probe.pin.instance) {
+                return Err(AnalysisError::UnknownInstance(probe.name.clone()));
+            }
+        }
+
+        let n = inputs.len();
+        let rows = env.isolated(|env| {
+            let mut rows = Vec::with_capacity(1 << n);
+            for combination in 0..(1usize << n) {
+                let states: Vec<State> = (0..n)
+                    .map(|i| State::from((combination >> (n - 1 - i)) & 1 == 1))
+                    .collect();
+
+                for (probe, state) in inputs.iter().zip(states.iter()) {
+                    env.force(probe.pin, *state);
+                }
+
+                let settled = env.settle(SETTLE_STEPS);
+                let results = outputs
+                    .iter()
+                    .map(|probe| if settled { env.output(&probe.pin) } else { State::Undefined })
+                    .collect();
+
+                rows.push(Row { inputs: states, outputs: results });
+            }
+            rows
+        });
+
+        Ok(Self {
+            inputs: inputs.iter().map(|p| p.name.clone()).collect(),
+            outputs: outputs.iter().map(|p| p.name.clone()).collect(),
+            rows,
+        })
+    }
+
+    /// Parse a truth table in CSV or Markdown format (as produced by
+    /// [`TruthTable::to_csv`] and [`TruthTable::to_markdown`]) whose
+    /// last `outputs` columns are outputs.
+    ///
+    /// Input combinations missing from the text are added with
+    /// [`State::Undefined`] outputs.
+    pub fn parse(text: &str, outputs: usize) -> Result<Self, AnalysisError> {
+        let mut lines = text
+            .lines()
+            .enumerate()
+            .map(|(i, line)| (i + 1, line.trim()))
+            .filter(|(_, line)| !line.is_empty() && !line.chars().all(|c| "|-: ".contains(c)))
+            .map(|(i, line)| {
+                let cells: Vec<String> = line
+                    .split(|c: char| c == ',' || c == '|')
+                    .map(|c| c.trim().to_string())
+                    .filter(|c| !c.is_empty())
+                    .collect();
+                (i, cells)
+            });
+
+        let header = match lines.next() {
+            Some((_, header)) => header,
+            None => return Err(AnalysisError::InvalidTable(1, "missing header".to_string())),
+        };
+        if header.len() <= outputs {
+            return Err(AnalysisError::InvalidTable(1, "expected at least one input column".to_string()));
+        }
+
+        let n = header.len() - outputs;
+        if n > MAX_INPUTS {
+            return Err(AnalysisError::TooManyInputs(n, MAX_INPUTS));
+        }
+
+        let mut rows: Vec<Row> = (0..(1usize << n))
+            .map(|r| Row {
+                inputs: (0..n).map(|i| State::from((r >> (n - 1 - i)) & 1 == 1)).collect(),
+                outputs: vec![State::Undefined; outputs],
+            })
+            .collect();
+
+        for (line, cells) in lines {
+            if cells.len() != header.len() {
+                return Err(AnalysisError::InvalidTable(
+                    line,
+                    format!("expected {} columns but found {}", header.len(), cells.len())
+                ));
+            }
+
+            let mut states = Vec::with_capacity(cells.len());
+            for cell in cells.iter() {
+                states.push(match cell.as_str() {
+                    "0" => State::Low,
+                    "1" => State::High,
+                    "U" | "X" | "x" | "-" => State::Undefined,
+                    "Z" | "z" => State::HighImpedance,
+                    c => return Err(AnalysisError::InvalidTable(line, format!("invalid state `{}`", c))),
+                });
+            }
+
+            let mut index = 0;
+            for s in states[..n].iter() {
+                index = match s {
+                    State::Low => index << 1,
+                    State::High => (index << 1) | 1,
+                    _ => return Err(AnalysisError::InvalidTable(line, "inputs must be 0 or 1".to_string())),
+                };
+            }
+            rows[index].outputs = states[n..].to_vec();
+        }
+
+        Ok(Self {
+            inputs: header[..n].to_vec(),
+            outputs: header[n..].to_vec(),
+            rows,
+        })
+    }
+
+    /// Get the names of the inputs.
+    pub fn inputs(&self) -> &[String] {
+        &self.inputs
+    }
+
+    /// Get the names of the outputs.
+    pub fn outputs(&self) -> &[String] {
+        &self.outputs
+    }
+
+    /// Get all rows.
+    pub fn rows(&self) -> &[Row] {
+        &self.rows
+    }
+
+    fn header(&self) -> impl Iterator<Item = &String> {
+        self.inputs.iter().chain(self.outputs.iter())
+    }
+
+    fn cells(row: &Row) -> impl Iterator<Item = String> + '_ {
+        row.inputs.iter().chain(row.outputs.iter()).map(|s| s.symbol().to_string())
+    }
+
+    /// Export the table as comma separated values.
+    pub fn to_csv(&self) -> String {
+        let mut s = self.header().cloned().collect::<Vec<_>>().join(",");
+        s += "\n";
+        for row in self.rows.iter() {
+            s += &Self::cells(row).collect::<Vec<_>>().join(",");
+            s += "\n";
+        }
+        s
+    }
+
+    /// Export the table as Markdown table.
+    pub fn to_markdown(&self) -> String {
+        let mut s = format!("| {} |\n", self.header().cloned().collect::<Vec<_>>().join(" | "));
+        s += &format!("|{}\n", "---|".repeat(self.inputs.len() + self.outputs.len()));
+        for row in self.rows.iter() {
+            s += &format!("| {} |\n", Self::cells(row).collect::<Vec<_>>().join(" | "));
+        }
+        s
+    }
+}
+
+#[cfg(test)]
+pub(crate) mod tests {
+    use super::*;
+    use wasmer::{Store, Imports};
+    use crate::core::contract::Contract;
+    use crate::core::sim::PinRef;
+    use crate::misc::Point;
+
+    /// A module without inputs and a single output that is
+    /// always undefined unless it's forced.
+    pub const SOURCE_WAT: &str = r#"
+        (module
+            (func (export "get_inputs_nr") (result i32) i32.const 0)
+            (func (export "get_outputs_nr") (result i32) i32.const 1)
+            (func (export "get_output") (param $i i32) (result i32) i32.const 0)
+        )
+    "#;
+
+    /// A two input AND gate using three-valued logic.
+    pub const AND_WAT: &str = r#"
+        (module
+            (global $a (mut i32) (i32.const 0))
+            (global $b (mut i32) (i32.const 0))
+            (global $q (mut i32) (i32.const 0))
+            (func (export "get_inputs_nr") (result i32) i32.const 2)
+            (func (export "get_outputs_nr") (result i32) i32.const 1)
+            (func (export "set_input") (param $i i32) (param $s i32)
+                (if (i32.eqz (local.get $i))
+                    (then (global.set $a (local.get $s)))
+                    (else (global.set $b (local.get $s)))
+                )
+            )
+            (func (export "get_output") (param $i i32) (result i32) global.get $q)
+            (func (export "update")
+                (if (i32.or (i32.eq (global.get $a) (i32.const 1)) (i32.eq (global.get $b) (i32.const 1)))
+                    (then (global.set $q (i32.const 1)))
+                    (else
+                        (if (i32.and (i32.eq (global.get $a) (i32.const 2)) (i32.eq (global.get $b) (i32.const 2)))
+                            (then (global.set $q (i32.const 2)))
+                            (else (global.set $q (i32.const 0)))
+                        )
+                    )
+                )
+            )
+        )
+    "#;
+
+    /// Create an environment that contains the `Test::source`
+    /// and `Test::and` modules.
+    pub fn test_env() -> ModuleEnv {
+        let store = Store::default();
+        let contract = Contract {
+            exports: vec![],
+            imports: vec![],
+            optional: vec![],
+        };
+        let mut env = ModuleEnv::new(store, Imports::new(), contract);
+        env.add_module_raw("Test", "source", SOURCE_WAT.as_bytes()).unwrap();
+        env.add_module_raw("Test", "and", AND_WAT.as_bytes()).unwrap();
+        env
+    }
+
+    #[test]
+    fn and_truth_table_test() {
+        let mut env = test_env();
+        let a = env.instantiate("Test", "source", Point { x: 0.0, y: 0.0 }).unwrap();
+        let b = env.instantiate("Test", "source", Point { x: 0.0, y: 50.0 }).unwrap();
+        let q = env.instantiate("Test", "and", Point { x: 100.0, y: 25.0 }).unwrap();
+        assert!(env.connect(PinRef::new(a, 0), PinRef::new(q, 0)));
+        assert!(env.connect(PinRef::new(b, 0), PinRef::new(q, 1)));
+
+        let table = TruthTable::generate(
+            &mut env,
+            &[Probe::new("A", PinRef::new(a, 0)), Probe::new("B", PinRef::new(b, 0))],
+            &[Probe::new("Q", PinRef::new(q, 0))],
+            MAX_INPUTS,
+        ).unwrap();
+
+        assert_eq!(4, table.rows().len());
+        assert_eq!("A,B,Q\n0,0,0\n0,1,0\n1,0,0\n1,1,1\n", table.to_csv());
+        assert_eq!(
+            "| A | B | Q |\n|---|---|---|\n| 0 | 0 | 0 |\n| 0 | 1 | 0 |\n| 1 | 0 | 0 |\n| 1 | 1 | 1 |\n",
+            table.to_markdown()
+        );
+    }
+
+    #[test]
+    fn generate_keeps_state_test() {
+        let mut env = test_env();
+        let a = env.instantiate("Test", "source", Point { x: 0.0, y: 0.0 }).unwrap();
+        let b = env.instantiate("Test", "source", Point { x: 0.0, y: 50.0 }).unwrap();
+        let q = env.instantiate("Test", "and", Point { x: 100.0, y: 25.0 }).unwrap();
+        assert!(env.connect(PinRef::new(a, 0), PinRef::new(q, 0)));
+        assert!(env.connect(PinRef::new(b, 0), PinRef::new(q, 1)));
+
+        // The user forced A high and watches Q.
+        env.force(PinRef::new(a, 0), State::High);
+        env.add_probe(Probe::new("Q", PinRef::new(q, 0)));
+        env.settle(SETTLE_STEPS);
+        let history = env.history().clone();
+        let steps = env.simulator().steps();
+
+        TruthTable::generate(
+            &mut env,
+            &[Probe::new("A", PinRef::new(a, 0)), Probe::new("B", PinRef::new(b, 0))],
+            &[Probe::new("Q", PinRef::new(q, 0))],
+            MAX_INPUTS,
+        ).unwrap();
+
+        assert_eq!(&history, env.history());
+        assert_eq!(steps, env.simulator().steps());
+        assert_eq!(State::High, env.output(&PinRef::new(a, 0)));
+        assert_eq!(State::Undefined, env.output(&PinRef::new(b, 0)));
+        assert_eq!(State::Undefined, env.output(&PinRef::new(q, 0)));
+    }
+
+    #[test]
+    fn undefined_output_test() {
+        let mut env = test_env();
+        let a = env.instantiate("Test", "source", Point { x: 0.0, y: 0.0 }).unwrap();
+        let q = env.instantiate("Test", "and", Point { x: 100.0, y: 25.0 }).unwrap();
+        // The second input of the AND gate is left floating.
+        assert!(env.connect(PinRef::new(a, 0), PinRef::new(q, 0)));
+
+        let table = TruthTable::generate(
+            &mut env,
+            &[Probe::new("A", PinRef::new(a, 0))],
+            &[Probe::new("Q", PinRef::new(q, 0))],
+            MAX_INPUTS,
+        ).unwrap();
+
+        assert_eq!("A,Q\n0,0\n1,U\n", table.to_csv());
+    }
+
+    #[test]
+    fn parse_test() {
+        let table = TruthTable::parse("A,B,Q\n0,0,0\n0,1,0\n1,1,1\n", 1).unwrap();
+        assert_eq!("A,B,Q\n0,0,0\n0,1,0\n1,0,U\n1,1,1\n", table.to_csv());
+        assert_eq!(table, TruthTable::parse(&table.to_markdown(), 1).unwrap());
+
+        assert_eq!(
+            Err(AnalysisError::InvalidTable(3, "invalid state `2`".to_string())),
+            TruthTable::parse("A,Q\n0,1\n1,2\n", 1)
+        );
+    }
+
+    #[test]
+    fn too_many_inputs_test() {
+        let mut env = test_env();
+        let a = env.instantiate("Test", "source", Point { x: 0.0, y: 0.0 }).unwrap();
+        let b = env.instantiate("Test", "source", Point { x: 0.0, y: 50.0 }).unwrap();
+
+        assert_eq!(
+            Err(AnalysisError::TooManyInputs(2, 1)),
+            TruthTable::generate(
+                &mut env,
+                &[Probe::new("A", PinRef::new(a, 0)), Probe::new("B", PinRef::new(b, 0))],
+                &[],
+                1,
+            )
+        );
+    }
+}
@@ -0,0 +1,72 @@
+pub mod circuit;
+pub mod testbench;
+
+use wasmer::{Store, FunctionEnv};
+use std::{
+    error::Error,
+    fmt,
+};
+use crate::core::module::ModuleEnv;
+use crate::core::contract::megs_contract_with;
+use crate::core::report::{Recorder, recording_draw_fns};
+use crate::contract::FileEnv;
+
+#[derive(Debug)]
+pub enum BenchError {
+    /// A line of a circuit or test bench couldn't be parsed.
+    ParseErr(usize, String),
+    /// A line refers to an instance, module or net that doesn't exist,
+    /// or to a setting the instance doesn't support.
+    ResolveErr(usize, String),
+    /// A test bench doesn't name the circuit to test.
+    NoCircuit,
+    IOErr(std::io::Error),
+}
+
+impl fmt::Display for BenchError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            BenchError::ParseErr(line, msg) | BenchError::ResolveErr(line, msg) => {
+                write!(f, "line {}: {}", line, msg)
+            },
+            BenchError::NoCircuit => {
+                write!(f, "the test bench doesn't name a circuit")
+            },
+            BenchError::IOErr(e) => {
+                write!(f, "{}", e)
+            },
+        }
+    }
+}
+
+impl Error for BenchError {
+
+}
+
+impl From<std::io::Error> for BenchError {
+    fn from(e: std::io::Error) -> Self {
+        Self::IOErr(e)
+    }
+}
+
+/// Create an environment that doesn't need a window, e.g. to run
+/// test benches or to check modules from the command line.
+///
+/// The environment provides the same imports and contract as the
+/// application, but drawing is only recorded, see
+/// [`ModuleEnv::record_drawing`]. Modules are always compiled,
+/// callers that want to use the cache of compiled modules enable it
+/// ([`ModuleEnv::enable_cache`]).
+pub fn headless_env() -> ModuleEnv {
+    let mut store = Store::default();
+    let files = FunctionEnv::new(&mut store, FileEnv::default());
+    let recorder = FunctionEnv::new(&mut store, Recorder::default());
+    let draw = recording_draw_fns(&mut store, &recorder);
+    let (imports, contract) = megs_contract_with(&mut store, &files, draw);
+
+    let mut env = ModuleEnv::new(store, imports, contract);
+    env.set_file_env(files);
+    env.set_recorder(recorder);
+    env.enable_wasi();
+    env
+}
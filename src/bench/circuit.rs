@@ -0,0 +1,263 @@
+use std::collections::{HashMap, HashSet};
+use uuid::Uuid;
+use crate::bench::BenchError;
+use crate::core::module::ModuleEnv;
+use crate::core::sim::{PinRef, Probe};
+use crate::misc::Point;
+
+/// The settings of an instance a circuit can change.
+pub const SETTINGS: [&str; 4] = ["inputs", "bits", "data_bits", "delay"];
+
+/// A pin given as `<instance>.<pin>`, e.g. `a.0`.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Endpoint {
+    /// The name of the instance.
+    pub instance: String,
+    /// The index of the pin.
+    pub pin: usize,
+}
+
+impl Endpoint {
+    fn parse(text: &str, line: usize) -> Result<Self, BenchError> {
+        text.rsplit_once('.')
+            .and_then(|(instance, pin)| Some(Self { instance: instance.to_string(), pin: pin.parse().ok()? }))
+            .ok_or_else(|| BenchError::ParseErr(line, format!("`{}` is not a pin like `a.0`", text)))
+    }
+}
+
+/// A statement of a [`Circuit`] together with its line number.
+#[derive(Debug, Clone, PartialEq)]
+pub enum Statement {
+    /// `instance <name> <category>/<module>`
+    Instance(usize, String, String, String),
+    /// `set <name> <inputs|bits|data_bits|delay> <value>`
+    Set(usize, String, String, u64),
+    /// `wire <instance>.<output> -> <instance>.<input>`
+    Wire(usize, Endpoint, Endpoint),
+    /// `probe <net> <instance>.<output>`
+    Probe(usize, String, Endpoint),
+}
+
+/// A circuit written by hand as text, one statement per line:
+///
+/// ```text
+/// # A half adder
+/// instance a Input Controlls/switch
+/// instance b Input Controlls/switch
+/// instance s Gates/xor
+/// instance c Gates/and
+/// set s delay 2
+/// wire a.0 -> s.0
+/// wire b.0 -> s.1
+/// wire a.0 -> c.0
+/// wire b.0 -> c.1
+/// probe A a.0
+/// probe B b.0
+/// probe S s.0
+/// probe C c.0
+/// ```
+///
+/// Everything following a `#` is a comment and every instance needs
+/// a name of its own. Probes name the nets that test benches,
+/// breakpoints and watches refer to.
+#[derive(Debug, Clone, PartialEq, Default)]
+pub struct Circuit {
+    statements: Vec<Statement>,
+}
+
+impl Circuit {
+    /// Parse a circuit from its textual representation.
+    pub fn parse(text: &str) -> Result<Self, BenchError> {
+        let mut statements = Vec::new();
+        let mut names = HashSet::new();
+        for (i, line) in text.lines().enumerate() {
+            let line = line.split('#').next().unwrap_or("").trim();
+            let i = i + 1;
+            let (keyword, rest) = match line.split_once(char::is_whitespace) {
+                Some((keyword, rest)) => (keyword, rest.trim()),
+                None if line.is_empty() => continue,
+                None => (line, ""),
+            };
+            let args: Vec<&str> = rest.split_whitespace().collect();
+
+            let statement = match (keyword, args.as_slice()) {
+                ("instance", [name, ..]) => {
+                    if !names.insert(*name) {
+                        return Err(BenchError::ParseErr(i, format!("there already is an instance `{}`", name)));
+                    }
+                    let path = rest[name.len()..].trim();
+                    let (category, module) = path.rsplit_once('/').ok_or_else(|| {
+                        BenchError::ParseErr(i, format!("`{}` is not a module like `Gates/and`", path))
+                    })?;
+                    Statement::Instance(i, name.to_string(), category.to_string(), module.to_string())
+                },
+                ("set", [name, key, value]) => {
+                    if !SETTINGS.contains(key) {
+                        return Err(BenchError::ParseErr(i, format!("unknown setting `{}`", key)));
+                    }
+                    let value = value.parse().map_err(|_| {
+                        BenchError::ParseErr(i, format!("`{}` is not a number", value))
+                    })?;
+                    Statement::Set(i, name.to_string(), key.to_string(), value)
+                },
+                ("wire", [from, "->", to]) => {
+                    Statement::Wire(i, Endpoint::parse(from, i)?, Endpoint::parse(to, i)?)
+                },
+                ("probe", [net, pin]) => {
+                    Statement::Probe(i, net.to_string(), Endpoint::parse(pin, i)?)
+                },
+                _ => return Err(BenchError::ParseErr(i, format!("invalid statement `{}`", line))),
+            };
+            statements.push(statement);
+        }
+        Ok(Self { statements })
+    }
+
+    /// Get all statements in the order they were written.
+    pub fn statements(&self) -> &[Statement] {
+        &self.statements
+    }
+
+    /// Instantiate the circuit within `env`, apply all settings,
+    /// connect all wires and add all probes.
+    ///
+    /// Returns the ids of all instances by name. On failure all
+    /// instances created so far are removed again.
+    pub fn build(&self, env: &mut ModuleEnv) -> Result<HashMap<String, Uuid>, BenchError> {
+        let mut ids = HashMap::new();
+        match self.build_into(env, &mut ids) {
+            Ok(()) => Ok(ids),
+            Err(e) => {
+                // Don't leave a half-built circuit behind.
+                for id in ids.values() {
+                    env.remove_instance(*id);
+                }
+                Err(e)
+            },
+        }
+    }
+
+    /// Build the circuit, see [`Circuit::build`], recording every
+    /// instance created in `ids`.
+    fn build_into(&self, env: &mut ModuleEnv, ids: &mut HashMap<String, Uuid>) -> Result<(), BenchError> {
+        let resolve = |ids: &HashMap<String, Uuid>, line: usize, name: &str| {
+            ids.get(name).copied().ok_or_else(|| {
+                BenchError::ResolveErr(line, format!("there is no instance `{}`", name))
+            })
+        };
+
+        // Instances first, then settings as they may remove wires.
+        for statement in &self.statements {
+            if let Statement::Instance(line, name, category, module) = statement {
+                let id = env.instantiate(category, module, Point { x: 0.0, y: 0.0 }).ok_or_else(|| {
+                    BenchError::ResolveErr(*line, format!("there is no module `{}/{}`", category, module))
+                })?;
+                ids.insert(name.clone(), id);
+            }
+        }
+        for statement in &self.statements {
+            if let Statement::Set(line, name, key, value) = statement {
+                let id = resolve(ids, *line, name)?;
+                let n = *value as usize;
+                let applied = match key.as_str() {
+                    "inputs" => env.set_inputs_nr(id, n) == Some(n),
+                    "bits" => env.set_bits(id, n) == Some(n),
+                    "data_bits" => env.set_data_bits(id, n) == Some(n),
+                    _ => env.set_delay(id, Some(*value)),
+                };
+                if !applied {
+                    return Err(BenchError::ResolveErr(*line, format!("can't set `{}` of `{}` to {}", key, name, value)));
+                }
+            }
+        }
+        for statement in &self.statements {
+            match statement {
+                Statement::Wire(line, from, to) => {
+                    let from_pin = PinRef::new(resolve(ids, *line, &from.instance)?, from.pin);
+                    let to_pin = PinRef::new(resolve(ids, *line, &to.instance)?, to.pin);
+                    if !env.connect(from_pin, to_pin) {
+                        return Err(BenchError::ResolveErr(*line, format!(
+                            "can't connect `{}.{}` to `{}.{}`", from.instance, from.pin, to.instance, to.pin
+                        )));
+                    }
+                },
+                Statement::Probe(line, net, pin) => {
+                    let id = resolve(ids, *line, &pin.instance)?;
+                    env.add_probe(Probe::new(net, PinRef::new(id, pin.pin)));
+                },
+                _ => {},
+            }
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::analysis::truth_table::tests::test_env;
+    use crate::contract::State;
+
+    const HALF_AND: &str = "
+        # An AND gate driven by two sources
+        instance a Test/source
+        instance b Test/source
+        instance q Test/and   # the gate
+        set q delay 2
+        wire a.0 -> q.0
+        wire b.0 -> q.1
+        probe A a.0
+        probe Q q.0
+    ";
+
+    #[test]
+    fn parse_circuit_test() {
+        let circuit = Circuit::parse(HALF_AND).unwrap();
+        assert_eq!(8, circuit.statements().len());
+        assert_eq!(
+            Statement::Instance(3, "a".to_string(), "Test".to_string(), "source".to_string()),
+            circuit.statements()[0]
+        );
+        assert_eq!(
+            Statement::Wire(7, Endpoint { instance: "a".to_string(), pin: 0 }, Endpoint { instance: "q".to_string(), pin: 0 }),
+            circuit.statements()[4]
+        );
+
+        assert!(matches!(Circuit::parse("wire a.0 q.1"), Err(BenchError::ParseErr(1, _))));
+        assert!(matches!(Circuit::parse("\nprobe A a"), Err(BenchError::ParseErr(2, _))));
+        assert!(matches!(Circuit::parse("instance a and"), Err(BenchError::ParseErr(1, _))));
+        assert!(matches!(Circuit::parse("set a color 3"), Err(BenchError::ParseErr(1, _))));
+        assert!(matches!(
+            Circuit::parse("instance a Test/source\ninstance a Test/and"),
+            Err(BenchError::ParseErr(2, _))
+        ));
+    }
+
+    #[test]
+    fn build_circuit_test() {
+        let mut env = test_env();
+        let ids = Circuit::parse(HALF_AND).unwrap().build(&mut env).unwrap();
+        assert_eq!(3, ids.len());
+        assert_eq!(2, env.wires().len());
+        assert_eq!(Some(2), env.delay(ids["q"]));
+        assert_eq!(2, env.history().traces().len());
+
+        env.force(PinRef::new(ids["a"], 0), State::High);
+        env.force(PinRef::new(ids["b"], 0), State::High);
+        env.settle(16);
+        assert_eq!(State::High, env.output(&PinRef::new(ids["q"], 0)));
+
+        let mut env = test_env();
+        assert!(matches!(
+            Circuit::parse("instance a Test/or").unwrap().build(&mut env),
+            Err(BenchError::ResolveErr(1, _))
+        ));
+        assert!(matches!(
+            Circuit::parse("instance a Test/source\nwire a.0 -> b.0").unwrap().build(&mut env),
+            Err(BenchError::ResolveErr(2, _))
+        ));
+        // Nothing of the failed circuits is left behind.
+        assert!(env.instances().is_empty());
+        assert!(env.wires().is_empty());
+    }
+}
@@ -0,0 +1,352 @@
+use std::{
+    fs,
+    fmt,
+    path::{Path, PathBuf},
+};
+use crate::bench::{BenchError, headless_env};
+use crate::bench::circuit::Circuit;
+use crate::core::module::ModuleEnv;
+use crate::core::sim::PinRef;
+use crate::contract::{State, Bits};
+use crate::analysis::truth_table::SETTLE_STEPS;
+
+/// A value of a [`TestBench`] vector.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Value {
+    /// A number, e.g. `1`, `12` or `0x1f`, for single pins and buses.
+    Number(u32),
+    /// `U`, `Z` or `X` for all bits of the net.
+    State(State),
+    /// `-`, the output isn't checked.
+    DontCare,
+}
+
+impl Value {
+    fn parse(text: &str, line: usize) -> Result<Self, BenchError> {
+        let number = match text.strip_prefix("0x") {
+            Some(hex) => u32::from_str_radix(hex, 16).ok(),
+            None => text.parse().ok(),
+        };
+        match (number, text) {
+            (Some(v), _) => Ok(Value::Number(v)),
+            (_, "-") => Ok(Value::DontCare),
+            (_, "U") => Ok(Value::State(State::Undefined)),
+            (_, "Z") => Ok(Value::State(State::HighImpedance)),
+            (_, "X") => Ok(Value::State(State::Conflict)),
+            _ => Err(BenchError::ParseErr(line, format!("`{}` is not a value", text))),
+        }
+    }
+
+    /// Get the value for a net of `width` bits, `None` if it's a don't care.
+    pub fn to_bits(&self, width: usize) -> Option<Bits> {
+        match self {
+            Value::Number(v) => Some(Bits::from_u32(width, *v)),
+            Value::State(s) => {
+                let mut bits = Bits::undefined(width);
+                for i in 0..width {
+                    bits.set(i, *s);
+                }
+                Some(bits)
+            },
+            Value::DontCare => None,
+        }
+    }
+}
+
+/// A single row of a [`TestBench`], simulated for one cycle.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Vector {
+    /// The line the vector was read from.
+    pub line: usize,
+    /// The values to drive the inputs with.
+    pub inputs: Vec<Value>,
+    /// The expected values of the outputs at the end of the cycle.
+    pub outputs: Vec<Value>,
+}
+
+/// A difference between the expected and the actual value of a net.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Mismatch {
+    /// The cycle (starting at 1), i.e. the vector the mismatch occurred at.
+    pub cycle: usize,
+    /// The simulation time at the end of the cycle.
+    pub time: u64,
+    /// The name of the net.
+    pub net: String,
+    /// The expected value.
+    pub expected: Bits,
+    /// The actual value.
+    pub actual: Bits,
+}
+
+impl fmt::Display for Mismatch {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f, "cycle {} (t = {}): `{}` is {}, expected {}",
+            self.cycle, self.time, self.net, self.actual, self.expected
+        )
+    }
+}
+
+/// The result of running a [`TestBench`].
+#[derive(Debug, Clone, PartialEq, Default)]
+pub struct Report {
+    /// The number of vectors applied.
+    pub cycles: usize,
+    /// All mismatches in the order they occurred.
+    pub mismatches: Vec<Mismatch>,
+}
+
+impl Report {
+    /// Check if all outputs had their expected values.
+    pub fn passed(&self) -> bool {
+        self.mismatches.is_empty()
+    }
+}
+
+impl fmt::Display for Report {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for mismatch in &self.mismatches {
+            writeln!(f, "{}", mismatch)?;
+        }
+        write!(f, "{} cycles, {} mismatches", self.cycles, self.mismatches.len())
+    }
+}
+
+/// Input vectors and expected outputs for a circuit:
+///
+/// ```text
+/// circuit half_adder.circ
+/// cycle 4
+/// in A B
+/// out S C
+/// # A B | S C
+///   0 0 | 0 0
+///   0 1 | 1 0
+///   1 0 | 1 -
+///   1 1 | 0 1
+/// ```
+///
+/// Inputs and outputs are nets, i.e. probes of the circuit. The
+/// inputs are forced to the values left of the `|`, then the circuit
+/// is simulated for `cycle` steps (or until it settles, if omitted)
+/// and the outputs are compared to the values right of the `|`.
+#[derive(Debug, Clone, PartialEq, Default)]
+pub struct TestBench {
+    /// The path of the circuit, relative to the test bench.
+    pub circuit: Option<PathBuf>,
+    /// The number of steps per vector, `None` to wait for the circuit to settle.
+    pub cycle: Option<u64>,
+    /// The names of the input nets.
+    pub inputs: Vec<String>,
+    /// The names of the output nets.
+    pub outputs: Vec<String>,
+    /// The vectors in the order they are applied.
+    pub vectors: Vec<Vector>,
+    /// The line of the `in` and `out` declarations.
+    lines: (usize, usize),
+}
+
+impl TestBench {
+    /// Parse a test bench from its textual representation.
+    pub fn parse(text: &str) -> Result<Self, BenchError> {
+        let mut bench = Self::default();
+        for (i, line) in text.lines().enumerate() {
+            let line = line.split('#').next().unwrap_or("").trim();
+            let i = i + 1;
+            let args: Vec<&str> = line.split_whitespace().collect();
+
+            match args.as_slice() {
+                [] => {},
+                ["circuit", ..] => bench.circuit = Some(PathBuf::from(line["circuit".len()..].trim())),
+                ["cycle", n] => {
+                    bench.cycle = Some(n.parse().map_err(|_| {
+                        BenchError::ParseErr(i, format!("`{}` is not a number", n))
+                    })?);
+                },
+                ["in", names @ ..] => {
+                    bench.inputs = names.iter().map(|s| s.to_string()).collect();
+                    bench.lines.0 = i;
+                },
+                ["out", names @ ..] => {
+                    bench.outputs = names.iter().map(|s| s.to_string()).collect();
+                    bench.lines.1 = i;
+                },
+                _ => {
+                    let (inputs, outputs) = line.split_once('|').ok_or_else(|| {
+                        BenchError::ParseErr(i, format!("invalid vector `{}`", line))
+                    })?;
+                    let inputs = inputs.split_whitespace()
+                        .map(|v| Value::parse(v, i))
+                        .collect::<Result<Vec<_>, _>>()?;
+                    let outputs = outputs.split_whitespace()
+                        .map(|v| Value::parse(v, i))
+                        .collect::<Result<Vec<_>, _>>()?;
+
+                    if inputs.len() != bench.inputs.len() || outputs.len() != bench.outputs.len() {
+                        return Err(BenchError::ParseErr(i, format!(
+                            "expected {} inputs and {} outputs", bench.inputs.len(), bench.outputs.len()
+                        )));
+                    }
+                    if inputs.contains(&Value::DontCare) {
+                        return Err(BenchError::ParseErr(i, "inputs can't be `-`".to_string()));
+                    }
+                    bench.vectors.push(Vector { line: i, inputs, outputs });
+                },
+            }
+        }
+        Ok(bench)
+    }
+
+    /// Get the pin probed as `net` within `env`.
+    fn net(env: &ModuleEnv, net: &str, line: usize) -> Result<PinRef, BenchError> {
+        env.history()
+            .probe(net)
+            .map(|p| p.pin)
+            .ok_or_else(|| BenchError::ResolveErr(line, format!("there is no net `{}`", net)))
+    }
+
+    /// Apply all vectors to the circuit within `env`, see [`Circuit::build`].
+    ///
+    /// The inputs stay forced to the values of the last vector.
+    pub fn run(&self, env: &mut ModuleEnv) -> Result<Report, BenchError> {
+        self.run_each(env, |_, _| {})
+    }
+
+    /// Like [`TestBench::run`], but calls `f` with the cycle (starting
+    /// at 1) and the environment at the end of every cycle, e.g. to
+    /// print the values of all nets.
+    pub fn run_each(
+        &self,
+        env: &mut ModuleEnv,
+        mut f: impl FnMut(usize, &mut ModuleEnv),
+    ) -> Result<Report, BenchError> {
+        let inputs = self.inputs.iter()
+            .map(|n| Self::net(env, n, self.lines.0))
+            .collect::<Result<Vec<_>, _>>()?;
+        let outputs = self.outputs.iter()
+            .map(|n| Self::net(env, n, self.lines.1))
+            .collect::<Result<Vec<_>, _>>()?;
+
+        let mut report = Report::default();
+        for (cycle, vector) in self.vectors.iter().enumerate() {
+            for (pin, value) in inputs.iter().zip(vector.inputs.iter()) {
+                let width = env.output_width(pin).unwrap_or(1);
+                if let Some(bits) = value.to_bits(width) {
+                    env.force_bits(*pin, bits);
+                }
+            }
+
+            let settled = match self.cycle {
+                Some(n) => {
+                    for _ in 0..n {
+                        env.step();
+                    }
+                    true
+                },
+                None => env.settle(SETTLE_STEPS),
+            };
+
+            for ((pin, value), net) in outputs.iter().zip(vector.outputs.iter()).zip(self.outputs.iter()) {
+                let actual = if settled {
+                    env.output_bits(pin)
+                } else {
+                    Bits::undefined(env.output_width(pin).unwrap_or(1))
+                };
+                if let Some(expected) = value.to_bits(actual.width()) {
+                    if expected != actual {
+                        report.mismatches.push(Mismatch {
+                            cycle: cycle + 1,
+                            time: env.simulator().steps(),
+                            net: net.clone(),
+                            expected,
+                            actual,
+                        });
+                    }
+                }
+            }
+            report.cycles += 1;
+            f(cycle + 1, env);
+        }
+        Ok(report)
+    }
+}
+
+/// Load the test bench at `path` together with its circuit and run
+/// it headless (see [`headless_env`]), using the modules found in the
+/// directory `modules`.
+pub fn run_file(path: &Path, modules: &Path) -> Result<Report, BenchError> {
+    run_file_with(headless_env(), path, modules)
+}
+
+/// Like [`run_file`], but build the circuit within `env`, e.g. a
+/// headless environment using the cache of compiled modules.
+pub fn run_file_with(mut env: ModuleEnv, path: &Path, modules: &Path) -> Result<Report, BenchError> {
+    let bench = TestBench::parse(&fs::read_to_string(path)?)?;
+    let circuit = bench.circuit.as_ref().ok_or(BenchError::NoCircuit)?;
+    let circuit = path.parent().unwrap_or(Path::new("")).join(circuit);
+    let circuit = Circuit::parse(&fs::read_to_string(circuit)?)?;
+
+    env.add_modules(modules);
+    circuit.build(&mut env)?;
+    bench.run(&mut env)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::analysis::truth_table::tests::test_env;
+
+    const CIRCUIT: &str = "
+        instance a Test/source
+        instance b Test/source
+        instance q Test/and
+        wire a.0 -> q.0
+        wire b.0 -> q.1
+        probe A a.0
+        probe B b.0
+        probe Q q.0
+    ";
+
+    #[test]
+    fn parse_bench_test() {
+        let bench = TestBench::parse("circuit and gate.circ\ncycle 3\nin A B\nout Q\n0 0 | 0\n1 1 | -\n0x1 X | U").unwrap();
+        assert_eq!(Some(PathBuf::from("and gate.circ")), bench.circuit);
+        assert_eq!(Some(3), bench.cycle);
+        assert_eq!(vec!["A", "B"], bench.inputs);
+        assert_eq!(3, bench.vectors.len());
+        assert_eq!(vec![Value::DontCare], bench.vectors[1].outputs);
+        assert_eq!(vec![Value::Number(1), Value::State(State::Conflict)], bench.vectors[2].inputs);
+        assert_eq!(7, bench.vectors[2].line);
+
+        assert!(matches!(TestBench::parse("in A\nout Q\n0 0 | 1"), Err(BenchError::ParseErr(3, _))));
+        assert!(matches!(TestBench::parse("in A\nout Q\n- | 1"), Err(BenchError::ParseErr(3, _))));
+        assert!(matches!(TestBench::parse("in A\nout Q\n0 1"), Err(BenchError::ParseErr(3, _))));
+        assert!(matches!(TestBench::parse("in A\nout Q\n2 | y"), Err(BenchError::ParseErr(3, _))));
+    }
+
+    #[test]
+    fn run_bench_test() {
+        let mut env = test_env();
+        Circuit::parse(CIRCUIT).unwrap().build(&mut env).unwrap();
+        let bench = TestBench::parse("in A B\nout Q\n0 0 | 0\n0 1 | 0\n1 0 | 1\n1 1 | 1\n1 1 | -").unwrap();
+        let report = bench.run(&mut env).unwrap();
+
+        assert_eq!(5, report.cycles);
+        assert_eq!(1, report.mismatches.len());
+        assert_eq!(3, report.mismatches[0].cycle);
+        assert_eq!("Q", report.mismatches[0].net);
+        assert_eq!(Bits::from(State::Low), report.mismatches[0].actual);
+        assert!(report.to_string().ends_with("5 cycles, 1 mismatches"));
+
+        // With a fixed cycle the gate's delay becomes visible.
+        let mut env = test_env();
+        Circuit::parse(&format!("{}\nset q delay 2", CIRCUIT)).unwrap().build(&mut env).unwrap();
+        let bench = TestBench::parse("cycle 1\nin A B\nout Q\n1 1 | 1\n1 1 | 1").unwrap();
+        let report = bench.run(&mut env).unwrap();
+        assert_eq!(vec![1], report.mismatches.iter().map(|m| m.cycle).collect::<Vec<_>>());
+
+        let bench = TestBench::parse("in A C\nout Q").unwrap();
+        assert!(matches!(bench.run(&mut env), Err(BenchError::ResolveErr(1, _))));
+    }
+}
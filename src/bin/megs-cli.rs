@@ -0,0 +1,269 @@
+use std::{
+    env,
+    fs::{self, File},
+    io::{self, Write},
+    error::Error,
+    path::{Path, PathBuf},
+    process::ExitCode,
+};
+use megs::bench::headless_env;
+use megs::bench::circuit::Circuit;
+use megs::bench::testbench::{TestBench, run_file_with};
+use megs::core::module::{ModuleEnv, read_module};
+use megs::core::report::ModuleReport;
+use megs::core::cache::NO_CACHE_VAR;
+use megs::core::sim::Probe;
+use megs::analysis::truth_table::{TruthTable, MAX_INPUTS};
+use megs::analysis::boolean::Equation;
+use megs::export::vcd::{write_vcd, DEFAULT_TIMESCALE};
+
+const USAGE: &str = "\
+usage: megs-cli [--modules <dir>] [--no-cache] <command>
+
+commands:
+    check <module.wasm|wat>... [--preview <dir>]
+                                                report exports, imports and contract violations
+    list                                        list all categories and modules
+    clear-cache                                 remove all compiled modules from the cache
+    test <bench>...                             run test benches and report all mismatches
+    simulate <circuit> <vectors> [--vcd <file>] apply input vectors and print all nets
+    truth-table <circuit> <inputs> <outputs>    print the truth table, e.g. `A,B Q`
+    export <format> <circuit> <args>... [-o <file>]
+        vcd <circuit> <vectors>                 waveform of the simulated vectors
+        csv|markdown|equations <circuit> <inputs> <outputs>
+
+Modules are loaded from `assets/modules` unless `--modules` is given.
+Compiled modules are cached unless `--no-cache` is given or `MEGS_NO_CACHE` is set.";
+
+type CliResult = Result<bool, Box<dyn Error>>;
+
+/// Remove `--name <value>` from `args` and return the value.
+fn take_option(args: &mut Vec<String>, name: &str) -> Result<Option<String>, Box<dyn Error>> {
+    match args.iter().position(|a| a == name) {
+        Some(i) if i + 1 < args.len() => {
+            let value = args.remove(i + 1);
+            args.remove(i);
+            Ok(Some(value))
+        },
+        Some(_) => Err(format!("`{}` expects a value", name).into()),
+        None => Ok(None),
+    }
+}
+
+/// Remove the flag `name` from `args` and check if it was given.
+fn take_flag(args: &mut Vec<String>, name: &str) -> bool {
+    let len = args.len();
+    args.retain(|a| a != name);
+    args.len() != len
+}
+
+/// Create a headless environment that uses the cache of compiled
+/// modules, unless disabled.
+fn cli_env() -> ModuleEnv {
+    let mut env = headless_env();
+    env.enable_cache();
+    env
+}
+
+/// Create a headless environment containing the circuit at `path`.
+fn load_circuit(path: &str, modules: &Path) -> Result<ModuleEnv, Box<dyn Error>> {
+    let mut env = cli_env();
+    for e in env.add_modules(modules) {
+        eprintln!("warning: {}", e);
+    }
+    Circuit::parse(&fs::read_to_string(path)?)
+        .and_then(|circuit| circuit.build(&mut env))
+        .map_err(|e| format!("{}: {}", path, e))?;
+    Ok(env)
+}
+
+/// Get the probes of the comma separated nets `names`.
+fn probes(env: &ModuleEnv, names: &str) -> Result<Vec<Probe>, Box<dyn Error>> {
+    env.history().probes(names).map_err(|name| format!("there is no net `{}`", name).into())
+}
+
+/// Apply the vectors at `vectors` to the circuit within `env`, the
+/// circuit named by the test bench is ignored.
+fn run_vectors(env: &mut ModuleEnv, vectors: &str, verbose: bool) -> CliResult {
+    let bench = TestBench::parse(&fs::read_to_string(vectors)?).map_err(|e| format!("{}: {}", vectors, e))?;
+    let report = bench.run_each(env, |cycle, env| {
+        if verbose {
+            let nets: Vec<String> = env.history().traces()
+                .iter()
+                .map(|t| format!("{}={}", t.probe.name, env.output_bits(&t.probe.pin)))
+                .collect();
+            println!("{:>4} t={:<6} {}", cycle, env.simulator().steps(), nets.join(" "));
+        }
+    })?;
+
+    if verbose || !report.passed() {
+        println!("{}", report);
+    }
+    Ok(report.passed())
+}
+
+fn check(paths: &[String], preview: Option<String>) -> CliResult {
+    let mut env = cli_env();
+    let mut passed = true;
+    for path in paths {
+        let name = Path::new(path).file_stem().map(|s| s.to_string_lossy().to_string()).unwrap_or_default();
+        let module = match read_module(Path::new(path)) {
+            Ok(module) => module,
+            Err(e) => {
+                println!("module `{}`: {}", name, e);
+                passed = false;
+                continue;
+            },
+        };
+        match ModuleReport::generate(&mut env, &name, &module) {
+            Ok(report) => {
+                println!("{}", report);
+                passed &= report.passed();
+                if let Some(dir) = &preview {
+                    // A preview that can't be written fails this module only.
+                    let svg = Path::new(dir).join(format!("{}.svg", name));
+                    if let Err(e) = fs::create_dir_all(dir).and_then(|_| fs::write(&svg, report.preview_svg())) {
+                        println!("module `{}`: can't write {}: {}", name, svg.display(), e);
+                        passed = false;
+                    }
+                }
+            },
+            Err(e) => {
+                println!("module `{}`: {}", name, e);
+                passed = false;
+            },
+        }
+    }
+    Ok(passed)
+}
+
+fn list(modules: &Path) -> CliResult {
+    let mut env = cli_env();
+    let errors = env.add_modules(modules);
+    for e in errors.iter() {
+        eprintln!("{}", e);
+    }
+
+    let mut categories = env.category_names();
+    categories.sort();
+    for category in categories {
+        let mut names = env.module_names(category).unwrap_or_default();
+        names.sort();
+        let names: Vec<&str> = names.iter().map(|n| n.as_str()).collect();
+        println!("{}: {}", category, names.join(", "));
+    }
+    Ok(errors.is_empty())
+}
+
+fn test(benches: &[String], modules: &Path) -> CliResult {
+    let mut passed = true;
+    for bench in benches {
+        match run_file_with(cli_env(), Path::new(bench), modules) {
+            Ok(report) => {
+                println!("{}: {}", bench, if report.passed() { "ok" } else { "FAILED" });
+                println!("{}", report);
+                passed &= report.passed();
+            },
+            Err(e) => {
+                eprintln!("{}: {}", bench, e);
+                passed = false;
+            },
+        }
+    }
+    Ok(passed)
+}
+
+fn simulate(circuit: &str, vectors: &str, vcd: Option<String>, modules: &Path) -> CliResult {
+    let mut env = load_circuit(circuit, modules)?;
+    let passed = run_vectors(&mut env, vectors, true)?;
+    if let Some(vcd) = vcd {
+        write_vcd(env.history(), DEFAULT_TIMESCALE, &mut File::create(vcd)?)?;
+    }
+    Ok(passed)
+}
+
+fn truth_table(circuit: &str, inputs: &str, outputs: &str, modules: &Path) -> Result<TruthTable, Box<dyn Error>> {
+    let mut env = load_circuit(circuit, modules)?;
+    let (inputs, outputs) = (probes(&env, inputs)?, probes(&env, outputs)?);
+    Ok(TruthTable::generate(&mut env, &inputs, &outputs, MAX_INPUTS)?)
+}
+
+fn export(format: &str, args: &[String], out: &mut impl Write, modules: &Path) -> CliResult {
+    match (format, args) {
+        ("vcd", [circuit, vectors]) => {
+            let mut env = load_circuit(circuit, modules)?;
+            let passed = run_vectors(&mut env, vectors, false)?;
+            write_vcd(env.history(), DEFAULT_TIMESCALE, out)?;
+            Ok(passed)
+        },
+        ("csv", [circuit, inputs, outputs]) => {
+            write!(out, "{}", truth_table(circuit, inputs, outputs, modules)?.to_csv())?;
+            Ok(true)
+        },
+        ("markdown", [circuit, inputs, outputs]) => {
+            write!(out, "{}", truth_table(circuit, inputs, outputs, modules)?.to_markdown())?;
+            Ok(true)
+        },
+        ("equations", [circuit, inputs, outputs]) => {
+            for equation in Equation::from_table(&truth_table(circuit, inputs, outputs, modules)?) {
+                writeln!(out, "{}", equation)?;
+            }
+            Ok(true)
+        },
+        _ => Err(USAGE.into()),
+    }
+}
+
+fn clear_cache() -> CliResult {
+    let env = cli_env();
+    match env.cache() {
+        Some(cache) => {
+            let n = cache.len();
+            cache.clear()?;
+            println!("removed {} compiled modules from {}", n, cache.dir().display());
+        },
+        None => println!("the module cache is disabled"),
+    }
+    Ok(true)
+}
+
+fn run(mut args: Vec<String>) -> CliResult {
+    if take_flag(&mut args, "--no-cache") {
+        // Every environment is created after this, so no cache is ever opened.
+        env::set_var(NO_CACHE_VAR, "1");
+    }
+    let modules = PathBuf::from(take_option(&mut args, "--modules")?.unwrap_or("assets/modules".to_string()));
+    let vcd = take_option(&mut args, "--vcd")?;
+    let out = take_option(&mut args, "-o")?;
+    let preview = take_option(&mut args, "--preview")?;
+
+    let args: Vec<&str> = args.iter().map(|a| a.as_str()).collect();
+    let rest: Vec<String> = args.iter().skip(1).map(|a| a.to_string()).collect();
+    match args.as_slice() {
+        ["check", _, ..] => check(&rest, preview),
+        ["list"] => list(&modules),
+        ["clear-cache"] => clear_cache(),
+        ["test", _, ..] => test(&rest, &modules),
+        ["simulate", circuit, vectors] => simulate(circuit, vectors, vcd, &modules),
+        ["truth-table", circuit, inputs, outputs] => {
+            print!("{}", truth_table(circuit, inputs, outputs, &modules)?.to_markdown());
+            Ok(true)
+        },
+        ["export", format, ..] => match out {
+            Some(path) => export(format, &rest[1..], &mut File::create(path)?, &modules),
+            None => export(format, &rest[1..], &mut io::stdout(), &modules),
+        },
+        _ => Err(USAGE.into()),
+    }
+}
+
+fn main() -> ExitCode {
+    match run(env::args().skip(1).collect()) {
+        Ok(true) => ExitCode::SUCCESS,
+        Ok(false) => ExitCode::FAILURE,
+        Err(e) => {
+            eprintln!("{}", e);
+            ExitCode::FAILURE
+        },
+    }
+}
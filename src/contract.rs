@@ -1,24 +1,7 @@
 use macroquad::{shapes, color};
+use wasmer::{FunctionEnvMut, Memory};
 
-#[repr(C)]
-#[derive(Debug, Copy, Clone, PartialEq)]
-pub enum State {
-    Undefined,
-    Low,
-    High,
-}
-
-#[repr(C)]
-pub struct Color {
-    /// red
-    pub r: f32,
-    /// green
-    pub g: f32,
-    /// blue
-    pub b: f32,
-    /// alpha - opaque [0, 1] solid
-    pub a: f32,
-}
+pub use megs_abi::{State, Bits, MAX_WIDTH};
 
 pub fn draw_rectangle(x: f32, y: f32, w: f32, h: f32, r: f32, g: f32, b: f32) {
     shapes::draw_rectangle(x, y, w, h, color::Color::new(r, g, b, 1.));
@@ -39,3 +22,86 @@ pub fn draw_line(x1: f32, y1: f32, x2: f32, y2: f32, thick: f32, r: f32, g: f32,
 pub fn draw_arc(x: f32, y: f32, radius: f32, start_angle: f32, end_angle: f32, thickness: f32) {
 
 }
+
+/// The state shared by the file related host functions.
+///
+/// Instances can't access the file system. Instead the user picks a
+/// file, the host puts its contents into `picked` and lets the instance
+/// copy them into its own memory via [`read_picked_file`].
+#[derive(Debug, Clone, Default)]
+pub struct FileEnv {
+    /// The contents of the picked file.
+    pub picked: Option<Vec<u8>>,
+    /// The memory of the instance that is currently loading the file.
+    pub memory: Option<Memory>,
+}
+
+/// Copy up to `len` bytes of the picked file into the memory of the
+/// calling instance, starting at `ptr`.
+///
+/// Returns the number of bytes copied or `-1` if no file was picked.
+pub fn read_picked_file(mut env: FunctionEnvMut<FileEnv>, ptr: i32, len: i32) -> i32 {
+    let (data, store) = env.data_and_store_mut();
+    let (picked, memory) = match (&data.picked, &data.memory) {
+        (Some(picked), Some(memory)) => (picked, memory),
+        _ => return -1,
+    };
+
+    let n = picked.len().min(len.max(0) as usize);
+    match memory.view(&store).write(ptr as u32 as u64, &picked[..n]) {
+        Ok(()) => n as i32,
+        Err(_) => -1,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn bits_test() {
+        let mut bits = Bits::from_u32(4, 0b1010);
+        assert_eq!(Some(0b1010), bits.value());
+        assert_eq!("1010", bits.to_string());
+
+        bits.set(0, State::Undefined);
+        bits.set(2, State::High);
+        bits.set(7, State::High);
+        assert_eq!(None, bits.value());
+        assert_eq!("111U", bits.to_string());
+        assert_eq!(State::Undefined, bits.get(7));
+        assert_eq!(bits, Bits::from_i64(4, bits.to_i64()));
+
+        assert_eq!("UU", Bits::undefined(2).to_string());
+        assert_eq!(Some(u32::MAX), Bits::from_u32(40, u32::MAX).value());
+        assert_eq!(Bits::from_u32(1, 1), Bits::from(State::High));
+    }
+
+    #[test]
+    fn resolve_test() {
+        assert_eq!(State::High, State::HighImpedance.resolve(State::High));
+        assert_eq!(State::Low, State::Low.resolve(State::Low));
+        assert_eq!(State::Conflict, State::Low.resolve(State::High));
+        assert_eq!(State::Conflict, State::Conflict.resolve(State::HighImpedance));
+        assert_eq!(State::Undefined, State::Undefined.resolve(State::High));
+        assert_eq!(State::HighImpedance, State::HighImpedance.resolve(State::HighImpedance));
+
+        // Gates see floating inputs as undefined, conflicts propagate.
+        assert_eq!(State::Undefined, !State::HighImpedance);
+        assert_eq!(State::Low, State::Conflict & State::Low);
+        assert_eq!(State::Conflict, State::Conflict | State::Low);
+
+        let mut a = Bits::from_u32(4, 0b0011);
+        a.set(3, State::HighImpedance);
+        a.set(2, State::HighImpedance);
+        assert_eq!("ZZ11", a.to_string());
+        assert_eq!(a, Bits::from_i64(4, a.to_i64()));
+
+        let b = Bits::from_i64(4, (0b0010 << 32) | 0b1100);
+        assert_eq!("11U0", b.to_string());
+        let net = a.resolve(&b);
+        assert_eq!("11UX", net.to_string());
+        assert!(net.has_conflict());
+        assert_eq!("11UU", Bits::from_i64(4, net.to_i64()).to_string());
+    }
+}
@@ -1,2 +1,8 @@
 pub mod module;
 pub mod contract;
+pub mod sim;
+pub mod history;
+pub mod memory;
+pub mod debug;
+pub mod report;
+pub mod cache;
@@ -0,0 +1,175 @@
+use wasmer::{Store, Module, CompileError};
+use sha2::{Digest, Sha256};
+use std::{
+    io,
+    fs,
+    path::{Path, PathBuf},
+};
+
+/// Set to disable the cache, see [`ModuleCache::from_env`].
+pub const NO_CACHE_VAR: &str = "MEGS_NO_CACHE";
+/// Set to use another cache directory, see [`ModuleCache::from_env`].
+pub const CACHE_DIR_VAR: &str = "MEGS_CACHE_DIR";
+
+/// An on-disk cache of compiled modules.
+///
+/// Compiling a module takes much longer than loading its serialized
+/// form, so [`crate::core::module::ModuleEnv::add_module_raw`] looks
+/// up every module by the SHA-256 digest of its contents first.
+///
+/// Entries are kept in a sub-directory per wasmer version and engine,
+/// i.e. updating wasmer or switching the compiler invalidates them.
+/// Other builds may share the same root, so the directories of other
+/// versions are left alone. Entries that can't be loaded are removed
+/// and compiled again.
+#[derive(Debug, Clone)]
+pub struct ModuleCache {
+    /// The directory of the current wasmer version and engine.
+    dir: PathBuf,
+}
+
+impl ModuleCache {
+    /// Open the cache within `root` for modules compiled by the
+    /// engine of `store`.
+    ///
+    /// Temporary files left behind by an interrupted [`ModuleCache::compile`]
+    /// of this version are removed.
+    pub fn open(root: &Path, store: &Store) -> io::Result<Self> {
+        let engine = digest(store.engine().deterministic_id().as_bytes());
+        let dir = root.join(format!("wasmer-{}-{}", wasmer::VERSION, &engine[..16]));
+        fs::create_dir_all(&dir)?;
+
+        for entry in fs::read_dir(&dir)?.flatten() {
+            if entry.path().extension().is_some_and(|ext| ext == "tmp") {
+                let _ = fs::remove_file(entry.path());
+            }
+        }
+
+        Ok(Self { dir })
+    }
+
+    /// Open the default cache, unless disabled.
+    ///
+    /// The cache is disabled if [`NO_CACHE_VAR`] is set. It's located in
+    /// [`CACHE_DIR_VAR`] if set, otherwise in `$XDG_CACHE_HOME/megs` or
+    /// `$HOME/.cache/megs`. Returns `None` if the cache is disabled or
+    /// can't be opened.
+    pub fn from_env(store: &Store) -> Option<Self> {
+        if std::env::var_os(NO_CACHE_VAR).is_some() {
+            return None;
+        }
+
+        let root = match std::env::var_os(CACHE_DIR_VAR) {
+            Some(dir) => PathBuf::from(dir),
+            None => match (std::env::var_os("XDG_CACHE_HOME"), std::env::var_os("HOME")) {
+                (Some(dir), _) => PathBuf::from(dir).join("megs"),
+                (None, Some(home)) => PathBuf::from(home).join(".cache").join("megs"),
+                (None, None) => return None,
+            },
+        };
+        Self::open(&root, store).ok()
+    }
+
+    /// Get the directory of the entries.
+    pub fn dir(&self) -> &Path {
+        &self.dir
+    }
+
+    /// Get the path of the entry of the module `bytes`.
+    fn entry(&self, bytes: &[u8]) -> PathBuf {
+        self.dir.join(format!("{}.bin", digest(bytes)))
+    }
+
+    /// Load the module `bytes` (binary or text) from the cache, or
+    /// compile and add it.
+    ///
+    /// Failing to write the cache isn't an error, the module just
+    /// gets compiled again next time.
+    pub fn compile(&self, store: &Store, bytes: &[u8]) -> Result<Module, CompileError> {
+        let path = self.entry(bytes);
+
+        if path.exists() {
+            // Entries are only written by `compile`, for the same
+            // wasmer version and engine.
+            match unsafe { Module::deserialize_from_file(store, &path) } {
+                Ok(module) => return Ok(module),
+                Err(_) => {
+                    let _ = fs::remove_file(&path);
+                },
+            }
+        }
+
+        let module = Module::new(store, bytes)?;
+        if let Ok(serialized) = module.serialize() {
+            // Write to a temporary file first, so no one ever
+            // sees half an entry.
+            let tmp = path.with_extension(format!("{}.tmp", std::process::id()));
+            if fs::write(&tmp, &serialized).is_ok() && fs::rename(&tmp, &path).is_err() {
+                let _ = fs::remove_file(&tmp);
+            }
+        }
+        Ok(module)
+    }
+
+    /// Remove all entries.
+    pub fn clear(&self) -> io::Result<()> {
+        for entry in fs::read_dir(&self.dir)?.flatten() {
+            fs::remove_file(entry.path())?;
+        }
+        Ok(())
+    }
+
+    /// Get the number of entries.
+    pub fn len(&self) -> usize {
+        fs::read_dir(&self.dir).map(|entries| entries.flatten().count()).unwrap_or(0)
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+}
+
+/// Get the SHA-256 digest of `bytes` as hex string.
+fn digest(bytes: &[u8]) -> String {
+    Sha256::digest(bytes).iter().map(|b| format!("{:02x}", b)).collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use uuid::Uuid;
+
+    #[test]
+    fn module_cache_test() {
+        let module_wat = r#"
+            (module
+                (func (export "get_output") (param $i i32) (result i32) i32.const 2)
+            )
+        "#;
+
+        let root = std::env::temp_dir().join(format!("megs-{}", Uuid::new_v4()));
+        fs::create_dir_all(root.join("wasmer-0.0.0-other")).unwrap();
+        let store = Store::default();
+        let cache = ModuleCache::open(&root, &store).unwrap();
+        fs::write(cache.dir().join("0123.bin.42.tmp"), b"half an entry").unwrap();
+        let cache = ModuleCache::open(&root, &store).unwrap();
+        // The entries of other builds are kept, temporary files are not.
+        assert!(root.join("wasmer-0.0.0-other").exists());
+        assert!(cache.is_empty());
+
+        let module = cache.compile(&store, module_wat.as_bytes()).unwrap();
+        assert_eq!(1, cache.len());
+        let cached = cache.compile(&store, module_wat.as_bytes()).unwrap();
+        assert_eq!(module.exports().count(), cached.exports().count());
+        assert_eq!(1, cache.len());
+
+        // A broken entry is replaced.
+        fs::write(cache.entry(module_wat.as_bytes()), b"garbage").unwrap();
+        assert!(cache.compile(&store, module_wat.as_bytes()).is_ok());
+        assert!(fs::read(cache.entry(module_wat.as_bytes())).unwrap() != b"garbage");
+
+        cache.clear().unwrap();
+        assert!(cache.is_empty());
+        fs::remove_dir_all(&root).unwrap();
+    }
+}
@@ -1,9 +1,11 @@
-use wasmer::{ExportType, ImportType, Imports, Module, imports, ExternType, AsStoreRef};
+use wasmer::{ExportType, ImportType, Imports, Module, ExternType, AsStoreRef, FunctionType, Type, WasmTypeList, Store, Function, FunctionEnv};
 use std::{
     error::Error,
     fmt,
     convert::From,
 };
+use megs_abi::{self as abi, Signature, ValType};
+use crate::contract::FileEnv;
 
 /// A [`Contract`] describes what the environment expects
 /// from a given module and what the module can expect
@@ -14,10 +16,37 @@ pub struct Contract {
     pub exports: Vec<ExportType>,
     /// A set of imports a module can expect.
     pub imports: Vec<ImportType>,
+    /// A set of exports a module may provide, e.g. to declare
+    /// the width of its pins. If provided, their types must match.
+    pub optional: Vec<ExportType>,
 }
 
 impl Contract {
-    fn format_extern(ext: &ExternType) -> String {
+    /// Create the contract of the MEGS ABI, as shared with modules
+    /// through the `megs-abi` crate.
+    pub fn abi() -> Self {
+        Self {
+            exports: abi_exports(),
+            imports: abi_imports(),
+            optional: pin_exports(),
+        }
+    }
+
+    /// Start building a contract without any exports or imports.
+    ///
+    /// ```ignore
+    /// let contract = Contract::builder()
+    ///     .require_fn::<(), f32>("width")
+    ///     .require_fn::<(f32, f32, f32), ()>("draw")
+    ///     .imports_from(&imports, &store)
+    ///     .build();
+    /// ```
+    pub fn builder() -> ContractBuilder {
+        ContractBuilder::default()
+    }
+
+    /// Format the type of an import or export, e.g. `(param F32)(result) [function]`.
+    pub fn format_extern(ext: &ExternType) -> String {
         let mut s = String::new();
 
         match ext {
@@ -46,7 +75,22 @@ impl Contract {
         s
     }
 
+    /// Check if `module` obeys the contract.
+    ///
+    /// Returns the first violation, see [`Contract::violations`] for all of them.
     pub fn check(&self, module: &Module) -> Result<(), ContractError> {
+        match self.violations(module).into_iter().next() {
+            Some(e) => Err(e),
+            None => Ok(()),
+        }
+    }
+
+    /// Get all violations of the contract by `module`: missing
+    /// exports first, then optional exports of the wrong type and
+    /// finally imports the environment doesn't provide.
+    pub fn violations(&self, module: &Module) -> Vec<ContractError> {
+        let mut violations = Vec::new();
+
         // First verify that all exports are satisfied.
         'exp: for export in self.exports.iter() {
             for export_ in module.exports() {
@@ -57,11 +101,21 @@ impl Contract {
             }
 
             // Seems to be missing.
-            return Err(ContractError::ExportErr(
+            violations.push(ContractError::ExportErr(
                 format!("missing export `{}{}`", export.name(), Contract::format_extern(export.ty()))
             ));
         }
 
+        for export in self.optional.iter() {
+            for export_ in module.exports() {
+                if export.name() == export_.name() && *export != export_ {
+                    violations.push(ContractError::ExportErr(
+                        format!("export `{}` should be `{}{}`", export.name(), export.name(), Contract::format_extern(export.ty()))
+                    ));
+                }
+            }
+        }
+
         'imp: for import in module.imports() {
             for import_ in self.imports.iter() {
                 if import == *import_ {
@@ -70,15 +124,78 @@ impl Contract {
                 }
             }
 
-            return Err(ContractError::ImportErr(
+            violations.push(ContractError::ImportErr(
                 format!("missing import `{}::{}{}`", import.module(), import.name(), Contract::format_extern(import.ty()))
             ));
         }
 
-        Ok(())
+        violations
     }
 }
 
+/// Builds a [`Contract`] from the Rust types of its functions,
+/// see [`Contract::builder`].
+#[derive(Debug, Clone, Default)]
+pub struct ContractBuilder {
+    exports: Vec<ExportType>,
+    imports: Vec<ImportType>,
+    optional: Vec<ExportType>,
+}
+
+impl ContractBuilder {
+    /// Require a function `name` taking `Args` and returning `Rets`,
+    /// e.g. `require_fn::<(f32, f32), i32>("f")`.
+    pub fn require_fn<Args: WasmTypeList, Rets: WasmTypeList>(mut self, name: &str) -> Self {
+        self.exports.push(ExportType::new(name, fn_type::<Args, Rets>()));
+        self
+    }
+
+    /// Allow a function `name`, which must take `Args` and return
+    /// `Rets` if the module exports it.
+    pub fn optional_fn<Args: WasmTypeList, Rets: WasmTypeList>(mut self, name: &str) -> Self {
+        self.optional.push(ExportType::new(name, fn_type::<Args, Rets>()));
+        self
+    }
+
+    /// Provide a function `module::name` taking `Args` and returning `Rets`.
+    pub fn import_fn<Args: WasmTypeList, Rets: WasmTypeList>(mut self, module: &str, name: &str) -> Self {
+        self.imports.push(ImportType::new(module, name, fn_type::<Args, Rets>()));
+        self
+    }
+
+    /// Provide the sandboxed WASI imports, see [`wasi_imports`].
+    pub fn wasi(mut self) -> Self {
+        self.imports.extend(wasi_imports());
+        self
+    }
+
+    /// Provide everything `imports` exports.
+    pub fn imports_from(mut self, imports: &Imports, store: &impl AsStoreRef) -> Self {
+        self.imports.extend(inobj_types(imports, store));
+        self
+    }
+
+    /// Require the exports of the MEGS ABI and allow the ones used to
+    /// talk to the simulator (see [`abi_exports`] and [`pin_exports`]).
+    pub fn megs_exports(mut self) -> Self {
+        self.exports.extend(abi_exports());
+        self.optional.extend(pin_exports());
+        self
+    }
+
+    pub fn build(self) -> Contract {
+        Contract {
+            exports: self.exports,
+            imports: self.imports,
+            optional: self.optional,
+        }
+    }
+}
+
+fn fn_type<Args: WasmTypeList, Rets: WasmTypeList>() -> ExternType {
+    ExternType::Function(FunctionType::new(Args::wasm_types(), Rets::wasm_types()))
+}
+
 #[derive(Debug, Clone, PartialEq)]
 pub enum ContractError {
     ExportErr(String),
@@ -102,6 +219,54 @@ impl Error for ContractError {
     
 }
 
+/// Get the optional exports modules use to talk to the simulator,
+/// see the `Pins` section of the README.
+///
+/// Pins wider than one bit are declared via `get_input_width` and
+/// `get_output_width` and transfer their values as `i64` (see
+/// [`crate::contract::Bits::to_i64`]).
+pub fn pin_exports() -> Vec<ExportType> {
+    abi::PIN_EXPORTS.iter().map(export_type).collect()
+}
+
+/// Get the exports every module must provide.
+pub fn abi_exports() -> Vec<ExportType> {
+    abi::EXPORTS.iter().map(export_type).collect()
+}
+
+/// Get the imports the host provides to every module.
+pub fn abi_imports() -> Vec<ImportType> {
+    abi::IMPORTS.iter()
+        .map(|sig| ImportType::new(abi::IMPORT_MODULE, sig.name, extern_type(sig)))
+        .collect()
+}
+
+/// Get the sandboxed WASI imports the host may provide, see
+/// [`crate::wasi`].
+pub fn wasi_imports() -> Vec<ImportType> {
+    abi::WASI_IMPORTS.iter()
+        .map(|sig| ImportType::new(abi::WASI_MODULE, sig.name, extern_type(sig)))
+        .collect()
+}
+
+fn export_type(sig: &Signature) -> ExportType {
+    ExportType::new(sig.name, extern_type(sig))
+}
+
+fn extern_type(sig: &Signature) -> ExternType {
+    let ty = |v: &ValType| match v {
+        ValType::I32 => Type::I32,
+        ValType::I64 => Type::I64,
+        ValType::F32 => Type::F32,
+        ValType::F64 => Type::F64,
+    };
+
+    ExternType::Function(FunctionType::new(
+        sig.params.iter().map(ty).collect::<Vec<_>>(),
+        sig.results.iter().map(ty).collect::<Vec<_>>(),
+    ))
+}
+
 /// Translate the exports of the given [`Imports`] into
 /// [`ImportType`]s.
 pub fn inobj_types(io: &Imports, store: &impl AsStoreRef) -> Vec<ImportType> {
@@ -120,6 +285,84 @@ pub fn inobj_types(io: &Imports, store: &impl AsStoreRef) -> Vec<ImportType> {
     v
 }
 
+/// The drawing backend of the standard MEGS imports, one host function
+/// per drawing import.
+#[derive(Debug, Clone)]
+pub struct DrawFns {
+    pub rectangle: Function,
+    pub circle: Function,
+    pub circle_lines: Function,
+    pub line: Function,
+}
+
+fn skip_rectangle(_x: f32, _y: f32, _w: f32, _h: f32, _r: f32, _g: f32, _b: f32) {}
+fn skip_circle(_x: f32, _y: f32, _rad: f32, _r: f32, _g: f32, _b: f32) {}
+fn skip_circle_lines(_x: f32, _y: f32, _rad: f32, _thick: f32, _r: f32, _g: f32, _b: f32) {}
+fn skip_line(_x1: f32, _y1: f32, _x2: f32, _y2: f32, _thick: f32, _r: f32, _g: f32, _b: f32) {}
+
+impl DrawFns {
+    /// Draw with macroquad.
+    pub fn macroquad(store: &mut Store) -> Self {
+        Self {
+            rectangle: Function::new_native(store, crate::contract::draw_rectangle),
+            circle: Function::new_native(store, crate::contract::draw_circle),
+            circle_lines: Function::new_native(store, crate::contract::draw_circle_lines),
+            line: Function::new_native(store, crate::contract::draw_line),
+        }
+    }
+
+    /// Draw nothing, e.g. without a window.
+    pub fn none(store: &mut Store) -> Self {
+        Self {
+            rectangle: Function::new_native(store, skip_rectangle),
+            circle: Function::new_native(store, skip_circle),
+            circle_lines: Function::new_native(store, skip_circle_lines),
+            line: Function::new_native(store, skip_line),
+        }
+    }
+}
+
+/// Get the standard MEGS imports, drawing with macroquad, and the
+/// matching [`Contract`].
+///
+/// `files` backs `read_picked_file`, pass it to
+/// [`crate::core::module::ModuleEnv::set_file_env`] as well.
+pub fn megs_contract(store: &mut Store, files: &FunctionEnv<FileEnv>) -> (Imports, Contract) {
+    let draw = DrawFns::macroquad(store);
+    megs_contract_with(store, files, draw)
+}
+
+/// Like [`megs_contract`], but draw with `draw`, e.g. to record or
+/// skip the drawing.
+///
+/// The imports are those listed by [`abi::IMPORTS`], the contract is
+/// derived from the types of the host functions, so a host function
+/// that doesn't match its signature shows up in the contract.
+///
+/// # Panics
+///
+/// If there is no host function for one of the [`abi::IMPORTS`].
+pub fn megs_contract_with(store: &mut Store, files: &FunctionEnv<FileEnv>, draw: DrawFns) -> (Imports, Contract) {
+    let mut imports = Imports::new();
+    for sig in abi::IMPORTS.iter() {
+        let function = match sig.name {
+            "draw_rectangle" => draw.rectangle.clone(),
+            "draw_circle" => draw.circle.clone(),
+            "draw_circle_lines" => draw.circle_lines.clone(),
+            "draw_line" => draw.line.clone(),
+            "read_picked_file" => Function::new_typed_with_env(store, files, crate::contract::read_picked_file),
+            name => panic!("there is no host function for the import `{}`", name),
+        };
+        imports.define(abi::IMPORT_MODULE, sig.name, function);
+    }
+    let contract = Contract::builder()
+        .megs_exports()
+        .imports_from(&imports, &*store)
+        .build();
+
+    (imports, contract)
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -150,6 +393,7 @@ mod tests {
             imports: vec![
                 ImportType::new("env", "draw_rectangle", ExternType::Function(FunctionType::new([Type::F32, Type::F32, Type::F32, Type::F32], []))), 
             ],
+            optional: vec![],
         };
 
         assert_eq!(Ok(()), contract.check(&module));
@@ -177,6 +421,7 @@ mod tests {
             imports: vec![
                 ImportType::new("env", "draw_rectangle", ExternType::Function(FunctionType::new([Type::F32, Type::F32, Type::F32, Type::F32], []))), 
             ],
+            optional: vec![],
         };
 
         assert_eq!(
@@ -208,6 +453,7 @@ mod tests {
             imports: vec![
                 ImportType::new("env", "draw_rectangle", ExternType::Function(FunctionType::new([Type::F32, Type::F32, Type::F32, Type::F32], []))), 
             ],
+            optional: vec![],
         };
 
         assert_eq!(
@@ -217,6 +463,73 @@ mod tests {
         );
     }
 
+    #[test]
+    fn optional_export_test() {
+        let module_wat = r#"
+            (module
+                (func (export "get_inputs_nr") (result i32)
+                    i32.const 2
+                )
+                (func (export "get_input_width") (param $i i32) (result i64)
+                    i64.const 8
+                )
+            )
+        "#;
+
+        let store = Store::default();
+        let module = Module::new(&store, module_wat).expect("unable to create module");
+
+        let mut contract = Contract {
+            exports: vec![],
+            imports: vec![],
+            optional: pin_exports(),
+        };
+
+        assert_eq!(
+            Err(ContractError::ExportErr(
+                    "export `get_input_width` should be `get_input_width(param I32)(result I32) [function]`".to_string())),
+            contract.check(&module)
+        );
+
+        contract.optional.retain(|e| e.name() != "get_input_width");
+        assert_eq!(Ok(()), contract.check(&module));
+    }
+
+    #[test]
+    fn violations_test() {
+        let module_wat = r#"
+            (module
+                (import "env" "draw_line" (func $dl (param f32 f32 f32 f32)))
+                (func (export "get_output") (param $i i32) (result i64)
+                    i64.const 2
+                )
+            )
+        "#;
+
+        let store = Store::default();
+        let module = Module::new(&store, module_wat).expect("unable to create module");
+
+        let contract = Contract {
+            exports: vec![
+                ExportType::new("width", ExternType::Function(FunctionType::new([], [Type::F32]))),
+            ],
+            imports: vec![],
+            optional: pin_exports(),
+        };
+
+        assert_eq!(
+            vec![
+                ContractError::ExportErr("missing export `width(param)(result F32) [function]`".to_string()),
+                ContractError::ExportErr(
+                    "export `get_output` should be `get_output(param I32)(result I32) [function]`".to_string()),
+                ContractError::ImportErr(
+                    "missing import `env::draw_line(param F32 F32 F32 F32)(result) [function]`".to_string()),
+            ],
+            contract.violations(&module)
+        );
+        assert_eq!(Err(contract.violations(&module)[0].clone()), contract.check(&module));
+    }
+
     #[test]
     fn missing_import_test() {
         let module_wat = r#"
@@ -240,6 +553,7 @@ mod tests {
             ],
             imports: vec![
             ],
+            optional: vec![],
         };
 
         assert_eq!(
@@ -282,10 +596,89 @@ mod tests {
                 ExportType::new("draw", ExternType::Function(FunctionType::new([Type::F32, Type::F32], []))),
             ],
             imports: inobj_types(&imports, &store),
+            optional: vec![],
         };
 
         assert_eq!(Ok(()), contract.check(&module));
     }
+
+    #[test]
+    fn abi_contract_test() {
+        let module_wat = r#"
+            (module
+                (import "env" "draw_rectangle" (func $dr (param f32 f32 f32 f32 f32 f32 f32)))
+                (import "env" "read_picked_file" (func $rf (param i32 i32) (result i32)))
+                (func (export "width") (result f32)
+                    f32.const 80
+                )
+                (func (export "height") (result f32)
+                    f32.const 60
+                )
+                (func (export "draw") (param $x f32) (param $y f32) (param $r f32)
+                )
+                (func (export "get_period") (result i32)
+                    i32.const 4
+                )
+            )
+        "#;
+
+        let store = Store::default();
+        let module = Module::new(&store, module_wat).expect("unable to create module");
+        let contract = Contract::abi();
+
+        assert_eq!(Ok(()), contract.check(&module));
+        assert!(contract.imports.iter().all(|i| i.module() == megs_abi::IMPORT_MODULE));
+        assert_eq!(megs_abi::PIN_EXPORTS.len(), contract.optional.len());
+    }
+
+    #[test]
+    fn builder_test() {
+        let contract = Contract::builder()
+            .require_fn::<(), f32>("width")
+            .require_fn::<(f32, f32, f32), ()>("draw")
+            .optional_fn::<i32, i64>("get_output_bits")
+            .import_fn::<(i32, i32), i32>("env", "read_picked_file")
+            .build();
+
+        assert_eq!(
+            vec![
+                ExportType::new("width", ExternType::Function(FunctionType::new([], [Type::F32]))),
+                ExportType::new("draw", ExternType::Function(FunctionType::new([Type::F32, Type::F32, Type::F32], []))),
+            ],
+            contract.exports
+        );
+        assert_eq!(
+            vec![ExportType::new("get_output_bits", ExternType::Function(FunctionType::new([Type::I32], [Type::I64])))],
+            contract.optional
+        );
+        assert_eq!(
+            vec![ImportType::new("env", "read_picked_file", ExternType::Function(FunctionType::new([Type::I32, Type::I32], [Type::I32])))],
+            contract.imports
+        );
+    }
+
+    #[test]
+    fn megs_contract_test() {
+        let mut store = Store::default();
+        let files = wasmer::FunctionEnv::new(&mut store, crate::contract::FileEnv::default());
+        let (imports, contract) = megs_contract(&mut store, &files);
+        let abi = Contract::abi();
+
+        // The imports must match the ABI, in whatever order and with
+        // the types of the host functions.
+        assert_eq!(abi::IMPORTS.len(), contract.imports.len());
+        assert_eq!(abi.imports.len(), contract.imports.len());
+        assert!(abi.imports.iter().all(|i| contract.imports.contains(i)));
+        assert_eq!(abi.exports, contract.exports);
+        assert_eq!(abi.optional, contract.optional);
+        assert_eq!(inobj_types(&imports, &store).len(), contract.imports.len());
+
+        // Other drawing backends don't change the contract.
+        let draw = DrawFns::none(&mut store);
+        let (_, headless) = megs_contract_with(&mut store, &files, draw);
+        assert_eq!(contract.imports.len(), headless.imports.len());
+        assert!(contract.imports.iter().all(|i| headless.imports.contains(i)));
+    }
 }
 
 
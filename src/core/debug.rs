@@ -0,0 +1,258 @@
+use std::{
+    collections::HashMap,
+    fmt,
+};
+use crate::core::sim::{Simulator, PinRef};
+use crate::contract::{State, Bits};
+use crate::analysis::boolean::{Expr, ParseError};
+
+/// The condition that makes a [`Breakpoint`] fire.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Trigger {
+    /// The pin changed from [`State::Low`] to [`State::High`].
+    Rising,
+    /// The pin changed from [`State::High`] to [`State::Low`].
+    Falling,
+    /// The pin changed to the given state.
+    Level(State),
+    /// The bus changed to the given value.
+    Equals(u32),
+}
+
+impl Trigger {
+    /// Check if the trigger fires for a pin that changed from
+    /// `last` to `now` during the last step.
+    ///
+    /// Levels and values only fire when the pin enters them, so the
+    /// simulation can be resumed while the condition still holds.
+    pub fn fires(&self, last: Option<Bits>, now: Bits) -> bool {
+        let level = last.map(|b| b.get(0));
+        match self {
+            Trigger::Rising => level == Some(State::Low) && now.get(0) == State::High,
+            Trigger::Falling => level == Some(State::High) && now.get(0) == State::Low,
+            _ => self.holds(now) && !last.is_some_and(|b| self.holds(b)),
+        }
+    }
+
+    /// Check if a level or value trigger matches `bits`.
+    fn holds(&self, bits: Bits) -> bool {
+        match self {
+            Trigger::Level(s) => bits.width() == 1 && bits.get(0) == *s,
+            Trigger::Equals(v) => bits.value() == Some(*v),
+            _ => false,
+        }
+    }
+
+    /// Parse a trigger like `rising`, `falling`, `high`, `low`, `z`,
+    /// `x` or `= 1f` (a hexadecimal bus value).
+    pub fn parse(text: &str) -> Option<Trigger> {
+        let text = text.trim();
+        if let Some(v) = text.strip_prefix('=') {
+            let v = v.trim();
+            return u32::from_str_radix(v.trim_start_matches("0x"), 16).ok().map(Trigger::Equals);
+        }
+
+        match text.to_lowercase().as_str() {
+            "rising" => Some(Trigger::Rising),
+            "falling" => Some(Trigger::Falling),
+            "high" | "1" => Some(Trigger::Level(State::High)),
+            "low" | "0" => Some(Trigger::Level(State::Low)),
+            "undefined" | "u" => Some(Trigger::Level(State::Undefined)),
+            "z" => Some(Trigger::Level(State::HighImpedance)),
+            "x" => Some(Trigger::Level(State::Conflict)),
+            _ => None,
+        }
+    }
+}
+
+impl fmt::Display for Trigger {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Trigger::Rising => write!(f, "rising"),
+            Trigger::Falling => write!(f, "falling"),
+            Trigger::Level(s) => write!(f, "= {}", s.symbol()),
+            Trigger::Equals(v) => write!(f, "= {:x}", v),
+        }
+    }
+}
+
+/// Pause the simulation once an output pin meets a condition.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Breakpoint {
+    /// A human readable name, e.g. of the probe attached to the pin.
+    pub name: String,
+    /// The output pin to observe.
+    pub pin: PinRef,
+    /// The condition to stop at.
+    pub trigger: Trigger,
+    /// Disabled breakpoints still count hits but don't pause.
+    pub enabled: bool,
+    /// How often the condition was met.
+    pub hits: u64,
+}
+
+impl fmt::Display for Breakpoint {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{} {}", self.name, self.trigger)
+    }
+}
+
+/// A Boolean expression over the probed pins, e.g. `EN & !RESET`,
+/// evaluated after every step.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Watch {
+    /// The expression, its variables are probe names.
+    pub expr: Expr,
+    /// The value after the last step.
+    pub value: State,
+    /// Pause the simulation when the value becomes [`State::High`].
+    pub breaks: bool,
+    /// How often the value became [`State::High`].
+    pub hits: u64,
+}
+
+/// The [`Debugger`] checks all breakpoints and watches after
+/// each simulation step.
+#[derive(Debug, Clone, Default)]
+pub struct Debugger {
+    breakpoints: Vec<Breakpoint>,
+    watches: Vec<Watch>,
+    /// The values of all observed pins after the previous step.
+    last: HashMap<PinRef, Bits>,
+    /// A description of what paused the simulation in the last step.
+    hit: Option<String>,
+}
+
+impl Debugger {
+    /// Create a new debugger without breakpoints or watches.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Stop when `pin` meets `trigger`.
+    pub fn add_breakpoint(&mut self, name: &str, pin: PinRef, trigger: Trigger) {
+        self.breakpoints.push(Breakpoint { name: name.to_string(), pin, trigger, enabled: true, hits: 0 });
+    }
+
+    /// Remove the `i`-th breakpoint.
+    pub fn remove_breakpoint(&mut self, i: usize) {
+        if i < self.breakpoints.len() {
+            self.breakpoints.remove(i);
+        }
+    }
+
+    /// Get all breakpoints.
+    pub fn breakpoints(&self) -> &[Breakpoint] {
+        &self.breakpoints
+    }
+
+    /// Get all breakpoints, e.g. to enable or disable them.
+    pub fn breakpoints_mut(&mut self) -> &mut [Breakpoint] {
+        &mut self.breakpoints
+    }
+
+    /// Watch the expression `text`, see [`Expr::parse`].
+    ///
+    /// If `breaks` is set, the simulation pauses whenever the
+    /// expression becomes [`State::High`].
+    pub fn add_watch(&mut self, text: &str, breaks: bool) -> Result<(), ParseError> {
+        let expr = Expr::parse(text)?;
+        self.watches.push(Watch { expr, value: State::Undefined, breaks, hits: 0 });
+        Ok(())
+    }
+
+    /// Remove the `i`-th watch.
+    pub fn remove_watch(&mut self, i: usize) {
+        if i < self.watches.len() {
+            self.watches.remove(i);
+        }
+    }
+
+    /// Get all watches.
+    pub fn watches(&self) -> &[Watch] {
+        &self.watches
+    }
+
+    /// Get what paused the simulation in the last step, if anything.
+    pub fn hit(&self) -> Option<&str> {
+        self.hit.as_deref()
+    }
+
+    /// Remove all breakpoints on pins matching `f`, e.g. those of a removed instance.
+    pub fn remove_pins(&mut self, f: impl Fn(&PinRef) -> bool) {
+        self.breakpoints.retain(|b| !f(&b.pin));
+        self.last.retain(|p, _| !f(p));
+    }
+
+    /// Evaluate all breakpoints and watches after a step of `sim`.
+    ///
+    /// Watch variables are looked up among the probes of the recorded
+    /// history. Returns `true` if the simulation should pause.
+    pub fn check(&mut self, sim: &Simulator) -> bool {
+        self.hit = None;
+
+        for bp in self.breakpoints.iter_mut() {
+            let now = sim.output_bits(&bp.pin);
+            if bp.trigger.fires(self.last.get(&bp.pin).copied(), now) {
+                bp.hits += 1;
+                if bp.enabled && self.hit.is_none() {
+                    self.hit = Some(format!("{} at t = {}", bp, sim.steps()));
+                }
+            }
+        }
+        for bp in self.breakpoints.iter() {
+            self.last.insert(bp.pin, sim.output_bits(&bp.pin));
+        }
+
+        if !self.watches.is_empty() {
+            let vars: HashMap<String, State> = sim.history().traces()
+                .iter()
+                .map(|t| (t.probe.name.clone(), sim.output(&t.probe.pin)))
+                .collect();
+
+            for watch in self.watches.iter_mut() {
+                let value = watch.expr.eval(&vars);
+                if value == State::High && watch.value != State::High {
+                    watch.hits += 1;
+                    if watch.breaks && self.hit.is_none() {
+                        self.hit = Some(format!("{} at t = {}", watch.expr, sim.steps()));
+                    }
+                }
+                watch.value = value;
+            }
+        }
+
+        self.hit.is_some()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn trigger_test() {
+        let (low, high) = (Bits::from(State::Low), Bits::from(State::High));
+        assert!(Trigger::Rising.fires(Some(low), high));
+        assert!(!Trigger::Rising.fires(None, high));
+        assert!(!Trigger::Rising.fires(Some(high), high));
+        assert!(Trigger::Falling.fires(Some(high), low));
+        assert!(Trigger::Level(State::High).fires(Some(low), high));
+        assert!(Trigger::Level(State::High).fires(None, high));
+        assert!(Trigger::Equals(0x2a).fires(None, Bits::from_u32(8, 0x2a)));
+        assert!(Trigger::Equals(0x2a).fires(Some(Bits::from_u32(8, 0x15)), Bits::from_u32(8, 0x2a)));
+        assert!(!Trigger::Equals(0x2a).fires(None, Bits::undefined(8)));
+
+        // A held level or value only fires when it's entered.
+        let (reset, bp) = (Trigger::Level(State::Low), Trigger::Equals(0x1f));
+        assert!(!reset.fires(Some(low), low));
+        assert!(!bp.fires(Some(Bits::from_u32(8, 0x1f)), Bits::from_u32(8, 0x1f)));
+        let hits = [high, low, low, low, high, low].windows(2).filter(|w| reset.fires(Some(w[0]), w[1])).count();
+        assert_eq!(2, hits);
+
+        assert_eq!(Some(Trigger::Equals(0x2a)), Trigger::parse("= 0x2A"));
+        assert_eq!(Some(Trigger::Level(State::Conflict)), Trigger::parse("X"));
+        assert_eq!(Some(Trigger::Rising), Trigger::parse(" rising "));
+        assert_eq!(None, Trigger::parse("sideways"));
+    }
+}
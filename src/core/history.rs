@@ -0,0 +1,200 @@
+use uuid::Uuid;
+use crate::core::sim::{Probe, PinRef};
+use crate::contract::{State, Bits};
+
+/// The recorded values of a single [`Probe`].
+#[derive(Debug, Clone, PartialEq)]
+pub struct Trace {
+    /// The probe the trace belongs to.
+    pub probe: Probe,
+    /// The width of the probed pin, i.e. of the widest value recorded.
+    pub width: usize,
+    /// Every change of the value as `(time, new value)`, ordered by time.
+    pub changes: Vec<(u64, Bits)>,
+}
+
+impl Trace {
+    /// Get the value of the trace at the given point in time.
+    pub fn bits_at(&self, time: u64) -> Bits {
+        match self.changes.partition_point(|(t, _)| *t <= time) {
+            0 => Bits::undefined(self.width),
+            i => self.changes[i - 1].1,
+        }
+    }
+
+    /// Get the state of bit `0` of the trace at the given point in time.
+    pub fn state_at(&self, time: u64) -> State {
+        self.bits_at(time).get(0)
+    }
+
+    /// Split the interval `[from, to)` into segments of constant value.
+    ///
+    /// Returns a list of `(start, end, value)` tuples.
+    pub fn segments(&self, from: u64, to: u64) -> Vec<(u64, u64, Bits)> {
+        let mut v = Vec::new();
+        if from >= to {
+            return v;
+        }
+
+        let mut start = from;
+        let mut bits = self.bits_at(from);
+        for (t, b) in self.changes.iter().filter(|(t, _)| *t > from && *t < to) {
+            v.push((start, *t, bits));
+            start = *t;
+            bits = *b;
+        }
+        v.push((start, to, bits));
+        v
+    }
+}
+
+/// The [`History`] records the states of a set of probed
+/// pins over simulation time.
+///
+/// Only changes are stored, so long periods of inactivity
+/// are cheap.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct History {
+    /// One trace per probe.
+    traces: Vec<Trace>,
+    /// The time of the last sample.
+    end: u64,
+}
+
+impl History {
+    /// Create a new history without any probes.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Start recording the given probe.
+    pub fn add_probe(&mut self, probe: Probe) {
+        self.traces.push(Trace { probe, width: 1, changes: Vec::new() });
+    }
+
+    /// Stop recording all probes attached to `pin` and drop their traces.
+    pub fn remove_probe(&mut self, pin: &PinRef) {
+        self.traces.retain(|t| t.probe.pin != *pin);
+    }
+
+    /// Stop recording all probes of the given instance.
+    pub fn remove_instance(&mut self, id: Uuid) {
+        self.traces.retain(|t| t.probe.pin.instance != id);
+    }
+
+    /// Forget all recorded changes but keep the probes.
+    pub fn clear(&mut self) {
+        for trace in self.traces.iter_mut() {
+            trace.changes.clear();
+        }
+        self.end = 0;
+    }
+
+    /// Get all traces.
+    pub fn traces(&self) -> &[Trace] {
+        &self.traces
+    }
+
+    /// Get the first probe called `name`.
+    pub fn probe(&self, name: &str) -> Option<&Probe> {
+        self.traces.iter().map(|t| &t.probe).find(|p| p.name == name)
+    }
+
+    /// Get the probes of the comma separated `names`, e.g. `A, B`,
+    /// empty names are skipped.
+    ///
+    /// Fails with the first name there is no probe for.
+    pub fn probes(&self, names: &str) -> Result<Vec<Probe>, String> {
+        names.split(',')
+            .map(str::trim)
+            .filter(|name| !name.is_empty())
+            .map(|name| self.probe(name).cloned().ok_or_else(|| name.to_string()))
+            .collect()
+    }
+
+    /// Get the time of the last sample.
+    pub fn end(&self) -> u64 {
+        self.end
+    }
+
+    /// Sample all probes at the given point in time using `bits`
+    /// to look up the current value of a pin.
+    pub fn record(&mut self, time: u64, bits: impl Fn(&PinRef) -> Bits) {
+        for trace in self.traces.iter_mut() {
+            let b = bits(&trace.probe.pin);
+            trace.width = trace.width.max(b.width());
+            if trace.changes.last().map(|(_, last)| *last) != Some(b) {
+                trace.changes.push((time, b));
+            }
+        }
+        self.end = self.end.max(time);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn record_changes_test() {
+        let pin = PinRef::new(Uuid::new_v4(), 0);
+        let mut history = History::new();
+        history.add_probe(Probe::new("A", pin));
+
+        for (t, s) in [(0, State::Undefined), (1, State::Low), (2, State::Low), (5, State::High)] {
+            history.record(t, |_| Bits::from(s));
+        }
+
+        let trace = &history.traces()[0];
+        let changes: Vec<(u64, State)> = trace.changes.iter().map(|(t, b)| (*t, b.get(0))).collect();
+        assert_eq!(vec![(0, State::Undefined), (1, State::Low), (5, State::High)], changes);
+        assert_eq!(5, history.end());
+        assert_eq!(Some(&Probe::new("A", pin)), history.probe("A"));
+        assert_eq!(None, history.probe("B"));
+        assert_eq!(State::Low, trace.state_at(4));
+        assert_eq!(State::High, trace.state_at(7));
+    }
+
+    #[test]
+    fn record_bus_test() {
+        let mut history = History::new();
+        history.add_probe(Probe::new("D", PinRef::new(Uuid::new_v4(), 0)));
+
+        history.record(0, |_| Bits::undefined(8));
+        history.record(3, |_| Bits::from_u32(8, 0x5a));
+        history.record(4, |_| Bits::from_u32(8, 0x5a));
+
+        let trace = &history.traces()[0];
+        assert_eq!(8, trace.width);
+        assert_eq!(2, trace.changes.len());
+        assert_eq!(Some(0x5a), trace.bits_at(3).value());
+        assert_eq!(None, trace.bits_at(2).value());
+    }
+
+    #[test]
+    fn probes_test() {
+        let mut history = History::new();
+        history.add_probe(Probe::new("A", PinRef::new(Uuid::new_v4(), 0)));
+        history.add_probe(Probe::new("B", PinRef::new(Uuid::new_v4(), 0)));
+
+        let names: Vec<String> = history.probes(" B, A,").unwrap().into_iter().map(|p| p.name).collect();
+        assert_eq!(vec!["B", "A"], names);
+        assert_eq!(Err("C".to_string()), history.probes("A,C"));
+    }
+
+    #[test]
+    fn segments_test() {
+        let trace = Trace {
+            probe: Probe::new("A", PinRef::new(Uuid::new_v4(), 0)),
+            width: 1,
+            changes: vec![(1, State::Low.into()), (4, State::High.into()), (6, State::Low.into())],
+        };
+
+        assert_eq!(
+            vec![(0, 1, State::Undefined.into()), (1, 4, State::Low.into()), (4, 5, State::High.into())],
+            trace.segments(0, 5)
+        );
+        assert_eq!(vec![(6, 9, State::Low.into())], trace.segments(6, 9));
+        assert!(trace.segments(3, 3).is_empty());
+    }
+}
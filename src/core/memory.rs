@@ -0,0 +1,102 @@
+use std::{
+    error::Error,
+    fmt,
+};
+
+/// The largest supported data width of a memory word.
+pub const MAX_DATA_BITS: usize = 16;
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum MemoryError {
+    /// A line of a hex image couldn't be parsed.
+    ParseErr(usize, String),
+    /// The instance doesn't exist or isn't a memory.
+    NotAMemory,
+    /// The environment has no host functions to pass files to instances.
+    NoFileEnv,
+}
+
+impl fmt::Display for MemoryError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            MemoryError::ParseErr(line, msg) => {
+                write!(f, "line {}: {}", line, msg)
+            },
+            MemoryError::NotAMemory => {
+                write!(f, "instance is not a memory")
+            },
+            MemoryError::NoFileEnv => {
+                write!(f, "file access isn't available")
+            },
+        }
+    }
+}
+
+impl Error for MemoryError {
+
+}
+
+/// Parse the contents of a memory image into words of
+/// `data_bits` bits.
+///
+/// If `hex` is set, the image is text with one or more
+/// hexadecimal words per line, separated by whitespace.
+/// Everything following a `#` is a comment. Otherwise the
+/// image is binary and contains one byte per word, or two
+/// bytes (little-endian) if `data_bits` exceeds 8.
+pub fn parse_image(bytes: &[u8], hex: bool, data_bits: usize) -> Result<Vec<u16>, MemoryError> {
+    let data_bits = data_bits.clamp(1, MAX_DATA_BITS);
+    let max = ((1u32 << data_bits) - 1) as u16;
+
+    if !hex {
+        let words: Vec<u16> = if data_bits > 8 {
+            bytes.chunks(2).map(|c| u16::from_le_bytes([c[0], *c.get(1).unwrap_or(&0)])).collect()
+        } else {
+            bytes.iter().map(|b| *b as u16).collect()
+        };
+        return Ok(words.into_iter().map(|w| w & max).collect());
+    }
+
+    let text = String::from_utf8_lossy(bytes);
+    let mut words = Vec::new();
+    for (i, line) in text.lines().enumerate() {
+        let line = line.split('#').next().unwrap_or("");
+        for word in line.split_whitespace() {
+            let digits = word.trim_start_matches("0x").trim_start_matches("0X");
+            let v = u32::from_str_radix(digits, 16).map_err(|_| {
+                MemoryError::ParseErr(i + 1, format!("`{}` is not a hexadecimal number", word))
+            })?;
+            if v > max as u32 {
+                return Err(MemoryError::ParseErr(
+                    i + 1, format!("`{}` doesn't fit into {} bits", word, data_bits)
+                ));
+            }
+            words.push(v as u16);
+        }
+    }
+    Ok(words)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parse_hex_image_test() {
+        let image = "# a comment\n00 0x1f ff\n\n  7e # trailing\n";
+        assert_eq!(vec![0x00, 0x1f, 0xff, 0x7e], parse_image(image.as_bytes(), true, 8).unwrap());
+        assert_eq!(
+            MemoryError::ParseErr(2, "`ff` doesn't fit into 4 bits".to_string()),
+            parse_image(image.as_bytes(), true, 4).unwrap_err()
+        );
+        assert!(matches!(parse_image(b"12 xy", true, 8), Err(MemoryError::ParseErr(1, _))));
+    }
+
+    #[test]
+    fn parse_binary_image_test() {
+        let image = [0x34, 0x12, 0xff];
+        assert_eq!(vec![0x34, 0x12, 0xff], parse_image(&image, false, 8).unwrap());
+        assert_eq!(vec![0x04, 0x02, 0x0f], parse_image(&image, false, 4).unwrap());
+        assert_eq!(vec![0x1234, 0x00ff], parse_image(&image, false, 16).unwrap());
+    }
+}
@@ -1,4 +1,4 @@
-use wasmer::{Store, Module, Instance, Value, imports, Imports, FunctionType, Type, ImportType, ExternType, Function, ExportType, AsStoreMut};
+use wasmer::{Store, Module, Instance, Value, imports, Imports, FunctionType, Type, ImportType, ExternType, Function, ExportType, AsStoreMut, FunctionEnv};
 use macroquad::prelude::*;
 use uuid::Uuid;
 use std::{
@@ -7,17 +7,38 @@ use std::{
     fs::{File},
     collections::HashMap,
     convert::From,
+    ops::Range,
     error::Error,
     fmt,
 };
 use crate::misc::{Point, parse_path};
 use crate::core::contract::*;
+use crate::core::sim::{Simulator, PinRef, Wire, Probe};
+use crate::core::history::History;
+use crate::core::memory::{MemoryError, parse_image};
+use crate::core::debug::Debugger;
+use crate::contract::{State, Bits, FileEnv};
+use crate::core::cache::ModuleCache;
+use crate::core::report::{Recorder, DrawCall};
+use crate::wasi::{self, WasiEnv};
+
+/// The default simulation speed in steps per second.
+pub const DEFAULT_SPEED: f32 = 60.0;
+/// The maximum number of steps simulated per frame, so a high
+/// speed can't freeze the application.
+pub const MAX_STEPS_PER_FRAME: usize = 10_000;
+/// The distance within which a click hits a pin.
+pub const PIN_RADIUS: f32 = 6.0;
 
 #[derive(Debug)]
 pub enum ModuleError {
     CompileErr(wasmer::CompileError),
     ContractErr(ContractError),
     IOErr(std::io::Error),
+    MemoryErr(MemoryError),
+    /// A WebAssembly text file couldn't be parsed, lines and
+    /// columns start at 1.
+    WatErr { file: String, line: usize, column: usize, message: String },
 }
 
 impl From<wasmer::CompileError> for ModuleError {
@@ -38,6 +59,12 @@ impl From<std::io::Error> for ModuleError {
     }
 }
 
+impl From<MemoryError> for ModuleError {
+    fn from(e: MemoryError) -> Self {
+        Self::MemoryErr(e)
+    }
+}
+
 impl fmt::Display for ModuleError {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         match self {
@@ -50,6 +77,12 @@ impl fmt::Display for ModuleError {
             ModuleError::IOErr(e) => {
                 write!(f, "{}", &e)
             },
+            ModuleError::MemoryErr(e) => {
+                write!(f, "{}", &e)
+            },
+            ModuleError::WatErr { file, line, column, message } => {
+                write!(f, "{}:{}:{}: {}", file, line, column, message)
+            },
         }
     }
 }
@@ -58,6 +91,37 @@ impl Error for ModuleError {
 
 }
 
+/// Read the module at `path`, translating WebAssembly text (`.wat`)
+/// into its binary format.
+pub fn read_module(path: &Path) -> Result<Vec<u8>, ModuleError> {
+    let mut buffer = Vec::new();
+    File::open(path)?.read_to_end(&mut buffer)?;
+
+    if path.extension().is_some_and(|ext| ext == "wat") {
+        let text = String::from_utf8(buffer)
+            .map_err(|e| std::io::Error::new(std::io::ErrorKind::InvalidData, e))?;
+        buffer = parse_wat(&text, &path.display().to_string())?;
+    }
+    Ok(buffer)
+}
+
+/// Translate the WebAssembly text `text` of `file` into its binary format.
+pub fn parse_wat(text: &str, file: &str) -> Result<Vec<u8>, ModuleError> {
+    let err = |e: wast::Error| {
+        let (line, column) = e.span().linecol_in(text);
+        ModuleError::WatErr {
+            file: file.to_string(),
+            line: line + 1,
+            column: column + 1,
+            message: e.message(),
+        }
+    };
+
+    let buffer = wast::parser::ParseBuffer::new(text).map_err(err)?;
+    let mut wat = wast::parser::parse::<wast::Wat>(&buffer).map_err(err)?;
+    wat.module.encode().map_err(err)
+}
+
 /// The instance of a [`LogicModule`].
 ///
 /// This class acts as a wrapper around a WebAssembly module
@@ -111,6 +175,271 @@ impl LogicInstance {
             // TODO: log error
         }
     }
+
+    /// Get the width of the instance.
+    pub fn width(&self, store: &mut impl AsStoreMut) -> f32 {
+        self.call_f32(store, "width").unwrap_or(0.0)
+    }
+
+    /// Get the height of the instance.
+    pub fn height(&self, store: &mut impl AsStoreMut) -> f32 {
+        self.call_f32(store, "height").unwrap_or(0.0)
+    }
+
+    /// Get the position of the input `i`.
+    ///
+    /// Inputs are assumed to be spread evenly along the left edge.
+    pub fn input_pos(&self, store: &mut impl AsStoreMut, i: usize) -> Point {
+        let n = self.inputs_nr(store);
+        Point {
+            x: self.location.x,
+            y: self.location.y + self.height(store) * (i as f32 + 1.0) / (n as f32 + 1.0),
+        }
+    }
+
+    /// Get the position of the output `i`.
+    ///
+    /// Outputs are assumed to be spread evenly along the right edge.
+    pub fn output_pos(&self, store: &mut impl AsStoreMut, i: usize) -> Point {
+        let n = self.outputs_nr(store);
+        Point {
+            x: self.location.x + self.width(store),
+            y: self.location.y + self.height(store) * (i as f32 + 1.0) / (n as f32 + 1.0),
+        }
+    }
+
+    /// Check if `point` lies within the bounding box of the instance.
+    pub fn contains(&self, store: &mut impl AsStoreMut, point: &Point) -> bool {
+        let (w, h) = (self.width(store), self.height(store));
+        point.x >= self.location.x && point.x <= self.location.x + w &&
+            point.y >= self.location.y && point.y <= self.location.y + h
+    }
+
+    /// Notify the instance that the mouse button has been pressed at `point`.
+    ///
+    /// The coordinates passed to the module are relative to the
+    /// location of the instance.
+    pub fn press(&self, store: &mut impl AsStoreMut, point: &Point) {
+        if let Ok(press) = self.instance.exports.get_function("on_press") {
+            let _ = press.call(store, &[
+                Value::F32(point.x - self.location.x),
+                Value::F32(point.y - self.location.y),
+            ]);
+        }
+    }
+
+    /// Notify the instance that the mouse button has been released at `point`.
+    ///
+    /// The coordinates passed to the module are relative to the
+    /// location of the instance.
+    pub fn release(&self, store: &mut impl AsStoreMut, point: &Point) {
+        if let Ok(release) = self.instance.exports.get_function("on_release") {
+            let _ = release.call(store, &[
+                Value::F32(point.x - self.location.x),
+                Value::F32(point.y - self.location.y),
+            ]);
+        }
+    }
+
+    /// Tell the instance the current simulation time, e.g. to drive a clock.
+    pub fn tick(&self, store: &mut impl AsStoreMut, now: u64) {
+        if let Ok(tick) = self.instance.exports.get_function("tick") {
+            let _ = tick.call(store, &[Value::I64(now as i64)]);
+        }
+    }
+
+    /// Call the exported function `name` and interpret its
+    /// first result as `f32`.
+    fn call_f32(&self, store: &mut impl AsStoreMut, name: &str) -> Option<f32> {
+        let f = self.instance.exports.get_function(name).ok()?;
+        match f.call(store, &[]).ok()?.first() {
+            Some(Value::F32(v)) => Some(*v),
+            _ => None,
+        }
+    }
+
+    /// Call the exported function `name` and interpret its
+    /// first result as `i32`.
+    fn call_i32(&self, store: &mut impl AsStoreMut, name: &str, params: &[Value]) -> Option<i32> {
+        let f = self.instance.exports.get_function(name).ok()?;
+        match f.call(store, params).ok()?.first() {
+            Some(Value::I32(v)) => Some(*v),
+            _ => None,
+        }
+    }
+
+    /// Get the number of inputs of the instance.
+    ///
+    /// Returns `0` if the module doesn't export `get_inputs_nr`.
+    pub fn inputs_nr(&self, store: &mut impl AsStoreMut) -> usize {
+        self.call_i32(store, "get_inputs_nr", &[]).unwrap_or(0).max(0) as usize
+    }
+
+    /// Change the number of inputs of the instance.
+    ///
+    /// Modules ignore counts they don't support, so check
+    /// [`LogicInstance::inputs_nr`] afterwards.
+    pub fn set_inputs_nr(&self, store: &mut impl AsStoreMut, n: usize) {
+        if let Ok(set) = self.instance.exports.get_function("set_inputs_nr") {
+            let _ = set.call(store, &[Value::I32(n as i32)]);
+        }
+    }
+
+    /// Get the number of outputs of the instance.
+    ///
+    /// Returns `0` if the module doesn't export `get_outputs_nr`.
+    pub fn outputs_nr(&self, store: &mut impl AsStoreMut) -> usize {
+        self.call_i32(store, "get_outputs_nr", &[]).unwrap_or(0).max(0) as usize
+    }
+
+    /// Drive the input `i` of the instance with the given [`State`].
+    pub fn set_input(&self, store: &mut impl AsStoreMut, i: usize, state: State) {
+        if let Ok(set) = self.instance.exports.get_function("set_input") {
+            let _ = set.call(store, &[Value::I32(i as i32), Value::I32(state.into())]);
+        }
+    }
+
+    /// Get the [`State`] of the output `i`.
+    ///
+    /// Returns [`State::Undefined`] if the module doesn't export `get_output`.
+    pub fn output(&self, store: &mut impl AsStoreMut, i: usize) -> State {
+        match self.call_i32(store, "get_output", &[Value::I32(i as i32)]) {
+            Some(v) => State::from(v),
+            None => State::Undefined,
+        }
+    }
+
+    /// Get the number of bits of the input `i`.
+    ///
+    /// Returns `1` if the module doesn't export `get_input_width`.
+    pub fn input_width(&self, store: &mut impl AsStoreMut, i: usize) -> usize {
+        self.call_i32(store, "get_input_width", &[Value::I32(i as i32)]).unwrap_or(1).max(1) as usize
+    }
+
+    /// Get the number of bits of the output `i`.
+    ///
+    /// Returns `1` if the module doesn't export `get_output_width`.
+    pub fn output_width(&self, store: &mut impl AsStoreMut, i: usize) -> usize {
+        self.call_i32(store, "get_output_width", &[Value::I32(i as i32)]).unwrap_or(1).max(1) as usize
+    }
+
+    /// Drive the input `i` of the instance with the given [`Bits`].
+    ///
+    /// Single bits are passed via `set_input`, buses via `set_input_bits`.
+    pub fn set_input_bits(&self, store: &mut impl AsStoreMut, i: usize, bits: Bits) {
+        if bits.width() == 1 {
+            self.set_input(store, i, bits.get(0));
+        } else if let Ok(set) = self.instance.exports.get_function("set_input_bits") {
+            let _ = set.call(store, &[Value::I32(i as i32), Value::I64(bits.to_i64())]);
+        }
+    }
+
+    /// Get the [`Bits`] of the output `i`.
+    ///
+    /// Single bits are read via `get_output`, buses via `get_output_bits`.
+    pub fn output_bits(&self, store: &mut impl AsStoreMut, i: usize) -> Bits {
+        let width = self.output_width(store, i);
+        if width == 1 {
+            return Bits::from(self.output(store, i));
+        }
+
+        let f = match self.instance.exports.get_function("get_output_bits") {
+            Ok(f) => f,
+            Err(_) => return Bits::undefined(width),
+        };
+        match f.call(store, &[Value::I32(i as i32)]).ok().and_then(|r| r.first().cloned()) {
+            Some(Value::I64(v)) => Bits::from_i64(width, v),
+            _ => Bits::undefined(width),
+        }
+    }
+
+    /// Get the propagation delay declared by the module, i.e. the
+    /// number of time units until changed inputs affect the outputs.
+    ///
+    /// Returns `1` if the module doesn't export `get_delay`.
+    pub fn delay(&self, store: &mut impl AsStoreMut) -> u64 {
+        self.call_i32(store, "get_delay", &[]).unwrap_or(1).max(1) as u64
+    }
+
+    /// Get the period of a clock instance in units of simulation time.
+    ///
+    /// Returns `None` if the module doesn't export `get_period`.
+    pub fn period(&self, store: &mut impl AsStoreMut) -> Option<u64> {
+        self.call_i32(store, "get_period", &[]).map(|p| p.max(1) as u64)
+    }
+
+    /// Notify the instance that input `i` changed from [`State::Low`]
+    /// to [`State::High`], e.g. to let a flip-flop latch its data.
+    pub fn rising_edge(&self, store: &mut impl AsStoreMut, i: usize) {
+        if let Ok(edge) = self.instance.exports.get_function("rising_edge") {
+            let _ = edge.call(store, &[Value::I32(i as i32)]);
+        }
+    }
+
+    /// Change the number of bits of a parameterised instance, e.g. a register.
+    ///
+    /// Modules ignore values they don't support, so check
+    /// [`LogicInstance::bits`] afterwards.
+    pub fn set_bits(&self, store: &mut impl AsStoreMut, n: usize) {
+        if let Ok(set) = self.instance.exports.get_function("set_bits") {
+            let _ = set.call(store, &[Value::I32(n as i32)]);
+        }
+    }
+
+    /// Get the number of bits of a parameterised instance.
+    ///
+    /// Returns `None` if the module isn't parameterised.
+    pub fn bits(&self, store: &mut impl AsStoreMut) -> Option<usize> {
+        self.call_i32(store, "get_bits", &[]).map(|n| n.max(0) as usize)
+    }
+
+    /// Change the width of the data words of a memory instance.
+    pub fn set_data_bits(&self, store: &mut impl AsStoreMut, n: usize) {
+        if let Ok(set) = self.instance.exports.get_function("set_data_bits") {
+            let _ = set.call(store, &[Value::I32(n as i32)]);
+        }
+    }
+
+    /// Get the width of the data words of a memory instance.
+    ///
+    /// Returns `None` if the instance isn't a memory.
+    pub fn data_bits(&self, store: &mut impl AsStoreMut) -> Option<usize> {
+        self.call_i32(store, "get_data_bits", &[]).map(|n| n.max(0) as usize)
+    }
+
+    /// Get the number of words of a memory instance.
+    ///
+    /// Returns `None` if the instance isn't a memory.
+    pub fn mem_size(&self, store: &mut impl AsStoreMut) -> Option<usize> {
+        self.call_i32(store, "mem_size", &[]).map(|n| n.max(0) as usize)
+    }
+
+    /// Read the word at `addr` of a memory instance.
+    pub fn mem_read(&self, store: &mut impl AsStoreMut, addr: usize) -> Option<u16> {
+        self.call_i32(store, "mem_read", &[Value::I32(addr as i32)]).map(|v| v as u16)
+    }
+
+    /// Overwrite the word at `addr` of a memory instance.
+    pub fn mem_write(&self, store: &mut impl AsStoreMut, addr: usize, value: u16) {
+        if let Ok(write) = self.instance.exports.get_function("mem_write") {
+            let _ = write.call(store, &[Value::I32(addr as i32), Value::I32(value as i32)]);
+        }
+    }
+
+    /// Let a memory instance load the picked file via the
+    /// `read_picked_file` import.
+    ///
+    /// Returns the number of words loaded.
+    pub fn load_contents(&self, store: &mut impl AsStoreMut) -> Option<usize> {
+        self.call_i32(store, "load_contents", &[]).map(|n| n.max(0) as usize)
+    }
+
+    /// Let the instance evaluate its inputs and update its outputs.
+    pub fn update(&self, store: &mut impl AsStoreMut) {
+        if let Ok(update) = self.instance.exports.get_function("update") {
+            let _ = update.call(store, &[]);
+        }
+    }
 }
 
 /// Represents a WebAssembly module with additional infromation.
@@ -225,6 +554,27 @@ pub struct ModuleEnv {
     cat_id: usize,
     /// Global module counter
     mod_id: usize,
+    /// The simulator that propagates states between instances.
+    sim: Simulator,
+    /// The instance that received the last mouse press.
+    pressed: Option<Uuid>,
+    /// The state of the file related host functions, if provided.
+    files: Option<FunctionEnv<FileEnv>>,
+    /// The state of recording drawing functions, if provided.
+    recorder: Option<FunctionEnv<Recorder>>,
+    /// Don't advance the simulation on tick.
+    paused: bool,
+    /// The simulation speed in steps per second.
+    speed: f32,
+    /// Steps owed to the simulation since the last tick, to run at
+    /// `speed` independent of the frame rate.
+    budget: f32,
+    /// Breakpoints and watches checked after every step.
+    debugger: Debugger,
+    /// Provide the sandboxed WASI imports to every instance.
+    wasi: bool,
+    /// The cache of compiled modules, if enabled.
+    cache: Option<ModuleCache>,
 }
 
 impl ModuleEnv {
@@ -243,8 +593,89 @@ impl ModuleEnv {
             contract,
             cat_id: 0,
             mod_id: 0,
+            sim: Simulator::new(),
+            pressed: None,
+            files: None,
+            recorder: None,
+            paused: false,
+            speed: DEFAULT_SPEED,
+            budget: 0.0,
+            debugger: Debugger::new(),
+            wasi: false,
+            cache: None,
         }
     }
+
+    /// Set the environment of the file related host functions
+    /// (see [`crate::contract::read_picked_file`]) the imports
+    /// were created with.
+    pub fn set_file_env(&mut self, files: FunctionEnv<FileEnv>) {
+        self.files = Some(files);
+    }
+
+    /// Set the environment of the recording drawing functions (see
+    /// [`crate::core::report::recording_draw_fns`]) the imports were
+    /// created with.
+    pub fn set_recorder(&mut self, recorder: FunctionEnv<Recorder>) {
+        self.recorder = Some(recorder);
+    }
+
+    /// Let `instance` draw itself and return the recorded calls.
+    ///
+    /// Returns nothing if the imports don't record (see
+    /// [`ModuleEnv::set_recorder`]).
+    pub fn record_drawing(&mut self, instance: &LogicInstance) -> Vec<DrawCall> {
+        let recorder = match &self.recorder {
+            Some(recorder) => recorder.clone(),
+            None => return Vec::new(),
+        };
+
+        recorder.as_mut(&mut self.store).recording = true;
+        instance.draw(&mut self.store);
+        let recorder = recorder.as_mut(&mut self.store);
+        recorder.recording = false;
+        std::mem::take(&mut recorder.calls)
+    }
+
+    /// Get the store of all instances, e.g. to inspect an instance
+    /// that isn't part of the environment.
+    pub(crate) fn store_mut(&mut self) -> &mut Store {
+        &mut self.store
+    }
+
+    /// Provide the sandboxed WASI subset of [`crate::wasi`] to all
+    /// instances created from now on and allow modules to import it.
+    ///
+    /// Modules already added are checked against the old contract and
+    /// have to be added again.
+    pub fn enable_wasi(&mut self) {
+        if !self.wasi {
+            self.wasi = true;
+            self.contract.imports.extend(wasi_imports());
+        }
+    }
+
+    /// Check if instances get the WASI imports.
+    pub fn wasi_enabled(&self) -> bool {
+        self.wasi
+    }
+
+    /// Load modules from `cache` instead of compiling them, or always
+    /// compile them if `None`.
+    pub fn set_cache(&mut self, cache: Option<ModuleCache>) {
+        self.cache = cache;
+    }
+
+    /// Enable the default cache of compiled modules unless disabled,
+    /// see [`ModuleCache::from_env`].
+    pub fn enable_cache(&mut self) {
+        self.cache = ModuleCache::from_env(&self.store);
+    }
+
+    /// Get the cache of compiled modules, if enabled.
+    pub fn cache(&self) -> Option<&ModuleCache> {
+        self.cache.as_ref()
+    }
     
     /// Get a reference to all existing categories.
     pub fn categories(&self) -> &HashMap<String, Category> {
@@ -271,7 +702,495 @@ impl ModuleEnv {
         &self.instances
     }
     
+    /// Get a reference to the simulator.
+    pub fn simulator(&self) -> &Simulator {
+        &self.sim
+    }
+
+    /// Get all wires between instances.
+    pub fn wires(&self) -> &[Wire] {
+        self.sim.wires()
+    }
+
+    /// Connect the output pin `from` with the input pin `to`.
+    ///
+    /// Returns `false` if one of the instances doesn't exist,
+    /// doesn't have a pin with the given index or the widths
+    /// of both pins differ.
+    pub fn connect(&mut self, from: PinRef, to: PinRef) -> bool {
+        match self.wire_width(&from, &to) {
+            Some(width) => {
+                self.sim.connect(from, to, width);
+                true
+            },
+            None => false,
+        }
+    }
+
+    /// Get the width of a wire from the output pin `from` to the
+    /// input pin `to`, or `None` if both can't be connected.
+    fn wire_width(&mut self, from: &PinRef, to: &PinRef) -> Option<usize> {
+        let src = self.instances.get(&from.instance)?;
+        let dst = self.instances.get(&to.instance)?;
+
+        if from.pin >= src.outputs_nr(&mut self.store) || to.pin >= dst.inputs_nr(&mut self.store) {
+            return None;
+        }
+
+        let width = src.output_width(&mut self.store, from.pin);
+        if width != dst.input_width(&mut self.store, to.pin) {
+            return None;
+        }
+        Some(width)
+    }
+
+    /// Remove all wires of the instance `id` that became invalid,
+    /// e.g. after changing its number of pins or their widths.
+    fn prune_wires(&mut self, id: Uuid) {
+        let stale: Vec<Wire> = self.sim.wires().iter()
+            .filter(|w| w.from.instance == id || w.to.instance == id)
+            .copied()
+            .collect();
+        let stale: Vec<Wire> = stale.into_iter()
+            .filter(|w| self.wire_width(&w.from, &w.to) != Some(w.width))
+            .collect();
+        self.sim.disconnect(|w| stale.contains(w));
+    }
+
+    /// Remove the instance `id` together with all its wires.
+    ///
+    /// Returns `false` if the instance doesn't exist.
+    pub fn remove_instance(&mut self, id: Uuid) -> bool {
+        self.sim.disconnect_instance(id);
+        self.debugger.remove_pins(|p| p.instance == id);
+        self.instances.remove(&id).is_some()
+    }
+
+    /// Change the number of inputs of the instance `id`.
+    ///
+    /// Returns the number of inputs the instance actually has
+    /// afterwards, or `None` if the instance doesn't exist.
+    pub fn set_inputs_nr(&mut self, id: Uuid, n: usize) -> Option<usize> {
+        let instance = self.instances.get(&id)?;
+        instance.set_inputs_nr(&mut self.store, n);
+        let n = instance.inputs_nr(&mut self.store);
+        self.prune_wires(id);
+        Some(n)
+    }
+
+    /// Change the number of bits of the parameterised instance `id`.
+    ///
+    /// Returns the number of bits the instance actually has afterwards,
+    /// or `None` if the instance doesn't exist or isn't parameterised.
+    pub fn set_bits(&mut self, id: Uuid, n: usize) -> Option<usize> {
+        let instance = self.instances.get(&id)?;
+        instance.set_bits(&mut self.store, n);
+        let n = instance.bits(&mut self.store);
+        self.prune_wires(id);
+        n
+    }
+
+    /// Change the width of the data words of the memory instance `id`.
+    ///
+    /// Returns the width the instance actually has afterwards, or
+    /// `None` if the instance doesn't exist or isn't a memory.
+    pub fn set_data_bits(&mut self, id: Uuid, n: usize) -> Option<usize> {
+        let instance = self.instances.get(&id)?;
+        instance.set_data_bits(&mut self.store, n);
+        let n = instance.data_bits(&mut self.store);
+        self.prune_wires(id);
+        n
+    }
+
+    /// Get all words of the memory instance `id`.
+    ///
+    /// Returns `None` if the instance doesn't exist or isn't a memory.
+    pub fn memory(&mut self, id: Uuid) -> Option<Vec<u16>> {
+        self.read_memory(id, 0..usize::MAX)
+    }
+
+    /// Get the number of words of the memory instance `id`.
+    ///
+    /// Returns `None` if there is no such instance or it isn't a memory.
+    pub fn memory_size(&mut self, id: Uuid) -> Option<usize> {
+        self.instances.get(&id)?.mem_size(&mut self.store)
+    }
+
+    /// Get the words in `range` of the memory instance `id`.
+    ///
+    /// The range is cut to the size of the memory, so only the
+    /// words that actually exist are read.
+    pub fn read_memory(&mut self, id: Uuid, range: Range<usize>) -> Option<Vec<u16>> {
+        let instance = self.instances.get(&id)?;
+        let size = instance.mem_size(&mut self.store)?;
+        (range.start.min(size)..range.end.min(size))
+            .map(|addr| instance.mem_read(&mut self.store, addr))
+            .collect()
+    }
+
+    /// Overwrite the word at `addr` of the memory instance `id`.
+    ///
+    /// Memories can only be edited while the simulation is paused,
+    /// so returns `false` if it's running or the instance isn't a memory.
+    pub fn write_memory(&mut self, id: Uuid, addr: usize, value: u16) -> bool {
+        if !self.paused {
+            return false;
+        }
+
+        match self.instances.get(&id) {
+            Some(instance) if addr < instance.mem_size(&mut self.store).unwrap_or(0) => {
+                instance.mem_write(&mut self.store, addr, value);
+                true
+            },
+            _ => false,
+        }
+    }
+
+    /// Load the contents of the memory instance `id` from the file at `path`.
+    ///
+    /// Files ending in `.hex` are parsed as hex images, all others as
+    /// binary images (see [`parse_image`]). Returns the number of words
+    /// loaded.
+    pub fn load_memory(&mut self, id: Uuid, path: &Path) -> Result<usize, ModuleError> {
+        let files = self.files.clone().ok_or(MemoryError::NoFileEnv)?;
+        let instance = self.instances.get(&id).ok_or(MemoryError::NotAMemory)?;
+        let data_bits = instance.data_bits(&mut self.store).ok_or(MemoryError::NotAMemory)?;
+        let memory = instance.instance.exports.get_memory("memory")
+            .map_err(|_| MemoryError::NotAMemory)?
+            .clone();
+
+        let mut bytes = Vec::new();
+        File::open(path)?.read_to_end(&mut bytes)?;
+        let hex = path.extension().map_or(false, |e| e.eq_ignore_ascii_case("hex"));
+        let words = parse_image(&bytes, hex, data_bits)?;
+
+        // Instances receive the words as little-endian 16-bit values.
+        let env = files.as_mut(&mut self.store);
+        env.picked = Some(words.iter().flat_map(|w| w.to_le_bytes()).collect());
+        env.memory = Some(memory);
+        let loaded = instance.load_contents(&mut self.store);
+        let env = files.as_mut(&mut self.store);
+        env.picked = None;
+        env.memory = None;
+
+        loaded.ok_or(MemoryError::NotAMemory.into())
+    }
+
+    /// Stop advancing the simulation on tick.
+    pub fn pause(&mut self) {
+        self.paused = true;
+    }
+
+    /// Continue advancing the simulation on tick.
+    pub fn run(&mut self) {
+        self.paused = false;
+    }
+
+    /// Check if the simulation is paused.
+    pub fn is_paused(&self) -> bool {
+        self.paused
+    }
+
+    /// Set the number of steps simulated per second while running.
+    pub fn set_speed(&mut self, steps_per_second: f32) {
+        self.speed = steps_per_second.max(0.0);
+    }
+
+    /// Get the number of steps simulated per second while running.
+    pub fn speed(&self) -> f32 {
+        self.speed
+    }
+
+    /// Simulate as many steps as are due after `dt` seconds at the
+    /// current speed, but at most [`MAX_STEPS_PER_FRAME`].
+    ///
+    /// Returns the number of steps simulated.
+    pub fn advance(&mut self, dt: f32) -> usize {
+        self.budget += dt * self.speed;
+        let n = (self.budget as usize).min(MAX_STEPS_PER_FRAME);
+        // Forget what couldn't be simulated instead of catching up later.
+        self.budget = if n == MAX_STEPS_PER_FRAME { 0.0 } else { self.budget.fract() };
+
+        for i in 0..n {
+            self.step();
+            if self.debugger.hit().is_some() {
+                return i + 1;
+            }
+        }
+        n
+    }
+
+    /// Simulate until the next pending output change became visible,
+    /// skipping the time in between, or a single step if nothing is pending.
+    ///
+    /// Returns the number of steps simulated.
+    pub fn step_event(&mut self) -> u64 {
+        let target = self.sim.next_event().unwrap_or(self.sim.steps() + 1);
+        let start = self.sim.steps();
+        while self.sim.steps() < target {
+            self.step();
+            if self.debugger.hit().is_some() {
+                break;
+            }
+        }
+        self.sim.steps() - start
+    }
+
+    /// Get the length of a clock cycle, i.e. the period of the fastest
+    /// clock instance, or `1` if there are no clocks.
+    pub fn cycle_length(&mut self) -> u64 {
+        let store = &mut self.store;
+        self.instances.values().filter_map(|i| i.period(store)).min().unwrap_or(1)
+    }
+
+    /// Simulate `n` clock cycles (see [`ModuleEnv::cycle_length`]),
+    /// or until a breakpoint is hit.
+    pub fn run_cycles(&mut self, n: u64) {
+        for _ in 0..n * self.cycle_length() {
+            self.step();
+            if self.debugger.hit().is_some() {
+                break;
+            }
+        }
+    }
+
+    /// Simulate until `condition` holds after a step, but at most `max_steps` steps.
+    ///
+    /// Returns the number of steps simulated if the condition was met.
+    pub fn run_until(&mut self, max_steps: u64, mut condition: impl FnMut(&mut ModuleEnv) -> bool) -> Option<u64> {
+        for i in 1..=max_steps {
+            self.step();
+            if condition(self) {
+                return Some(i);
+            }
+        }
+        None
+    }
+
+    /// Override the propagation delay of the instance `id`, or use
+    /// the one declared by its module again if `None`.
+    ///
+    /// Returns `false` if the instance doesn't exist.
+    pub fn set_delay(&mut self, id: Uuid, delay: Option<u64>) -> bool {
+        if !self.instances.contains_key(&id) {
+            return false;
+        }
+        self.sim.set_delay(id, delay);
+        true
+    }
+
+    /// Get the propagation delay of the instance `id`.
+    pub fn delay(&mut self, id: Uuid) -> Option<u64> {
+        let instance = self.instances.get(&id)?;
+        Some(self.sim.delay(&mut self.store, instance))
+    }
+
+    /// Drive the output pin `pin` with `state`, regardless of
+    /// what the instance itself computes.
+    pub fn force(&mut self, pin: PinRef, state: State) {
+        self.sim.force(pin, state);
+    }
+
+    /// Drive the output bus `pin` with `bits`, regardless of
+    /// what the instance itself computes.
+    pub fn force_bits(&mut self, pin: PinRef, bits: Bits) {
+        self.sim.force_bits(pin, bits);
+    }
+
+    /// Stop forcing the output pin `pin`.
+    pub fn release(&mut self, pin: PinRef) {
+        self.sim.release(pin);
+    }
+
+    /// Get the current state of the output pin `pin`.
+    pub fn output(&self, pin: &PinRef) -> State {
+        self.sim.output(pin)
+    }
+
+    /// Get the current value of the output pin `pin`, e.g. a bus.
+    pub fn output_bits(&self, pin: &PinRef) -> Bits {
+        self.sim.output_bits(pin)
+    }
+
+    /// Get the number of bits of the output pin `pin`, or `None`
+    /// if its instance doesn't exist.
+    pub fn output_width(&mut self, pin: &PinRef) -> Option<usize> {
+        let instance = self.instances.get(&pin.instance)?;
+        Some(instance.output_width(&mut self.store, pin.pin))
+    }
+
+    /// Record the state of `probe` after every simulation step.
+    pub fn add_probe(&mut self, probe: Probe) {
+        self.sim.history_mut().add_probe(probe);
+    }
+
+    /// Stop recording all probes attached to `pin`.
+    pub fn remove_probe(&mut self, pin: &PinRef) {
+        self.sim.history_mut().remove_probe(pin);
+    }
+
+    /// Probe the output pin `pin`, or stop probing it if it's probed
+    /// already.
+    ///
+    /// New probes are named after the module and the pin, e.g. `and.0`,
+    /// followed by a number if the name is taken (`and.0_2`). Returns the
+    /// name of the new probe, if any.
+    pub fn toggle_probe(&mut self, pin: PinRef) -> Option<String> {
+        if self.history().traces().iter().any(|t| t.probe.pin == pin) {
+            self.remove_probe(&pin);
+            return None;
+        }
+
+        let base = format!("{}.{}", self.instances.get(&pin.instance)?.name, pin.pin);
+        let name = (1..)
+            .map(|i| if i == 1 { base.clone() } else { format!("{}_{}", base, i) })
+            .find(|name| self.history().probe(name).is_none())?;
+        self.add_probe(Probe::new(&name, pin));
+        Some(name)
+    }
+
+    /// Get the recorded states of all probes.
+    pub fn history(&self) -> &History {
+        self.sim.history()
+    }
+
+    /// Forget all recorded states but keep the probes.
+    pub fn clear_history(&mut self) {
+        self.sim.history_mut().clear();
+    }
+
+    /// Get the breakpoints and watches.
+    pub fn debugger(&self) -> &Debugger {
+        &self.debugger
+    }
+
+    /// Get the breakpoints and watches, e.g. to add new ones.
+    pub fn debugger_mut(&mut self) -> &mut Debugger {
+        &mut self.debugger
+    }
+
+    /// Simulate a single step.
+    ///
+    /// The simulation is paused if a breakpoint or watch is hit
+    /// (see [`Debugger::check`]).
+    ///
+    /// Returns `true` if at least one output changed its state.
+    pub fn step(&mut self) -> bool {
+        let changed = self.sim.step(&mut self.store, &self.instances);
+        if self.debugger.check(&self.sim) {
+            self.paused = true;
+        }
+        changed
+    }
+
+    /// Simulate until the circuit settles, but at most `max_steps` steps.
+    ///
+    /// Returns `false` if the circuit didn't settle.
+    pub fn settle(&mut self, max_steps: usize) -> bool {
+        self.sim.settle(&mut self.store, &self.instances, max_steps)
+    }
+
+    /// Run `f` on the circuit and put the simulator back into its
+    /// previous state afterwards, e.g. to analyse the circuit.
+    ///
+    /// Forced pins, outputs, the recorded history and the number of
+    /// steps are restored and the instances see their previous inputs
+    /// again. The internal state of sequential instances isn't saved.
+    pub fn isolated<T>(&mut self, f: impl FnOnce(&mut ModuleEnv) -> T) -> T {
+        let saved = self.sim.clone();
+        let result = f(self);
+        self.sim = saved;
+
+        for wire in self.sim.wires() {
+            if let Some(instance) = self.instances.get(&wire.to.instance) {
+                instance.set_input_bits(&mut self.store, wire.to.pin, self.sim.net(&wire.to));
+            }
+        }
+        for instance in self.instances.values() {
+            instance.update(&mut self.store);
+        }
+        result
+    }
+
+    /// Get the instance at `point`, if any.
+    pub fn instance_at(&mut self, point: &Point) -> Option<Uuid> {
+        for (id, instance) in self.instances.iter() {
+            if instance.contains(&mut self.store, point) {
+                return Some(*id);
+            }
+        }
+        None
+    }
+
+    /// Get the output pin within `radius` of `point`, if any.
+    pub fn output_at(&mut self, point: &Point, radius: f32) -> Option<PinRef> {
+        for (id, instance) in self.instances.iter() {
+            for i in 0..instance.outputs_nr(&mut self.store) {
+                let p = instance.output_pos(&mut self.store, i);
+                if (p.x - point.x).powi(2) + (p.y - point.y).powi(2) <= radius * radius {
+                    return Some(PinRef::new(*id, i));
+                }
+            }
+        }
+        None
+    }
+
+    /// Forward a mouse press at `point` to the instance below it.
+    ///
+    /// Returns the id of the pressed instance.
+    pub fn on_press(&mut self, point: Point) -> Option<Uuid> {
+        let id = self.instance_at(&point)?;
+        self.instances[&id].press(&mut self.store, &point);
+        self.pressed = Some(id);
+        Some(id)
+    }
+
+    /// Forward a mouse release at `point` to the instance that
+    /// received the preceding press.
+    pub fn on_release(&mut self, point: Point) {
+        if let Some(id) = self.pressed.take() {
+            if let Some(instance) = self.instances.get(&id) {
+                instance.release(&mut self.store, &point);
+            }
+        }
+    }
+
+    /// Draw all wires colored by the value of their net.
+    ///
+    /// Nets in conflict are highlighted.
+    pub fn draw_wires(&mut self) {
+        for wire in self.sim.wires().iter() {
+            let (src, dst) = match (self.instances.get(&wire.from.instance), self.instances.get(&wire.to.instance)) {
+                (Some(src), Some(dst)) => (src, dst),
+                _ => continue,
+            };
+            let from = src.output_pos(&mut self.store, wire.from.pin);
+            let to = dst.input_pos(&mut self.store, wire.to.pin);
+
+            let net = self.sim.net(&wire.to);
+            let (thickness, color) = if net.has_conflict() {
+                (5.0, RED)
+            } else {
+                let thickness = if wire.width > 1 { 3.0 } else { 1.5 };
+                match net.get(0) {
+                    _ if wire.width > 1 => (thickness, DARKBLUE),
+                    State::Low => (thickness, DARKGREEN),
+                    State::High => (thickness, GREEN),
+                    State::HighImpedance => (thickness, SKYBLUE),
+                    State::Undefined | State::Conflict => (thickness, ORANGE),
+                }
+            };
+            draw_line(from.x, from.y, to.x, to.y, thickness, color);
+        }
+    }
+
     pub fn on_tick(&mut self) {
+        if !self.paused {
+            self.advance(get_frame_time());
+        }
+
+        self.draw_wires();
+
         for (_, instance) in &self.instances {
             instance.draw(&mut self.store);
         }
@@ -305,7 +1224,7 @@ impl ModuleEnv {
         
         let id = self.mod_id;
 
-        let module = Module::new(&self.store, module)?;
+        let module = self.compile(module)?;
         self.contract.check(&module)?;
 
         self.categories.get_mut(category).unwrap().add_module(
@@ -320,6 +1239,53 @@ impl ModuleEnv {
         Ok(())
     }
     
+    /// Compile `module` (binary or text), using the cache of compiled
+    /// modules if enabled.
+    pub fn compile(&self, module: &[u8]) -> Result<Module, ModuleError> {
+        Ok(match &self.cache {
+            Some(cache) => cache.compile(&self.store, module)?,
+            None => Module::new(&self.store, module)?,
+        })
+    }
+
+    /// Get the contract all modules must obey.
+    pub fn contract(&self) -> &Contract {
+        &self.contract
+    }
+
+    /// Add all WebAssembly modules found within `dir`, binary (`.wasm`)
+    /// as well as text (`.wat`) ones.
+    ///
+    /// Every sub-directory of `dir` is treated as a category, e.g.
+    /// `dir/Gates/and.wasm` is added as module `and` of the category
+    /// `Gates`. Returns the errors of all modules that couldn't be added.
+    pub fn add_modules(&mut self, dir: &Path) -> Vec<ModuleError> {
+        let mut errors = Vec::new();
+
+        let categories = match std::fs::read_dir(dir) {
+            Ok(entries) => entries,
+            Err(e) => return vec![e.into()],
+        };
+
+        for category in categories.flatten() {
+            let modules = match std::fs::read_dir(category.path()) {
+                Ok(entries) => entries,
+                Err(_) => continue,
+            };
+
+            for module in modules.flatten() {
+                let path = module.path();
+                if path.extension().is_some_and(|ext| ext == "wasm" || ext == "wat") {
+                    if let Err(e) = self.add_module(&path) {
+                        errors.push(e);
+                    }
+                }
+            }
+        }
+
+        errors
+    }
+
     /// Add WebAssembly module from file path to the specified category.
     ///
     /// If the category doesn't exist, a new one is created.
@@ -327,12 +1293,9 @@ impl ModuleEnv {
     /// After adding the module one can create new instances of
     /// it by invoking [`ModuleEnv::instantiate`].
     pub fn add_module(&mut self, wasm_file: &Path) -> Result<(), ModuleError> {
-        let mut buffer = Vec::new();
-        let mut module = File::open(wasm_file)?;
-        module.read_to_end(&mut buffer)?;
+        let buffer = read_module(wasm_file)?;
 
         let (category, name) = parse_path(wasm_file).expect("invalid path");
-        println!("{}, {}", &category, &name);
         self.add_module_raw(&category, &name, &buffer)
     }
     
@@ -343,9 +1306,10 @@ impl ModuleEnv {
             return None;
         }
 
-        if let Ok(instance) = self.categories[category].modules()[module].instantiate(
-            &mut self.store, &self.imports, pos, 0.0
-        ) {
+        let module = self.categories[category].modules()[module].clone();
+        let instance = self.instantiate_module(&module, pos);
+
+        if let Ok(instance) = instance {
             let uuid = Some(instance.id());
             self.instances.insert(instance.id(), instance);
             uuid
@@ -354,11 +1318,31 @@ impl ModuleEnv {
             None
         }
     }
+
+    /// Create an instance of `module` with the imports of the environment,
+    /// without adding it to the environment.
+    pub fn instantiate_module(&mut self, module: &LogicModule, pos: Point) -> Result<LogicInstance, wasmer::InstantiationError> {
+        if self.wasi {
+            // Every instance has a memory of its own, so it needs its
+            // own WASI environment as well.
+            let mut imports = self.imports.clone();
+            let env = FunctionEnv::new(&mut self.store, WasiEnv::new(module.name()));
+            wasi::define_imports(&mut imports, &mut self.store, &env);
+            module.instantiate(&mut self.store, &imports, pos, 0.0).and_then(|instance| {
+                wasi::initialize(&mut self.store, &env, &instance.instance)
+                    .map_err(wasmer::InstantiationError::Start)?;
+                Ok(instance)
+            })
+        } else {
+            module.instantiate(&mut self.store, &self.imports, pos, 0.0)
+        }
+    }
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::core::debug::Trigger;
 
     pub fn draw_rectangle(x: f32, y: f32, w: f32, h: f32, r: f32, g: f32, b: f32) {
 
@@ -377,6 +1361,7 @@ mod tests {
             imports: vec![
                 ImportType::new("env", "draw_rectangle", ExternType::Function(FunctionType::new([Type::F32, Type::F32, Type::F32, Type::F32, Type::F32, Type::F32, Type::F32], []))),
             ],
+            optional: vec![],
         };
 
         let env = ModuleEnv::new(store, imports, contract);
@@ -397,6 +1382,7 @@ mod tests {
             imports: vec![
                 ImportType::new("env", "draw_rectangle", ExternType::Function(FunctionType::new([Type::F32, Type::F32, Type::F32, Type::F32, Type::F32, Type::F32, Type::F32], []))),
             ],
+            optional: vec![],
         };
         let mut env = ModuleEnv::new(store, imports, contract);
         env.add_category("Gates".to_string());
@@ -436,6 +1422,7 @@ mod tests {
             imports: vec![
                 ImportType::new("env", "draw_rectangle", ExternType::Function(FunctionType::new([Type::F32, Type::F32, Type::F32, Type::F32, Type::F32, Type::F32, Type::F32], []))),
             ],
+            optional: vec![],
         };
         let mut env = ModuleEnv::new(store, imports, contract);
         env.add_category("Gates".to_string());
@@ -450,4 +1437,343 @@ mod tests {
         env.instantiate("Gates", "AND", Point { x: -15.0, y: 200.0 });
         assert_eq!(3, env.instances().len());
     }
+
+    #[test]
+    fn press_and_tick_test() {
+        // Output 0 toggles on every press, the time is stored in `$now`.
+        let module_wat = r#"
+            (module
+                (global $q (mut i32) (i32.const 1))
+                (global $now (mut i64) (i64.const 0))
+                (func (export "width") (result f32) f32.const 40)
+                (func (export "height") (result f32) f32.const 40)
+                (func (export "get_outputs_nr") (result i32) i32.const 1)
+                (func (export "get_output") (param $i i32) (result i32) global.get $q)
+                (func (export "on_press") (param $x f32) (param $y f32)
+                    (global.set $q (i32.sub (i32.const 3) (global.get $q)))
+                )
+                (func (export "tick") (param $t i64) (global.set $now (local.get $t)))
+                (func (export "now") (result i64) global.get $now)
+            )
+        "#;
+
+        let contract = Contract {
+            exports: vec![],
+            imports: vec![],
+            optional: vec![],
+        };
+        let mut env = ModuleEnv::new(Store::default(), Imports::new(), contract);
+        env.add_module_raw("Input Controlls", "switch", module_wat.as_bytes()).unwrap();
+        let id = env.instantiate("Input Controlls", "switch", Point { x: 100.0, y: 100.0 }).unwrap();
+        let pin = PinRef::new(id, 0);
+
+        assert_eq!(None, env.on_press(Point { x: 50.0, y: 50.0 }));
+        env.step();
+        assert_eq!(State::Low, env.output(&pin));
+
+        assert_eq!(Some(id), env.on_press(Point { x: 120.0, y: 130.0 }));
+        env.on_release(Point { x: 0.0, y: 0.0 });
+        env.step();
+        assert_eq!(State::High, env.output(&pin));
+
+        let now = env.instances()[&id].instance.exports.get_function("now").unwrap().clone();
+        assert_eq!(1, now.call(&mut env.store, &[]).unwrap()[0].unwrap_i64());
+    }
+
+    #[test]
+    fn set_bits_test() {
+        // An n-bit module with one input per bit, supporting 1 to 4 bits.
+        let module_wat = r#"
+            (module
+                (global $bits (mut i32) (i32.const 1))
+                (func (export "get_bits") (result i32) global.get $bits)
+                (func (export "set_bits") (param $n i32)
+                    (if (i32.and (i32.ge_s (local.get $n) (i32.const 1)) (i32.le_s (local.get $n) (i32.const 4)))
+                        (then (global.set $bits (local.get $n)))
+                    )
+                )
+                (func (export "get_inputs_nr") (result i32) global.get $bits)
+                (func (export "set_input") (param $i i32) (param $s i32))
+            )
+        "#;
+
+        let contract = Contract {
+            exports: vec![],
+            imports: vec![],
+            optional: vec![],
+        };
+        let mut env = ModuleEnv::new(Store::default(), Imports::new(), contract);
+        env.add_module_raw("Arithmetic", "adder", module_wat.as_bytes()).unwrap();
+        env.add_module_raw("Arithmetic", "none", "(module)".as_bytes()).unwrap();
+        let adder = env.instantiate("Arithmetic", "adder", Point { x: 0.0, y: 0.0 }).unwrap();
+        let none = env.instantiate("Arithmetic", "none", Point { x: 0.0, y: 0.0 }).unwrap();
+
+        assert_eq!(Some(3), env.set_bits(adder, 3));
+        assert_eq!(Some(3), env.set_bits(adder, 17));
+        assert_eq!(3, env.instances()[&adder].inputs_nr(&mut env.store));
+        assert_eq!(None, env.set_bits(none, 3));
+        assert_eq!(None, env.set_bits(Uuid::new_v4(), 3));
+    }
+
+    #[test]
+    fn wat_module_test() {
+        let dir = std::env::temp_dir().join(format!("megs-{}", Uuid::new_v4()));
+        std::fs::create_dir_all(dir.join("Gates")).unwrap();
+        std::fs::write(dir.join("Gates/high.wat"), r#"
+            (module
+                (func (export "get_output") (param $i i32) (result i32) i32.const 2)
+            )
+        "#).unwrap();
+        std::fs::write(dir.join("Gates/broken.wat"), "(module\n  (func\n    i32.bogus))\n").unwrap();
+
+        let mut env = ModuleEnv::new(Store::default(), Imports::new(), Contract::builder().build());
+        let errors = env.add_modules(&dir);
+        std::fs::remove_dir_all(&dir).unwrap();
+
+        assert_eq!(Some(vec![&"high".to_string()]), env.module_names("Gates"));
+        assert_eq!(1, errors.len());
+        match &errors[0] {
+            ModuleError::WatErr { file, line, column, .. } => {
+                assert!(file.ends_with("broken.wat"));
+                assert_eq!((3, 5), (*line, *column));
+            },
+            e => panic!("unexpected error {}", e),
+        }
+    }
+
+    #[test]
+    fn wasi_test() {
+        let module_wat = r#"
+            (module
+                (import "wasi_snapshot_preview1" "fd_write" (func $write (param i32 i32 i32 i32) (result i32)))
+                (import "wasi_snapshot_preview1" "random_get" (func $random (param i32 i32) (result i32)))
+                (memory (export "memory") 1)
+                (data (i32.const 16) "hello\n")
+                (func (export "_initialize")
+                    (i32.store (i32.const 8) (i32.const 16))
+                    (i32.store (i32.const 12) (i32.const 6))
+                    (drop (call $write (i32.const 1) (i32.const 8) (i32.const 1) (i32.const 24)))
+                    (drop (call $random (i32.const 0) (i32.const 4)))
+                    (i32.store (i32.const 28) (call $random (i32.const 0) (i32.const -1)))
+                )
+                (func (export "get_outputs_nr") (result i32) i32.const 2)
+                (func (export "get_output") (param $i i32) (result i32)
+                    (if (result i32) (local.get $i)
+                        (then (select (i32.const 2) (i32.const 1) (i32.and
+                            (i32.ne (i32.load (i32.const 0)) (i32.const 0))
+                            (i32.eq (i32.load (i32.const 28)) (i32.const 21))
+                        )))
+                        (else (select (i32.const 2) (i32.const 1) (i32.eq (i32.load (i32.const 24)) (i32.const 6))))
+                    )
+                )
+            )
+        "#;
+        let exit_wat = r#"
+            (module
+                (import "wasi_snapshot_preview1" "proc_exit" (func $exit (param i32)))
+                (func (export "_initialize") (call $exit (i32.const 3)))
+            )
+        "#;
+
+        let contract = Contract::builder().megs_exports().build();
+        let mut env = ModuleEnv::new(Store::default(), Imports::new(), Contract { exports: vec![], ..contract });
+        assert!(matches!(env.add_module_raw("WASI", "hello", module_wat.as_bytes()), Err(ModuleError::ContractErr(_))));
+
+        env.enable_wasi();
+        assert!(env.wasi_enabled());
+        env.add_module_raw("WASI", "hello", module_wat.as_bytes()).unwrap();
+        env.add_module_raw("WASI", "exit", exit_wat.as_bytes()).unwrap();
+
+        let id = env.instantiate("WASI", "hello", Point { x: 0.0, y: 0.0 }).unwrap();
+        let instance = env.instances.get(&id).unwrap();
+        // `fd_write` reported all six bytes written and `random_get`
+        // filled in a (non zero) word, but refused to fill 4 GiB.
+        assert_eq!(State::High, instance.output(&mut env.store, 0));
+        assert_eq!(State::High, instance.output(&mut env.store, 1));
+
+        assert_eq!(None, env.instantiate("WASI", "exit", Point { x: 0.0, y: 0.0 }));
+    }
+
+    #[test]
+    fn load_memory_test() {
+        // A memory of four 8-bit words stored as 16-bit values.
+        let module_wat = r#"
+            (module
+                (import "env" "read_picked_file" (func $read (param i32 i32) (result i32)))
+                (memory (export "memory") 1)
+                (func (export "get_data_bits") (result i32) i32.const 8)
+                (func (export "mem_size") (result i32) i32.const 4)
+                (func (export "mem_read") (param $a i32) (result i32)
+                    (i32.load16_u (i32.mul (local.get $a) (i32.const 2)))
+                )
+                (func (export "mem_write") (param $a i32) (param $v i32)
+                    (i32.store16 (i32.mul (local.get $a) (i32.const 2)) (local.get $v))
+                )
+                (func (export "load_contents") (result i32)
+                    (i32.shr_u (call $read (i32.const 0) (i32.const 8)) (i32.const 1))
+                )
+            )
+        "#;
+
+        let mut store = Store::default();
+        let files = FunctionEnv::new(&mut store, FileEnv::default());
+        let imports = imports! {
+            "env" => {
+                "read_picked_file" => Function::new_typed_with_env(&mut store, &files, crate::contract::read_picked_file),
+            },
+        };
+        let contract = Contract {
+            exports: vec![],
+            imports: vec![
+                ImportType::new("env", "read_picked_file", ExternType::Function(FunctionType::new([Type::I32, Type::I32], [Type::I32]))),
+            ],
+            optional: vec![],
+        };
+        let mut env = ModuleEnv::new(store, imports, contract);
+        env.add_module_raw("Memory", "ram", module_wat.as_bytes()).unwrap();
+        let id = env.instantiate("Memory", "ram", Point { x: 0.0, y: 0.0 }).unwrap();
+
+        let path = std::env::temp_dir().join(format!("megs-{}.hex", Uuid::new_v4()));
+        std::fs::write(&path, "01 02 # comment\nff\n").unwrap();
+        assert!(matches!(env.load_memory(id, &path), Err(ModuleError::MemoryErr(MemoryError::NoFileEnv))));
+
+        env.set_file_env(files);
+        assert_eq!(3, env.load_memory(id, &path).unwrap());
+        std::fs::remove_file(&path).unwrap();
+        assert_eq!(Some(vec![0x01, 0x02, 0xff, 0x00]), env.memory(id));
+
+        assert!(!env.write_memory(id, 3, 0x42));
+        env.pause();
+        assert!(env.write_memory(id, 3, 0x42));
+        assert!(!env.write_memory(id, 4, 0x42));
+        assert_eq!(Some(vec![0x01, 0x02, 0xff, 0x42]), env.memory(id));
+
+        assert_eq!(Some(4), env.memory_size(id));
+        assert_eq!(Some(vec![0x02, 0xff]), env.read_memory(id, 1..3));
+        assert_eq!(Some(vec![0x42]), env.read_memory(id, 3..12));
+        assert_eq!(Some(vec![]), env.read_memory(id, 8..16));
+    }
+
+    #[test]
+    fn simulation_controls_test() {
+        // A clock with a period of four time units and a delay of three.
+        let module_wat = r#"
+            (module
+                (global $now (mut i64) (i64.const 0))
+                (func (export "get_outputs_nr") (result i32) i32.const 1)
+                (func (export "get_period") (result i32) i32.const 4)
+                (func (export "get_delay") (result i32) i32.const 3)
+                (func (export "tick") (param $t i64) (global.set $now (local.get $t)))
+                (func (export "get_output") (param $i i32) (result i32)
+                    (select (i32.const 2) (i32.const 1) (i32.wrap_i64 (i64.and (global.get $now) (i64.const 2))))
+                )
+            )
+        "#;
+
+        let contract = Contract {
+            exports: vec![],
+            imports: vec![],
+            optional: vec![],
+        };
+        let mut env = ModuleEnv::new(Store::default(), Imports::new(), contract);
+        assert_eq!(1, env.cycle_length());
+        env.add_module_raw("Input Controlls", "clock", module_wat.as_bytes()).unwrap();
+        let id = env.instantiate("Input Controlls", "clock", Point { x: 0.0, y: 0.0 }).unwrap();
+        let pin = PinRef::new(id, 0);
+        assert_eq!(4, env.cycle_length());
+
+        // Nothing is pending yet, then skip to the first change.
+        assert_eq!(1, env.step_event());
+        assert_eq!(2, env.step_event());
+        assert_eq!(State::Low, env.output(&pin));
+
+        assert_eq!(Some(2), env.run_until(100, |env| env.output(&pin) == State::High));
+        assert_eq!(None, env.run_until(1, |_| false));
+        assert_eq!(6, env.simulator().steps());
+
+        env.run_cycles(2);
+        assert_eq!(14, env.simulator().steps());
+
+        env.set_speed(8.0);
+        assert_eq!(4, env.advance(0.5));
+        assert_eq!(0, env.advance(0.0625));
+        assert_eq!(1, env.advance(0.0625));
+        env.set_speed(1e9);
+        assert_eq!(MAX_STEPS_PER_FRAME, env.advance(1.0));
+    }
+
+    #[test]
+    fn breakpoint_test() {
+        // The clock of `simulation_controls_test`, it rises at t = 5, 9, 13, ...
+        let module_wat = r#"
+            (module
+                (global $now (mut i64) (i64.const 0))
+                (func (export "get_outputs_nr") (result i32) i32.const 1)
+                (func (export "get_period") (result i32) i32.const 4)
+                (func (export "get_delay") (result i32) i32.const 3)
+                (func (export "tick") (param $t i64) (global.set $now (local.get $t)))
+                (func (export "get_output") (param $i i32) (result i32)
+                    (select (i32.const 2) (i32.const 1) (i32.wrap_i64 (i64.and (global.get $now) (i64.const 2))))
+                )
+            )
+        "#;
+
+        let contract = Contract {
+            exports: vec![],
+            imports: vec![],
+            optional: vec![],
+        };
+        let mut env = ModuleEnv::new(Store::default(), Imports::new(), contract);
+        env.add_module_raw("Input Controlls", "clock", module_wat.as_bytes()).unwrap();
+        let id = env.instantiate("Input Controlls", "clock", Point { x: 0.0, y: 0.0 }).unwrap();
+        let pin = PinRef::new(id, 0);
+        env.add_probe(Probe::new("CLK", pin));
+        env.debugger_mut().add_breakpoint("CLK", pin, Trigger::Rising);
+        env.debugger_mut().add_watch("CLK", false).unwrap();
+        assert!(env.debugger_mut().add_watch("CLK &", false).is_err());
+
+        // Running stops at the first rising edge.
+        env.run_cycles(10);
+        assert_eq!(5, env.simulator().steps());
+        assert!(env.is_paused());
+        assert_eq!(Some("CLK rising at t = 5"), env.debugger().hit());
+        assert_eq!(1, env.debugger().breakpoints()[0].hits);
+        assert_eq!(1, env.debugger().watches()[0].hits);
+        assert_eq!(State::High, env.debugger().watches()[0].value);
+
+        env.step();
+        assert_eq!(None, env.debugger().hit());
+
+        // Disabled breakpoints keep counting.
+        env.debugger_mut().breakpoints_mut()[0].enabled = false;
+        env.run();
+        env.run_cycles(2);
+        assert_eq!(14, env.simulator().steps());
+        assert!(!env.is_paused());
+        assert_eq!(3, env.debugger().breakpoints()[0].hits);
+        assert_eq!(3, env.debugger().watches()[0].hits);
+
+        env.remove_instance(id);
+        assert!(env.debugger().breakpoints().is_empty());
+    }
+
+    #[test]
+    fn toggle_probe_test() {
+        let mut env = crate::analysis::truth_table::tests::test_env();
+        let a = env.instantiate("Test", "source", Point { x: 0.0, y: 0.0 }).unwrap();
+        let b = env.instantiate("Test", "source", Point { x: 100.0, y: 0.0 }).unwrap();
+
+        // The test source has no size, its output sits at its location.
+        let pin = env.output_at(&Point { x: 98.0, y: 3.0 }, PIN_RADIUS).unwrap();
+        assert_eq!(PinRef::new(b, 0), pin);
+        assert_eq!(None, env.output_at(&Point { x: 50.0, y: 0.0 }, PIN_RADIUS));
+
+        assert_eq!(Some("source.0".to_string()), env.toggle_probe(PinRef::new(a, 0)));
+        assert_eq!(Some("source.0_2".to_string()), env.toggle_probe(pin));
+        assert_eq!(2, env.history().traces().len());
+
+        assert_eq!(None, env.toggle_probe(PinRef::new(a, 0)));
+        assert_eq!(None, env.history().probe("source.0"));
+        assert_eq!(Some(&Probe::new("source.0_2", pin)), env.history().probe("source.0_2"));
+    }
 }
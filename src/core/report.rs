@@ -0,0 +1,331 @@
+use wasmer::{Store, Function, FunctionEnv, FunctionEnvMut, ExternType};
+use std::fmt;
+use crate::core::contract::{Contract, ContractError, DrawFns};
+use crate::core::module::{LogicModule, ModuleEnv, ModuleError};
+use crate::misc::Point;
+
+/// The optional exports of the `Pins` section of the README, grouped
+/// by what they provide.
+pub const EXPORT_GROUPS: [(&str, &[&str]); 6] = [
+    ("pins", &["get_inputs_nr", "set_inputs_nr", "get_outputs_nr", "set_input", "get_output", "update"]),
+    ("buses", &["get_input_width", "get_output_width", "set_input_bits", "get_output_bits"]),
+    ("timing", &["get_delay", "get_period", "tick", "rising_edge"]),
+    ("events", &["on_press", "on_release"]),
+    ("parameters", &["set_bits", "get_bits"]),
+    ("memory", &["set_data_bits", "get_data_bits", "mem_size", "mem_read", "mem_write", "load_contents"]),
+];
+
+/// A single call of one of the imported drawing functions.
+///
+/// Colors are given as `[r, g, b]` within `0..=1`.
+#[derive(Debug, Clone, PartialEq)]
+pub enum DrawCall {
+    Rectangle { x: f32, y: f32, w: f32, h: f32, color: [f32; 3] },
+    Circle { x: f32, y: f32, radius: f32, color: [f32; 3] },
+    CircleLines { x: f32, y: f32, radius: f32, thickness: f32, color: [f32; 3] },
+    Line { x1: f32, y1: f32, x2: f32, y2: f32, thickness: f32, color: [f32; 3] },
+}
+
+/// The state of the recording drawing functions.
+#[derive(Debug, Clone, Default)]
+pub struct Recorder {
+    /// Record calls, otherwise they are dropped.
+    pub recording: bool,
+    /// All calls in the order they were made.
+    pub calls: Vec<DrawCall>,
+}
+
+impl Recorder {
+    fn record(&mut self, call: DrawCall) {
+        if self.recording {
+            self.calls.push(call);
+        }
+    }
+}
+
+fn record_rectangle(mut env: FunctionEnvMut<Recorder>, x: f32, y: f32, w: f32, h: f32, r: f32, g: f32, b: f32) {
+    env.data_mut().record(DrawCall::Rectangle { x, y, w, h, color: [r, g, b] });
+}
+
+fn record_circle(mut env: FunctionEnvMut<Recorder>, x: f32, y: f32, radius: f32, r: f32, g: f32, b: f32) {
+    env.data_mut().record(DrawCall::Circle { x, y, radius, color: [r, g, b] });
+}
+
+fn record_circle_lines(
+    mut env: FunctionEnvMut<Recorder>, x: f32, y: f32, radius: f32, thickness: f32, r: f32, g: f32, b: f32
+) {
+    env.data_mut().record(DrawCall::CircleLines { x, y, radius, thickness, color: [r, g, b] });
+}
+
+fn record_line(
+    mut env: FunctionEnvMut<Recorder>, x1: f32, y1: f32, x2: f32, y2: f32, thickness: f32, r: f32, g: f32, b: f32
+) {
+    env.data_mut().record(DrawCall::Line { x1, y1, x2, y2, thickness, color: [r, g, b] });
+}
+
+/// Get drawing functions that record their calls into `recorder`
+/// instead of drawing, see [`ModuleEnv::record_drawing`].
+pub fn recording_draw_fns(store: &mut Store, recorder: &FunctionEnv<Recorder>) -> DrawFns {
+    DrawFns {
+        rectangle: Function::new_typed_with_env(store, recorder, record_rectangle),
+        circle: Function::new_typed_with_env(store, recorder, record_circle),
+        circle_lines: Function::new_typed_with_env(store, recorder, record_circle_lines),
+        line: Function::new_typed_with_env(store, recorder, record_line),
+    }
+}
+
+/// Everything a module author wants to know about a module: what
+/// it exports and imports, how it violates the [`Contract`] and
+/// what it looks like.
+#[derive(Debug, Clone, PartialEq, Default)]
+pub struct ModuleReport {
+    /// The name of the module.
+    pub name: String,
+    /// All exports with their types.
+    pub exports: Vec<String>,
+    /// All imports with their types.
+    pub imports: Vec<String>,
+    /// All violations of the contract, see [`Contract::violations`].
+    pub violations: Vec<ContractError>,
+    /// The optional exports found, by group (see [`EXPORT_GROUPS`]).
+    pub optional: Vec<(&'static str, Vec<&'static str>)>,
+    /// Exported functions that are neither required nor optional.
+    pub unknown: Vec<String>,
+    /// Why the module couldn't be instantiated, if it couldn't.
+    pub instantiation: Option<String>,
+    /// The size of the exported memory in bytes.
+    pub memory: Option<u64>,
+    /// The reported `width()` and `height()`.
+    pub size: (f32, f32),
+    /// The number of inputs and outputs of a new instance.
+    pub pins: (usize, usize),
+    /// The position of every input relative to the origin.
+    pub input_pos: Vec<Point>,
+    /// The position of every output relative to the origin.
+    pub output_pos: Vec<Point>,
+    /// The width of every input and output.
+    pub widths: (Vec<usize>, Vec<usize>),
+    /// The declared propagation delay.
+    pub delay: u64,
+    /// The period, if the module is a clock.
+    pub period: Option<u64>,
+    /// The number of bits, if the module is parameterised.
+    pub bits: Option<usize>,
+    /// The number of words and bits per word, if the module is a memory.
+    pub words: Option<(usize, usize)>,
+    /// The calls made by `draw` at the origin without rotation.
+    pub drawing: Vec<DrawCall>,
+}
+
+impl ModuleReport {
+    /// Compile `module` within `env`, check it against the contract of
+    /// `env` and inspect a new instance of it.
+    ///
+    /// The instance gets the imports of `env` (including WASI, if
+    /// enabled), but isn't added to it. Its drawing is only recorded
+    /// if `env` records (see [`ModuleEnv::set_recorder`]). Fails only
+    /// if the module can't be compiled.
+    pub fn generate(env: &mut ModuleEnv, name: &str, module: &[u8]) -> Result<Self, ModuleError> {
+        let module = env.compile(module)?;
+        let contract = env.contract();
+
+        let mut report = ModuleReport {
+            name: name.to_string(),
+            exports: module.exports()
+                .map(|e| format!("{}{}", e.name(), Contract::format_extern(e.ty())))
+                .collect(),
+            imports: module.imports()
+                .map(|i| format!("{}::{}{}", i.module(), i.name(), Contract::format_extern(i.ty())))
+                .collect(),
+            violations: contract.violations(&module),
+            ..Default::default()
+        };
+
+        let names: Vec<String> = module.exports()
+            .filter(|e| matches!(e.ty(), ExternType::Function(_)))
+            .map(|e| e.name().to_string())
+            .collect();
+        for (group, exports) in EXPORT_GROUPS {
+            let found = exports.iter().copied().filter(|e| names.iter().any(|n| n == e)).collect();
+            report.optional.push((group, found));
+        }
+        report.unknown = names.into_iter()
+            .filter(|n| !contract.exports.iter().any(|e| e.name() == n))
+            .filter(|n| !EXPORT_GROUPS.iter().any(|(_, exports)| exports.contains(&n.as_str())))
+            .collect();
+
+        let logic = LogicModule::new(name.to_string(), 0, module);
+        let instance = match env.instantiate_module(&logic, Point { x: 0.0, y: 0.0 }) {
+            Ok(instance) => instance,
+            Err(e) => {
+                report.instantiation = Some(e.to_string());
+                return Ok(report);
+            },
+        };
+
+        let store = env.store_mut();
+        report.memory = instance.instance.exports.get_memory("memory").ok().map(|m| m.view(&*store).data_size());
+        report.size = (instance.width(store), instance.height(store));
+        report.pins = (instance.inputs_nr(store), instance.outputs_nr(store));
+        report.input_pos = (0..report.pins.0).map(|i| instance.input_pos(store, i)).collect();
+        report.output_pos = (0..report.pins.1).map(|i| instance.output_pos(store, i)).collect();
+        report.widths = (
+            (0..report.pins.0).map(|i| instance.input_width(store, i)).collect(),
+            (0..report.pins.1).map(|i| instance.output_width(store, i)).collect(),
+        );
+        report.delay = instance.delay(store);
+        report.period = instance.period(store);
+        report.bits = instance.bits(store);
+        report.words = instance.mem_size(store).zip(instance.data_bits(store));
+        report.drawing = env.record_drawing(&instance);
+        Ok(report)
+    }
+
+    /// Check if the module obeys the contract and can be instantiated.
+    pub fn passed(&self) -> bool {
+        self.violations.is_empty() && self.instantiation.is_none()
+    }
+
+    /// Render the recorded drawing as SVG image of the reported size.
+    pub fn preview_svg(&self) -> String {
+        let rgb = |c: &[f32; 3]| {
+            let [r, g, b] = c.map(|v| (v.clamp(0.0, 1.0) * 255.0).round() as u8);
+            format!("rgb({},{},{})", r, g, b)
+        };
+
+        let mut svg = format!(
+            "<svg xmlns=\"http://www.w3.org/2000/svg\" width=\"{w}\" height=\"{h}\" viewBox=\"0 0 {w} {h}\">\n",
+            w = self.size.0, h = self.size.1
+        );
+        for call in &self.drawing {
+            svg += &match call {
+                DrawCall::Rectangle { x, y, w, h, color } => format!(
+                    "<rect x=\"{}\" y=\"{}\" width=\"{}\" height=\"{}\" fill=\"{}\"/>\n", x, y, w, h, rgb(color)
+                ),
+                DrawCall::Circle { x, y, radius, color } => format!(
+                    "<circle cx=\"{}\" cy=\"{}\" r=\"{}\" fill=\"{}\"/>\n", x, y, radius, rgb(color)
+                ),
+                DrawCall::CircleLines { x, y, radius, thickness, color } => format!(
+                    "<circle cx=\"{}\" cy=\"{}\" r=\"{}\" fill=\"none\" stroke=\"{}\" stroke-width=\"{}\"/>\n",
+                    x, y, radius, rgb(color), thickness
+                ),
+                DrawCall::Line { x1, y1, x2, y2, thickness, color } => format!(
+                    "<line x1=\"{}\" y1=\"{}\" x2=\"{}\" y2=\"{}\" stroke=\"{}\" stroke-width=\"{}\"/>\n",
+                    x1, y1, x2, y2, rgb(color), thickness
+                ),
+            };
+        }
+        svg += "</svg>\n";
+        svg
+    }
+}
+
+impl fmt::Display for ModuleReport {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        writeln!(f, "module `{}`: {}", self.name, if self.passed() { "ok" } else { "FAILED" })?;
+        for v in &self.violations {
+            writeln!(f, "    violation: {}", v)?;
+        }
+        if let Some(e) = &self.instantiation {
+            writeln!(f, "    can't be instantiated: {}", e)?;
+        }
+
+        writeln!(f, "  size: {} x {}", self.size.0, self.size.1)?;
+        writeln!(f, "  pins: {} inputs, {} outputs", self.pins.0, self.pins.1)?;
+        let pins = |pos: &[Point], widths: &[usize]| -> String {
+            pos.iter().zip(widths)
+                .map(|(p, w)| format!("({}, {}) x{}", p.x, p.y, w))
+                .collect::<Vec<_>>()
+                .join(", ")
+        };
+        writeln!(f, "    inputs: {}", pins(&self.input_pos, &self.widths.0))?;
+        writeln!(f, "    outputs: {}", pins(&self.output_pos, &self.widths.1))?;
+        writeln!(f, "  delay: {}", self.delay)?;
+        if let Some(period) = self.period {
+            writeln!(f, "  period: {}", period)?;
+        }
+        if let Some(bits) = self.bits {
+            writeln!(f, "  bits: {}", bits)?;
+        }
+        if let Some((words, bits)) = self.words {
+            writeln!(f, "  words: {} x {} bits", words, bits)?;
+        }
+        match self.memory {
+            Some(bytes) => writeln!(f, "  memory: {} bytes", bytes)?,
+            None => writeln!(f, "  memory: none exported")?,
+        }
+        writeln!(f, "  draw: {} calls", self.drawing.len())?;
+
+        for (group, found) in &self.optional {
+            let found = if found.is_empty() { "-".to_string() } else { found.join(", ") };
+            writeln!(f, "  {}: {}", group, found)?;
+        }
+        if !self.unknown.is_empty() {
+            writeln!(f, "  unknown exports: {}", self.unknown.join(", "))?;
+        }
+
+        writeln!(f, "  exports:")?;
+        for e in &self.exports {
+            writeln!(f, "    {}", e)?;
+        }
+        write!(f, "  imports:")?;
+        for i in &self.imports {
+            write!(f, "\n    {}", i)?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::bench::headless_env;
+
+    #[test]
+    fn module_report_test() {
+        let module_wat = r#"
+            (module
+                (import "env" "draw_rectangle" (func $dr (param f32 f32 f32 f32 f32 f32 f32)))
+                (memory (export "memory") 1)
+                (func (export "width") (result f32) f32.const 80)
+                (func (export "height") (result f32) f32.const 60)
+                (func (export "draw") (param $x f32) (param $y f32) (param $r f32)
+                    (call $dr (local.get $x) (local.get $y) (f32.const 80) (f32.const 60)
+                        (f32.const 1) (f32.const 0) (f32.const 0))
+                )
+                (func (export "get_inputs_nr") (result i32) i32.const 2)
+                (func (export "get_output") (param $i i32) (result i64) i64.const 0)
+                (func (export "on_press") (param $x f32) (param $y f32))
+                (func (export "secret"))
+            )
+        "#;
+
+        let mut env = headless_env();
+        let report = ModuleReport::generate(&mut env, "test", module_wat.as_bytes()).unwrap();
+
+        assert!(!report.passed());
+        assert_eq!(1, report.violations.len());
+        assert_eq!(None, report.instantiation);
+        assert_eq!(Some(65536), report.memory);
+        assert_eq!((80.0, 60.0), report.size);
+        assert_eq!((2, 0), report.pins);
+        assert_eq!(vec![Point { x: 0.0, y: 20.0 }, Point { x: 0.0, y: 40.0 }], report.input_pos);
+        assert_eq!((vec![1, 1], vec![]), report.widths);
+        assert_eq!(1, report.delay);
+        assert_eq!((None, None, None), (report.period, report.bits, report.words));
+        assert_eq!(("pins", vec!["get_inputs_nr", "get_output"]), report.optional[0]);
+        assert_eq!(("events", vec!["on_press"]), report.optional[3]);
+        assert_eq!(vec!["secret".to_string()], report.unknown);
+        assert_eq!(
+            vec![DrawCall::Rectangle { x: 0.0, y: 0.0, w: 80.0, h: 60.0, color: [1.0, 0.0, 0.0] }],
+            report.drawing
+        );
+        assert!(report.preview_svg().contains("<rect x=\"0\" y=\"0\" width=\"80\" height=\"60\" fill=\"rgb(255,0,0)\"/>"));
+
+        let report = ModuleReport::generate(&mut env, "test", b"(module (import \"env\" \"nope\" (func)))").unwrap();
+        assert!(report.instantiation.is_some());
+        assert!(ModuleReport::generate(&mut env, "test", b"(module").is_err());
+        // The inspected instances aren't part of the environment.
+        assert!(env.instances().is_empty());
+    }
+}
@@ -0,0 +1,473 @@
+use wasmer::AsStoreMut;
+use uuid::Uuid;
+use std::collections::{HashMap, BTreeMap};
+use crate::core::module::LogicInstance;
+use crate::contract::{State, Bits};
+use crate::core::history::History;
+
+/// Reference to a single pin of a [`LogicInstance`].
+///
+/// Whether `pin` refers to an input or an output depends
+/// on the context the reference is used in.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct PinRef {
+    /// The id of the instance the pin belongs to.
+    pub instance: Uuid,
+    /// The index of the pin.
+    pub pin: usize,
+}
+
+impl PinRef {
+    /// Create a new pin reference.
+    pub fn new(instance: Uuid, pin: usize) -> Self {
+        Self { instance, pin }
+    }
+}
+
+/// A pin with a human readable name, e.g. to label
+/// columns of a table or traces of a waveform.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Probe {
+    /// The name of the probe.
+    pub name: String,
+    /// The pin the probe is attached to.
+    pub pin: PinRef,
+}
+
+impl Probe {
+    /// Create a new probe attached to `pin`.
+    pub fn new(name: &str, pin: PinRef) -> Self {
+        Self { name: name.to_string(), pin }
+    }
+}
+
+/// A connection from an output pin to an input pin.
+///
+/// Wires wider than one bit form a bus.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Wire {
+    /// The output pin driving the wire.
+    pub from: PinRef,
+    /// The input pin driven by the wire.
+    pub to: PinRef,
+    /// The number of bits carried by the wire.
+    pub width: usize,
+}
+
+/// The [`Simulator`] propagates the states of all output
+/// pins along the wires to the connected input pins.
+///
+/// Every step advances the simulation time by one unit. All
+/// instances are told the current time first, then all wires are
+/// evaluated, where inputs driven by several outputs see the
+/// resolution of all of them (see [`State::resolve`]). Afterwards
+/// rising edges (`Low` to `High`) are reported and finally all
+/// instances are updated. This is repeated until no output changes
+/// anymore, i.e. the circuit has settled.
+///
+/// Changed outputs don't become visible immediately. Instead they
+/// are put into a time-ordered event queue and applied after the
+/// propagation delay of their instance, so glitches and races show
+/// up in the recorded history.
+#[derive(Debug, Clone, Default)]
+pub struct Simulator {
+    /// All connections between instances.
+    wires: Vec<Wire>,
+    /// Output pins whose state is dictated by the host.
+    forced: HashMap<PinRef, Bits>,
+    /// The output states sampled after the last step.
+    outputs: HashMap<PinRef, Bits>,
+    /// The states last applied to the connected input pins.
+    inputs: HashMap<PinRef, Bits>,
+    /// Output changes waiting for their propagation delay to pass,
+    /// by the time they become visible.
+    events: BTreeMap<u64, Vec<(PinRef, Bits)>>,
+    /// The last value scheduled for every output pin.
+    scheduled: HashMap<PinRef, Bits>,
+    /// Propagation delays overriding those declared by the modules.
+    delays: HashMap<Uuid, u64>,
+    /// Number of steps simulated so far.
+    steps: u64,
+    /// The recorded states of all probed pins.
+    history: History,
+}
+
+impl Simulator {
+    /// Create a new simulator without any wires.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Get all wires.
+    pub fn wires(&self) -> &[Wire] {
+        &self.wires
+    }
+
+    /// Connect the output pin `from` to the input pin `to` with
+    /// a wire of `width` bits.
+    pub fn connect(&mut self, from: PinRef, to: PinRef, width: usize) {
+        self.wires.push(Wire { from, to, width });
+    }
+
+    /// Remove all wires matching `f`, e.g. because their pins vanished.
+    pub fn disconnect(&mut self, f: impl Fn(&Wire) -> bool) {
+        self.wires.retain(|w| !f(w));
+    }
+
+    /// Remove all wires from or to the given instance.
+    pub fn disconnect_instance(&mut self, id: Uuid) {
+        self.wires.retain(|w| w.from.instance != id && w.to.instance != id);
+        self.forced.retain(|p, _| p.instance != id);
+        self.history.remove_instance(id);
+        self.outputs.retain(|p, _| p.instance != id);
+        self.inputs.retain(|p, _| p.instance != id);
+        self.scheduled.retain(|p, _| p.instance != id);
+        self.delays.remove(&id);
+        for events in self.events.values_mut() {
+            events.retain(|(p, _)| p.instance != id);
+        }
+    }
+
+    /// Override the propagation delay of the instance `id`, or
+    /// use the one declared by its module again if `None`.
+    ///
+    /// Delays are at least one time unit.
+    pub fn set_delay(&mut self, id: Uuid, delay: Option<u64>) {
+        match delay {
+            Some(d) => self.delays.insert(id, d.max(1)),
+            None => self.delays.remove(&id),
+        };
+    }
+
+    /// Get the propagation delay of `instance`.
+    pub fn delay(&self, store: &mut impl AsStoreMut, instance: &LogicInstance) -> u64 {
+        match self.delays.get(&instance.id()) {
+            Some(d) => *d,
+            None => instance.delay(store),
+        }
+    }
+
+    /// Get the time at which the next pending output change
+    /// becomes visible, if any.
+    pub fn next_event(&self) -> Option<u64> {
+        self.events.keys().next().copied()
+    }
+
+    /// Check if any output changes are still waiting for their
+    /// propagation delay to pass.
+    pub fn pending(&self) -> bool {
+        !self.events.is_empty()
+    }
+
+    /// Override the state of the output pin `pin`.
+    pub fn force(&mut self, pin: PinRef, state: State) {
+        self.forced.insert(pin, Bits::from(state));
+    }
+
+    /// Override the value of the output bus `pin`.
+    pub fn force_bits(&mut self, pin: PinRef, bits: Bits) {
+        self.forced.insert(pin, bits);
+    }
+
+    /// Give control of the output pin `pin` back to its instance.
+    pub fn release(&mut self, pin: PinRef) {
+        self.forced.remove(&pin);
+    }
+
+    /// Get the state of the output pin `pin` after the last step.
+    ///
+    /// For buses this is the least significant bit, see
+    /// [`Simulator::output_bits`] for the whole value.
+    pub fn output(&self, pin: &PinRef) -> State {
+        self.output_bits(pin).get(0)
+    }
+
+    /// Get the value of the output pin `pin` after the last step.
+    pub fn output_bits(&self, pin: &PinRef) -> Bits {
+        match self.forced.get(pin) {
+            Some(bits) => *bits,
+            None => self.outputs.get(pin).copied().unwrap_or(Bits::undefined(1)),
+        }
+    }
+
+    /// Get the resolved value of all drivers of the input pin `pin`
+    /// as applied in the last step.
+    pub fn net(&self, pin: &PinRef) -> Bits {
+        self.inputs.get(pin).copied().unwrap_or(Bits::undefined(1))
+    }
+
+    /// Get all input pins whose drivers were in conflict in the last step.
+    pub fn conflicts(&self) -> Vec<PinRef> {
+        self.inputs.iter().filter(|(_, b)| b.has_conflict()).map(|(p, _)| *p).collect()
+    }
+
+    /// Get the recorded states of all probed pins.
+    pub fn history(&self) -> &History {
+        &self.history
+    }
+
+    /// Get a mutable reference to the recorded history, e.g. to add probes.
+    pub fn history_mut(&mut self) -> &mut History {
+        &mut self.history
+    }
+
+    /// Get the number of steps simulated so far.
+    pub fn steps(&self) -> u64 {
+        self.steps
+    }
+
+    /// Simulate a single step.
+    ///
+    /// Returns `true` if at least one output changed its state
+    /// or changes are still pending.
+    pub fn step(
+        &mut self,
+        store: &mut impl AsStoreMut,
+        instances: &HashMap<Uuid, LogicInstance>,
+    ) -> bool {
+        for instance in instances.values() {
+            instance.tick(store, self.steps);
+        }
+
+        // Several wires may drive the same input, resolve their values first.
+        let mut nets: HashMap<PinRef, Bits> = HashMap::new();
+        for wire in self.wires.iter() {
+            let bits = self.output_bits(&wire.from);
+            nets.entry(wire.to).and_modify(|net| *net = net.resolve(&bits)).or_insert(bits);
+        }
+
+        let mut edges = Vec::new();
+        for (pin, bits) in nets {
+            if let Some(instance) = instances.get(&pin.instance) {
+                instance.set_input_bits(store, pin.pin, bits);
+                let last = self.inputs.insert(pin, bits);
+                if bits.width() == 1 && last == Some(Bits::from(State::Low)) && bits.get(0) == State::High {
+                    edges.push(pin);
+                }
+            }
+        }
+
+        // Edges are reported after all inputs have been applied, so
+        // edge triggered instances see the data present at the edge.
+        for pin in edges {
+            instances[&pin.instance].rising_edge(store, pin.pin);
+        }
+
+        for instance in instances.values() {
+            instance.update(store);
+        }
+
+        for (id, instance) in instances.iter() {
+            for i in 0..instance.outputs_nr(store) {
+                let pin = PinRef::new(*id, i);
+                let bits = instance.output_bits(store, i);
+                if self.scheduled.insert(pin, bits) != Some(bits) {
+                    let at = self.steps + self.delay(store, instance);
+                    self.events.entry(at).or_default().push((pin, bits));
+                }
+            }
+        }
+
+        self.steps += 1;
+
+        let mut changed = false;
+        while let Some(entry) = self.events.first_entry() {
+            if *entry.key() > self.steps {
+                break;
+            }
+            for (pin, bits) in entry.remove() {
+                if self.outputs.insert(pin, bits) != Some(bits) {
+                    changed = true;
+                }
+            }
+        }
+
+        let (forced, outputs) = (&self.forced, &self.outputs);
+        self.history.record(self.steps, |pin| {
+            forced.get(pin).or_else(|| outputs.get(pin)).copied().unwrap_or(Bits::undefined(1))
+        });
+
+        changed || self.pending()
+    }
+
+    /// Simulate until no output changes anymore but at most
+    /// `max_steps` steps.
+    ///
+    /// Returns `false` if the circuit didn't settle, e.g. because
+    /// it oscillates.
+    pub fn settle(
+        &mut self,
+        store: &mut impl AsStoreMut,
+        instances: &HashMap<Uuid, LogicInstance>,
+        max_steps: usize,
+    ) -> bool {
+        for _ in 0..max_steps {
+            if !self.step(store, instances) {
+                return true;
+            }
+        }
+        false
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::analysis::truth_table::tests::test_env;
+    use crate::core::module::ModuleEnv;
+    use crate::misc::Point;
+
+    #[test]
+    fn rising_edge_test() {
+        // Counts the rising edges on input 0, the output is High
+        // after exactly two edges.
+        let module_wat = r#"
+            (module
+                (global $edges (mut i32) (i32.const 0))
+                (func (export "get_inputs_nr") (result i32) i32.const 1)
+                (func (export "get_outputs_nr") (result i32) i32.const 1)
+                (func (export "set_input") (param $i i32) (param $s i32))
+                (func (export "rising_edge") (param $i i32)
+                    (global.set $edges (i32.add (global.get $edges) (i32.const 1)))
+                )
+                (func (export "get_output") (param $i i32) (result i32)
+                    (select (i32.const 2) (i32.const 1) (i32.eq (global.get $edges) (i32.const 2)))
+                )
+            )
+        "#;
+
+        let mut env = test_env();
+        env.add_module_raw("Test", "edges", module_wat.as_bytes()).unwrap();
+        let clk = env.instantiate("Test", "source", Point { x: 0.0, y: 0.0 }).unwrap();
+        let ff = env.instantiate("Test", "edges", Point { x: 100.0, y: 0.0 }).unwrap();
+        assert!(env.connect(PinRef::new(clk, 0), PinRef::new(ff, 0)));
+
+        // Undefined to High is no edge, neither are repeated levels.
+        for s in [State::High, State::High, State::Low, State::High, State::High, State::Low, State::High] {
+            env.force(PinRef::new(clk, 0), s);
+            env.step();
+        }
+        assert_eq!(State::High, env.output(&PinRef::new(ff, 0)));
+
+        env.force(PinRef::new(clk, 0), State::Low);
+        env.step();
+        env.force(PinRef::new(clk, 0), State::High);
+        env.step();
+        assert_eq!(State::Low, env.output(&PinRef::new(ff, 0)));
+    }
+
+    #[test]
+    fn bus_test() {
+        // Passes an 8-bit bus from its input to its output.
+        let module_wat = r#"
+            (module
+                (global $v (mut i64) (i64.const 0))
+                (func (export "get_inputs_nr") (result i32) i32.const 1)
+                (func (export "get_outputs_nr") (result i32) i32.const 1)
+                (func (export "get_input_width") (param $i i32) (result i32) i32.const 8)
+                (func (export "get_output_width") (param $i i32) (result i32) i32.const 8)
+                (func (export "set_input_bits") (param $i i32) (param $v i64) (global.set $v (local.get $v)))
+                (func (export "get_output_bits") (param $i i32) (result i64) global.get $v)
+            )
+        "#;
+
+        let mut env = test_env();
+        env.add_module_raw("Test", "bus", module_wat.as_bytes()).unwrap();
+        let src = env.instantiate("Test", "source", Point { x: 0.0, y: 0.0 }).unwrap();
+        let a = env.instantiate("Test", "bus", Point { x: 100.0, y: 0.0 }).unwrap();
+        let b = env.instantiate("Test", "bus", Point { x: 200.0, y: 0.0 }).unwrap();
+
+        // A single bit can't drive a bus.
+        assert!(!env.connect(PinRef::new(src, 0), PinRef::new(a, 0)));
+        assert!(env.connect(PinRef::new(a, 0), PinRef::new(b, 0)));
+        assert_eq!(8, env.wires()[0].width);
+
+        let mut bits = Bits::from_u32(8, 0xa5);
+        bits.set(1, State::Undefined);
+        env.force_bits(PinRef::new(a, 0), bits);
+        env.step();
+        assert_eq!(bits, env.output_bits(&PinRef::new(b, 0)));
+        assert_eq!("101001U1", env.output_bits(&PinRef::new(b, 0)).to_string());
+    }
+
+    #[test]
+    fn resolve_drivers_test() {
+        let mut env = test_env();
+        let a = env.instantiate("Test", "source", Point { x: 0.0, y: 0.0 }).unwrap();
+        let b = env.instantiate("Test", "source", Point { x: 0.0, y: 50.0 }).unwrap();
+        let q = env.instantiate("Test", "and", Point { x: 100.0, y: 25.0 }).unwrap();
+        assert!(env.connect(PinRef::new(a, 0), PinRef::new(q, 0)));
+        assert!(env.connect(PinRef::new(b, 0), PinRef::new(q, 0)));
+
+        env.force(PinRef::new(a, 0), State::HighImpedance);
+        env.force(PinRef::new(b, 0), State::High);
+        env.step();
+        assert_eq!(Bits::from(State::High), env.simulator().net(&PinRef::new(q, 0)));
+        assert!(env.simulator().conflicts().is_empty());
+
+        env.force(PinRef::new(a, 0), State::Low);
+        env.step();
+        assert_eq!(Bits::from(State::Conflict), env.simulator().net(&PinRef::new(q, 0)));
+        assert_eq!(vec![PinRef::new(q, 0)], env.simulator().conflicts());
+    }
+
+    #[test]
+    fn glitch_test() {
+        // An inverter with a propagation delay of three time units.
+        let module_wat = r#"
+            (module
+                (global $a (mut i32) (i32.const 0))
+                (global $q (mut i32) (i32.const 0))
+                (func (export "get_inputs_nr") (result i32) i32.const 1)
+                (func (export "get_outputs_nr") (result i32) i32.const 1)
+                (func (export "get_delay") (result i32) i32.const 3)
+                (func (export "set_input") (param $i i32) (param $s i32) (global.set $a (local.get $s)))
+                (func (export "get_output") (param $i i32) (result i32) global.get $q)
+                (func (export "update")
+                    (global.set $q
+                        (select
+                            (i32.sub (i32.const 3) (global.get $a))
+                            (i32.const 0)
+                            (i32.or (i32.eq (global.get $a) (i32.const 1)) (i32.eq (global.get $a) (i32.const 2)))
+                        )
+                    )
+                )
+            )
+        "#;
+
+        // Q = A & !A is Low once settled, but the slow inverter
+        // lets a rising edge of A through for a short time.
+        let mut env = test_env();
+        env.add_module_raw("Test", "not", module_wat.as_bytes()).unwrap();
+        let a = env.instantiate("Test", "source", Point { x: 0.0, y: 0.0 }).unwrap();
+        let n = env.instantiate("Test", "not", Point { x: 100.0, y: 50.0 }).unwrap();
+        let q = env.instantiate("Test", "and", Point { x: 200.0, y: 0.0 }).unwrap();
+        assert!(env.connect(PinRef::new(a, 0), PinRef::new(q, 0)));
+        assert!(env.connect(PinRef::new(a, 0), PinRef::new(n, 0)));
+        assert!(env.connect(PinRef::new(n, 0), PinRef::new(q, 1)));
+        env.add_probe(Probe::new("Q", PinRef::new(q, 0)));
+
+        let glitch = |env: &mut ModuleEnv| {
+            env.force(PinRef::new(a, 0), State::Low);
+            assert!(env.settle(64));
+            env.force(PinRef::new(a, 0), State::High);
+            let mut high = 0;
+            for _ in 0..8 {
+                env.step();
+                high += (env.output(&PinRef::new(q, 0)) == State::High) as usize;
+            }
+            assert_eq!(State::Low, env.output(&PinRef::new(q, 0)));
+            high
+        };
+
+        assert_eq!(Some(3), env.delay(n));
+        assert_eq!(3, glitch(&mut env));
+        let changes: Vec<State> = env.history().traces()[0].changes.iter().map(|(_, b)| b.get(0)).collect();
+        assert_eq!(&[State::High, State::Low], &changes[changes.len() - 2..]);
+
+        assert!(env.set_delay(n, Some(1)));
+        assert_eq!(1, glitch(&mut env));
+        assert!(env.set_delay(n, None));
+        assert_eq!(3, glitch(&mut env));
+    }
+}
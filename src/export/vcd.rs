@@ -0,0 +1,169 @@
+use std::io::{self, Write};
+use crate::core::history::History;
+use crate::contract::{State, Bits};
+
+/// The time unit of a single simulation step.
+pub const DEFAULT_TIMESCALE: &str = "1ns";
+
+/// Get the VCD value character of a state.
+pub fn vcd_value(state: State) -> char {
+    match state {
+        State::Low => '0',
+        State::High => '1',
+        State::Undefined | State::Conflict => 'x',
+        State::HighImpedance => 'z',
+    }
+}
+
+/// Get the VCD value change of a `width` bit variable, e.g. `1` for a
+/// single bit and `b10zx` for a bus, the most significant bit first.
+pub fn vcd_bits(bits: &Bits, width: usize) -> String {
+    if width == 1 {
+        return vcd_value(bits.get(0)).to_string();
+    }
+
+    let mut s = "b".to_string();
+    s.extend((0..width).rev().map(|i| vcd_value(bits.get(i))));
+    s + " "
+}
+
+/// Get the short identifier code of the `i`-th variable.
+///
+/// Identifiers consist of the printable ASCII characters `!` to `~`.
+fn identifier(mut i: usize) -> String {
+    let mut s = String::new();
+    loop {
+        s.push((b'!' + (i % 94) as u8) as char);
+        i /= 94;
+        if i == 0 {
+            break;
+        }
+        i -= 1;
+    }
+    s
+}
+
+/// Write the recorded `history` as Value Change Dump (IEEE 1364), e.g.
+/// to inspect it with GTKWave.
+///
+/// Every probe becomes a wire of the width of the probed pin within
+/// the `megs` scope. [`State::Undefined`] is written as `x`.
+pub fn write_vcd(history: &History, timescale: &str, w: &mut impl Write) -> io::Result<()> {
+    writeln!(w, "$version MEGS {} $end", env!("CARGO_PKG_VERSION"))?;
+    writeln!(w, "$timescale {} $end", timescale)?;
+    writeln!(w, "$scope module megs $end")?;
+    for (i, trace) in history.traces().iter().enumerate() {
+        let name: String = trace.probe.name
+            .chars()
+            .map(|c| if c.is_whitespace() { '_' } else { c })
+            .collect();
+        writeln!(w, "$var wire {} {} {} $end", trace.width, identifier(i), name)?;
+    }
+    writeln!(w, "$upscope $end")?;
+    writeln!(w, "$enddefinitions $end")?;
+
+    // Initial values of all variables.
+    writeln!(w, "#0")?;
+    writeln!(w, "$dumpvars")?;
+    for (i, trace) in history.traces().iter().enumerate() {
+        writeln!(w, "{}{}", vcd_bits(&trace.bits_at(0), trace.width), identifier(i))?;
+    }
+    writeln!(w, "$end")?;
+
+    // All further changes ordered by time.
+    let mut changes: Vec<(u64, usize, String)> = history.traces()
+        .iter()
+        .enumerate()
+        .flat_map(|(i, trace)| trace.changes.iter().map(move |(t, b)| (*t, i, vcd_bits(b, trace.width))))
+        .filter(|(t, _, _)| *t > 0)
+        .collect();
+    changes.sort_by_key(|(t, i, _)| (*t, *i));
+
+    let mut time = 0;
+    for (t, i, value) in changes {
+        if t != time {
+            writeln!(w, "#{}", t)?;
+            time = t;
+        }
+        writeln!(w, "{}{}", value, identifier(i))?;
+    }
+
+    if history.end() > time {
+        writeln!(w, "#{}", history.end())?;
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::core::sim::{Probe, PinRef};
+    use crate::analysis::truth_table::tests::test_env;
+    use crate::misc::Point;
+
+    #[test]
+    fn identifier_test() {
+        assert_eq!("!", identifier(0));
+        assert_eq!("~", identifier(93));
+        assert_eq!("!!", identifier(94));
+        assert_eq!("\"!", identifier(95));
+    }
+
+    #[test]
+    fn vcd_bits_test() {
+        let mut bits = Bits::from_u32(4, 0b1001);
+        bits.set(1, State::HighImpedance);
+        assert_eq!("b10z1 ", vcd_bits(&bits, 4));
+        // Bits beyond the value are unknown.
+        assert_eq!("bxx10z1 ", vcd_bits(&bits, 6));
+        assert_eq!("1", vcd_bits(&bits, 1));
+    }
+
+    #[test]
+    fn write_vcd_test() {
+        let mut env = test_env();
+        let a = env.instantiate("Test", "source", Point { x: 0.0, y: 0.0 }).unwrap();
+        let b = env.instantiate("Test", "source", Point { x: 0.0, y: 50.0 }).unwrap();
+        let q = env.instantiate("Test", "and", Point { x: 100.0, y: 25.0 }).unwrap();
+        env.connect(PinRef::new(a, 0), PinRef::new(q, 0));
+        env.connect(PinRef::new(b, 0), PinRef::new(q, 1));
+        env.add_probe(Probe::new("A", PinRef::new(a, 0)));
+        env.add_probe(Probe::new("my Q", PinRef::new(q, 0)));
+
+        env.force(PinRef::new(a, 0), State::High);
+        env.force(PinRef::new(b, 0), State::High);
+        env.step();
+        env.step();
+        env.force(PinRef::new(a, 0), State::Low);
+        env.step();
+        env.step();
+
+        let mut out = Vec::new();
+        write_vcd(env.history(), DEFAULT_TIMESCALE, &mut out).unwrap();
+        assert_eq!(
+            format!(
+                "$version MEGS {} $end\n\
+                 $timescale 1ns $end\n\
+                 $scope module megs $end\n\
+                 $var wire 1 ! A $end\n\
+                 $var wire 1 \" my_Q $end\n\
+                 $upscope $end\n\
+                 $enddefinitions $end\n\
+                 #0\n\
+                 $dumpvars\n\
+                 x!\n\
+                 x\"\n\
+                 $end\n\
+                 #1\n\
+                 1!\n\
+                 1\"\n\
+                 #3\n\
+                 0!\n\
+                 0\"\n\
+                 #4\n",
+                env!("CARGO_PKG_VERSION")
+            ),
+            String::from_utf8(out).unwrap()
+        );
+    }
+}
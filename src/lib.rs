@@ -1,3 +1,9 @@
 pub mod core;
 pub mod misc;
 pub mod contract;
+pub mod wasi;
+pub mod analysis;
+pub mod synth;
+pub mod export;
+pub mod bench;
+pub mod ui;
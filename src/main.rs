@@ -3,7 +3,53 @@ use macroquad::ui::root_ui;
 use megs::core::module::*;
 use megs::misc::*;
 use megs::core::contract::*;
-use wasmer::{Store, Function, imports, Type, ImportType, ExportType, FunctionType, ExternType};
+use megs::ui::equations::{self, EquationPanel};
+use megs::ui::synth::{self, SynthPanel};
+use megs::ui::waveform::WaveformView;
+use megs::ui::memory::{self, MemoryPanel};
+use megs::ui::controls::{self, ControlPanel};
+use megs::ui::debug::{self, DebugPanel};
+use megs::contract::FileEnv;
+use wasmer::{Store, FunctionEnv};
+
+/// The areas of all panels.
+///
+/// The panels are stacked in a column at either side of the screen, so
+/// they never overlap: the debugger above the synthesis panel on the
+/// left, the simulation controls, the equations and the memory (if
+/// shown) on the right. The timing diagram sits on top of the circuit
+/// in between.
+struct Layout {
+    controls: Rect,
+    equations: Rect,
+    memory: Rect,
+    debug: Rect,
+    synth: Rect,
+    waveform: Rect,
+}
+
+impl Layout {
+    fn new(memory: bool, waveform_height: f32) -> Self {
+        let (w, h) = (screen_width(), screen_height());
+
+        let left = debug::PANEL_SIZE.0.max(synth::PANEL_SIZE.0);
+        let synth_h = synth::PANEL_SIZE.1.min(h / 2.0);
+
+        let right = controls::PANEL_SIZE.0.max(equations::PANEL_WIDTH).max(memory::PANEL_SIZE.0);
+        let controls_h = controls::PANEL_SIZE.1.min(h / 3.0);
+        let memory_h = if memory { memory::PANEL_SIZE.1.min((h - controls_h) / 2.0) } else { 0.0 };
+        let equations_h = h - controls_h - memory_h;
+
+        Self {
+            controls: Rect::new(w - right, 0.0, right, controls_h),
+            equations: Rect::new(w - right, controls_h, right, equations_h),
+            memory: Rect::new(w - right, h - memory_h, right, memory_h),
+            debug: Rect::new(0.0, 0.0, left, h - synth_h),
+            synth: Rect::new(0.0, h - synth_h, left, synth_h),
+            waveform: Rect::new(left, 0.0, (w - left - right).max(0.0), waveform_height.min(h / 2.0)),
+        }
+    }
+}
 
 #[macroquad::main("MEGS")]
 async fn main() {
@@ -19,30 +65,20 @@ async fn main() {
     "#;
     
     let mut store = Store::default();
-    let imports = imports! {
-        "env" => {
-            "draw_rectangle" => Function::new_native(&mut store, megs::contract::draw_rectangle),
-            "draw_circle" => Function::new_native(&mut store, megs::contract::draw_circle),
-            "draw_circle_lines" => Function::new_native(&mut store, megs::contract::draw_circle_lines),
-            "draw_line" => Function::new_native(&mut store, megs::contract::draw_line),
-        },
-    };
-    let contract = Contract {
-        exports: vec![
-            ExportType::new("width", ExternType::Function(FunctionType::new([], [Type::F32]))),
-            ExportType::new("height", ExternType::Function(FunctionType::new([], [Type::F32]))),
-            ExportType::new("draw", ExternType::Function(FunctionType::new([Type::F32, Type::F32, Type::F32], []))),
-        ],
-        imports: inobj_types(&imports, &store),
-    };
+    let files = FunctionEnv::new(&mut store, FileEnv::default());
+    let (imports, contract) = megs_contract(&mut store, &files);
 
     let mut env = ModuleEnv::new(store, imports, contract);
+    env.set_file_env(files);
+    env.enable_wasi();
+    if !std::env::args().any(|a| a == "--no-cache") {
+        env.enable_cache();
+    }
     env.add_category("Gates".to_string());
     //env.add_module_raw("Gates", "AND", module_wat.as_bytes());
-    match env.add_module(&std::path::Path::new("assets/modules/Gates/and.wasm")) {
-        Err(e) => println!("{}", e),
-        _ => {}
-    };
+    for e in env.add_modules(std::path::Path::new("assets/modules")) {
+        println!("{}", e);
+    }
     env.instantiate("Gates", "and", Point { x: 0.0, y: 0.0 });
     env.instantiate("Gates", "and", Point { x: 50.0, y: 30.0 });
     env.instantiate("Gates", "and", Point { x: -15.0, y: 200.0 });
@@ -51,10 +87,45 @@ async fn main() {
     println!("{}", env.categories()["Gates"].modules().len());
     println!("{}", env.instances().len());
 
+    let mut equation_panel = EquationPanel::new();
+    let mut synth_panel = SynthPanel::new();
+    let mut waveform = WaveformView::new();
+    let mut memory_panel = MemoryPanel::new();
+    let mut control_panel = ControlPanel::new();
+    let mut debug_panel = DebugPanel::new();
+
     loop {
         clear_background(RED);
 
+        let (mx, my) = mouse_position();
+        if is_mouse_button_pressed(MouseButton::Left) {
+            if let Some(id) = env.on_press(Point { x: mx, y: my }) {
+                if env.memory_size(id).is_some() {
+                    memory_panel.select(Some(id));
+                }
+            }
+        }
+        if is_mouse_button_released(MouseButton::Left) {
+            env.on_release(Point { x: mx, y: my });
+        }
+        // Right click an output to show it in the timing diagram,
+        // or to hide it again.
+        if is_mouse_button_pressed(MouseButton::Right) {
+            if let Some(pin) = env.output_at(&Point { x: mx, y: my }, PIN_RADIUS) {
+                env.toggle_probe(pin);
+            }
+        }
+
         env.on_tick();
+        let layout = Layout::new(memory_panel.is_shown(), WaveformView::height(env.history()));
+        equation_panel.draw(&mut env, layout.equations);
+        synth_panel.draw(&mut env, layout.synth, Point { x: layout.synth.w + 100.0, y: 300.0 });
+        memory_panel.draw(&mut env, layout.memory);
+        control_panel.draw(&mut env, layout.controls);
+        debug_panel.draw(&mut env, layout.debug);
+        if !env.history().traces().is_empty() {
+            waveform.draw(env.history(), layout.waveform);
+        }
         
         /*
         draw_line(40.0, 40.0, 100.0, 200.0, 15.0, BLUE);
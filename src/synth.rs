@@ -0,0 +1,478 @@
+use uuid::Uuid;
+use std::{
+    collections::HashMap,
+    convert::From,
+    error::Error,
+    fmt,
+};
+use crate::core::module::ModuleEnv;
+use crate::core::sim::PinRef;
+use crate::misc::Point;
+use crate::analysis::boolean::{Expr, Equation, ParseError};
+use crate::analysis::truth_table::{TruthTable, AnalysisError};
+
+/// The category the gate modules are taken from.
+pub const GATES: &str = "Gates";
+/// The category the constant modules (`high` and `low`) are taken from.
+pub const CONSTANTS: &str = "Input Controlls";
+/// Horizontal distance between two levels of gates.
+pub const COLUMN_WIDTH: f32 = 140.0;
+/// Vertical distance between two gates of the same level.
+pub const ROW_HEIGHT: f32 = 90.0;
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum SynthError {
+    /// A required module isn't loaded, given as `(category, name)`.
+    MissingModule(String, String),
+    /// Creating or configuring an instance failed, given as `(category, name)`.
+    InstantiateErr(String, String),
+    ParseErr(ParseError),
+    TableErr(AnalysisError),
+}
+
+impl From<ParseError> for SynthError {
+    fn from(e: ParseError) -> Self {
+        Self::ParseErr(e)
+    }
+}
+
+impl From<AnalysisError> for SynthError {
+    fn from(e: AnalysisError) -> Self {
+        Self::TableErr(e)
+    }
+}
+
+impl fmt::Display for SynthError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            SynthError::MissingModule(category, name) => {
+                write!(f, "the module `{}::{}` is required but isn't loaded", category, name)
+            },
+            SynthError::InstantiateErr(category, name) => {
+                write!(f, "unable to instantiate `{}::{}`", category, name)
+            },
+            SynthError::ParseErr(e) => {
+                write!(f, "{}", &e)
+            },
+            SynthError::TableErr(e) => {
+                write!(f, "{}", &e)
+            },
+        }
+    }
+}
+
+impl Error for SynthError {
+
+}
+
+/// A circuit created by [`synthesize`].
+#[derive(Debug, Clone, PartialEq)]
+pub struct Synthesis {
+    /// The name of the output.
+    pub output: String,
+    /// What drives the output.
+    pub driver: Source,
+    /// The (unconnected) input pins that belong to each variable.
+    pub inputs: HashMap<String, Vec<PinRef>>,
+    /// All created instances.
+    pub instances: Vec<Uuid>,
+}
+
+/// Either a variable or the output of a gate (or constant).
+///
+/// An output driven by a variable, e.g. `Q = A`, is just a wire
+/// from the input of the circuit.
+#[derive(Debug, Clone, PartialEq)]
+pub enum Source {
+    Var(String),
+    Pin(PinRef),
+}
+
+struct Synthesizer<'a> {
+    env: &'a mut ModuleEnv,
+    origin: Point,
+    /// The next free row of every level.
+    rows: HashMap<usize, usize>,
+    inputs: HashMap<String, Vec<PinRef>>,
+    instances: Vec<Uuid>,
+}
+
+impl<'a> Synthesizer<'a> {
+    /// The level of a gate is its distance to the inputs.
+    fn level(e: &Expr) -> usize {
+        match e {
+            Expr::Const(_) | Expr::Var(_) => 0,
+            Expr::Not(e) => 1 + Self::level(e),
+            Expr::And(v) | Expr::Or(v) => 1 + v.iter().map(Self::level).max().unwrap_or(0),
+        }
+    }
+
+    /// Get the category and name of the module of a constant.
+    fn constant(b: bool) -> (&'static str, &'static str) {
+        (CONSTANTS, if b { "high" } else { "low" })
+    }
+
+    /// Find the first module required by `e` that isn't loaded.
+    fn check(env: &ModuleEnv, e: &Expr) -> Result<(), SynthError> {
+        let ((category, kind), operands) = match e {
+            Expr::Const(b) => (Self::constant(*b), &[][..]),
+            Expr::Var(_) => return Ok(()),
+            Expr::Not(e) => ((GATES, "not"), std::slice::from_ref(&**e)),
+            Expr::And(v) => ((GATES, "and"), &v[..]),
+            Expr::Or(v) => ((GATES, "or"), &v[..]),
+        };
+
+        let loaded = match env.categories().get(category) {
+            Some(category) => category.modules().contains_key(kind),
+            None => false,
+        };
+        if !loaded {
+            return Err(SynthError::MissingModule(category.to_string(), kind.to_string()));
+        }
+
+        for e in operands {
+            Self::check(env, e)?;
+        }
+        Ok(())
+    }
+
+    fn place(&mut self, level: usize) -> Point {
+        let row = self.rows.entry(level).or_insert(0);
+        let p = Point {
+            x: self.origin.x + (level.max(1) - 1) as f32 * COLUMN_WIDTH,
+            y: self.origin.y + *row as f32 * ROW_HEIGHT,
+        };
+        *row += 1;
+        p
+    }
+
+    fn build(&mut self, e: &Expr) -> Result<Source, SynthError> {
+        let level = Self::level(e);
+        match e {
+            Expr::Const(b) => {
+                let (category, kind) = Self::constant(*b);
+                let id = self.instantiate(category, kind, level)?;
+                Ok(Source::Pin(PinRef::new(id, 0)))
+            },
+            Expr::Var(name) => Ok(Source::Var(name.clone())),
+            Expr::Not(e) => {
+                let operand = self.build(e)?;
+                self.gate("not", vec![operand], level)
+            },
+            Expr::And(v) => {
+                let operands = v.iter().map(|e| self.build(e)).collect::<Result<_, _>>()?;
+                self.gate("and", operands, level)
+            },
+            Expr::Or(v) => {
+                let operands = v.iter().map(|e| self.build(e)).collect::<Result<_, _>>()?;
+                self.gate("or", operands, level)
+            },
+        }
+    }
+
+    /// Place a new instance of `category::kind` at the given level.
+    fn instantiate(&mut self, category: &str, kind: &str, level: usize) -> Result<Uuid, SynthError> {
+        let p = self.place(level);
+        match self.env.instantiate(category, kind, p) {
+            Some(id) => {
+                self.instances.push(id);
+                Ok(id)
+            },
+            None => Err(SynthError::InstantiateErr(category.to_string(), kind.to_string())),
+        }
+    }
+
+    /// Create a gate of the given kind and connect it to the operands.
+    ///
+    /// If the gate doesn't support as many inputs as there are operands,
+    /// the operands are combined by additional gates of the same kind first.
+    fn gate(&mut self, kind: &str, operands: Vec<Source>, level: usize) -> Result<Source, SynthError> {
+        let id = self.instantiate(GATES, kind, level)?;
+
+        let n = operands.len();
+        let k = self.env.set_inputs_nr(id, n).unwrap_or(0);
+        let operands = if k >= n {
+            operands
+        } else if k >= 2 {
+            let size = n.div_ceil(k);
+            let mut groups = Vec::new();
+            let mut it = operands.into_iter().peekable();
+            while it.peek().is_some() {
+                groups.push(it.by_ref().take(size).collect::<Vec<_>>());
+            }
+
+            let mut combined = Vec::new();
+            for mut group in groups {
+                combined.push(match group.len() {
+                    1 => group.remove(0),
+                    _ => self.gate(kind, group, level)?,
+                });
+            }
+            self.env.set_inputs_nr(id, combined.len());
+            combined
+        } else {
+            return Err(SynthError::InstantiateErr(GATES.to_string(), kind.to_string()));
+        };
+
+        for (i, operand) in operands.into_iter().enumerate() {
+            let to = PinRef::new(id, i);
+            match operand {
+                Source::Var(name) => {
+                    self.inputs.entry(name).or_default().push(to);
+                },
+                Source::Pin(from) => {
+                    self.env.connect(from, to);
+                },
+            }
+        }
+
+        Ok(Source::Pin(PinRef::new(id, 0)))
+    }
+}
+
+/// Place and wire the gates required to compute `expr` within `env`.
+///
+/// The gates are taken from the [`GATES`] category (modules `and`, `or`
+/// and `not`), constants from the [`CONSTANTS`] category (modules `high`
+/// and `low`), and laid out in columns from left to right, starting at
+/// `origin`. The inputs of the circuit are left unconnected and returned
+/// as part of the [`Synthesis`]. An output that equals a variable, e.g.
+/// `Q = A`, requires no instances at all.
+pub fn synthesize(
+    env: &mut ModuleEnv,
+    output: &str,
+    expr: &Expr,
+    origin: Point,
+) -> Result<Synthesis, SynthError> {
+    Synthesizer::check(env, expr)?;
+
+    let mut s = Synthesizer {
+        env,
+        origin,
+        rows: HashMap::new(),
+        inputs: HashMap::new(),
+        instances: Vec::new(),
+    };
+
+    match s.build(expr) {
+        Ok(driver) => {
+            Ok(Synthesis {
+                output: output.to_string(),
+                driver,
+                inputs: s.inputs,
+                instances: s.instances,
+            })
+        },
+        Err(e) => {
+            // Don't leave a half-built circuit behind.
+            for id in s.instances {
+                s.env.remove_instance(id);
+            }
+            Err(e)
+        },
+    }
+}
+
+/// Synthesize an equation like `Q = (A & B) | !C`.
+pub fn synthesize_equation(env: &mut ModuleEnv, text: &str, origin: Point) -> Result<Synthesis, SynthError> {
+    let eq = Equation::parse(text)?;
+    synthesize(env, &eq.output, &eq.minimal, origin)
+}
+
+/// Synthesize the minimised equation of every output of `table`.
+///
+/// The circuits of the outputs are stacked vertically. If any output
+/// fails, the circuits of all outputs are removed again.
+pub fn synthesize_table(env: &mut ModuleEnv, table: &TruthTable, origin: Point) -> Result<Vec<Synthesis>, SynthError> {
+    let mut v: Vec<Synthesis> = Vec::new();
+    let mut y = origin.y;
+
+    for eq in Equation::from_table(table) {
+        let s = match synthesize(env, &eq.output, &eq.minimal, Point { x: origin.x, y }) {
+            Ok(s) => s,
+            Err(e) => {
+                for id in v.iter().flat_map(|s| s.instances.iter()) {
+                    env.remove_instance(*id);
+                }
+                return Err(e);
+            },
+        };
+        let mut ys: Vec<f32> = s.instances
+            .iter()
+            .filter_map(|id| env.instances().get(id).map(|i| i.location.y))
+            .collect();
+        ys.push(y);
+        y = ys.iter().cloned().fold(f32::MIN, f32::max) + ROW_HEIGHT;
+        v.push(s);
+    }
+
+    Ok(v)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use wasmer::{Store, Imports};
+    use crate::core::contract::Contract;
+    use crate::core::sim::Probe;
+    use crate::analysis::truth_table::tests::{SOURCE_WAT, AND_WAT};
+
+    const OR_WAT: &str = r#"
+        (module
+            (global $a (mut i32) (i32.const 0))
+            (global $b (mut i32) (i32.const 0))
+            (global $q (mut i32) (i32.const 0))
+            (func (export "get_inputs_nr") (result i32) i32.const 2)
+            (func (export "get_outputs_nr") (result i32) i32.const 1)
+            (func (export "set_input") (param $i i32) (param $s i32)
+                (if (i32.eqz (local.get $i))
+                    (then (global.set $a (local.get $s)))
+                    (else (global.set $b (local.get $s)))
+                )
+            )
+            (func (export "get_output") (param $i i32) (result i32) global.get $q)
+            (func (export "update")
+                (if (i32.or (i32.eq (global.get $a) (i32.const 2)) (i32.eq (global.get $b) (i32.const 2)))
+                    (then (global.set $q (i32.const 2)))
+                    (else
+                        (if (i32.and (i32.eq (global.get $a) (i32.const 1)) (i32.eq (global.get $b) (i32.const 1)))
+                            (then (global.set $q (i32.const 1)))
+                            (else (global.set $q (i32.const 0)))
+                        )
+                    )
+                )
+            )
+        )
+    "#;
+
+    const NOT_WAT: &str = r#"
+        (module
+            (global $a (mut i32) (i32.const 0))
+            (func (export "get_inputs_nr") (result i32) i32.const 1)
+            (func (export "get_outputs_nr") (result i32) i32.const 1)
+            (func (export "set_input") (param $i i32) (param $s i32)
+                (global.set $a (local.get $s))
+            )
+            (func (export "get_output") (param $i i32) (result i32)
+                (if (result i32) (i32.eq (global.get $a) (i32.const 1))
+                    (then (i32.const 2))
+                    (else
+                        (if (result i32) (i32.eq (global.get $a) (i32.const 2))
+                            (then (i32.const 1))
+                            (else (i32.const 0))
+                        )
+                    )
+                )
+            )
+        )
+    "#;
+
+    const HIGH_WAT: &str = r#"
+        (module
+            (func (export "get_inputs_nr") (result i32) i32.const 0)
+            (func (export "get_outputs_nr") (result i32) i32.const 1)
+            (func (export "get_output") (param $i i32) (result i32) i32.const 2)
+        )
+    "#;
+
+    fn gates_env(not: bool) -> ModuleEnv {
+        let contract = Contract {
+            exports: vec![],
+            imports: vec![],
+            optional: vec![],
+        };
+        let mut env = ModuleEnv::new(Store::default(), Imports::new(), contract);
+        env.add_module_raw("Test", "source", SOURCE_WAT.as_bytes()).unwrap();
+        env.add_module_raw(GATES, "and", AND_WAT.as_bytes()).unwrap();
+        env.add_module_raw(GATES, "or", OR_WAT.as_bytes()).unwrap();
+        env.add_module_raw(CONSTANTS, "high", HIGH_WAT.as_bytes()).unwrap();
+        if not {
+            env.add_module_raw(GATES, "not", NOT_WAT.as_bytes()).unwrap();
+        }
+        env
+    }
+
+    /// Connect a source to every input of the synthesized circuit
+    /// and derive its equation.
+    fn analyse(env: &mut ModuleEnv, s: &Synthesis, vars: &[&str]) -> String {
+        let mut probes = Vec::new();
+        for var in vars {
+            let id = env.instantiate("Test", "source", Point { x: -100.0, y: 0.0 }).unwrap();
+            for pin in s.inputs[*var].iter() {
+                assert!(env.connect(PinRef::new(id, 0), *pin));
+            }
+            probes.push(Probe::new(var, PinRef::new(id, 0)));
+        }
+
+        let output_pin = match &s.driver {
+            Source::Pin(pin) => *pin,
+            Source::Var(var) => panic!("`{}` is wired to `{}`", s.output, var),
+        };
+        let eqs = Equation::from_circuit(env, &probes, &[Probe::new(&s.output, output_pin)]).unwrap();
+        eqs[0].to_string()
+    }
+
+    #[test]
+    fn synthesize_equation_test() {
+        let mut env = gates_env(true);
+        let s = synthesize_equation(&mut env, "Q = (A & B) | !C", Point { x: 0.0, y: 0.0 }).unwrap();
+        assert_eq!(3, s.instances.len());
+        assert_eq!("Q = A & B | !C", analyse(&mut env, &s, &["A", "B", "C"]));
+    }
+
+    #[test]
+    fn split_wide_gate_test() {
+        // The test AND gate only supports two inputs.
+        let mut env = gates_env(true);
+        let s = synthesize_equation(&mut env, "Q = A & B & C", Point { x: 0.0, y: 0.0 }).unwrap();
+        assert_eq!(2, s.instances.len());
+        assert_eq!("Q = A & B & C", analyse(&mut env, &s, &["A", "B", "C"]));
+    }
+
+    #[test]
+    fn synthesize_table_test() {
+        let mut env = gates_env(true);
+        let table = TruthTable::parse("A,B,Q\n0,0,0\n0,1,1\n1,0,1\n1,1,1\n", 1).unwrap();
+        let s = synthesize_table(&mut env, &table, Point { x: 0.0, y: 0.0 }).unwrap();
+        assert_eq!(1, s.len());
+        assert_eq!("Q = A | B", analyse(&mut env, &s[0], &["A", "B"]));
+    }
+
+    #[test]
+    fn wire_and_constant_test() {
+        let mut env = gates_env(true);
+        let s = synthesize_equation(&mut env, "Q = A", Point { x: 0.0, y: 0.0 }).unwrap();
+        assert_eq!(Source::Var("A".to_string()), s.driver);
+        assert!(s.instances.is_empty());
+
+        let s = synthesize_equation(&mut env, "Q = 1", Point { x: 0.0, y: 0.0 }).unwrap();
+        assert_eq!(1, s.instances.len());
+        assert_eq!(Source::Pin(PinRef::new(s.instances[0], 0)), s.driver);
+        assert!(env.settle(8));
+        assert_eq!(crate::contract::State::High, env.output(&PinRef::new(s.instances[0], 0)));
+
+        assert_eq!(
+            Err(SynthError::MissingModule(CONSTANTS.to_string(), "low".to_string())),
+            synthesize_equation(&mut env, "Q = A & 0", Point { x: 0.0, y: 0.0 })
+        );
+    }
+
+    #[test]
+    fn missing_module_test() {
+        let mut env = gates_env(false);
+        assert_eq!(
+            Err(SynthError::MissingModule(GATES.to_string(), "not".to_string())),
+            synthesize_equation(&mut env, "Q = A & !B", Point { x: 0.0, y: 0.0 })
+        );
+        assert_eq!(0, env.instances().len());
+        assert_eq!(
+            "the module `Gates::not` is required but isn't loaded",
+            SynthError::MissingModule(GATES.to_string(), "not".to_string()).to_string()
+        );
+
+        // `P` is built, but `Q` fails, so `P` is removed again.
+        let table = TruthTable::parse("A,B,P,Q\n0,0,0,1\n0,1,1,1\n1,0,1,0\n1,1,1,0\n", 2).unwrap();
+        assert!(synthesize_table(&mut env, &table, Point { x: 0.0, y: 0.0 }).is_err());
+        assert_eq!(0, env.instances().len());
+    }
+}
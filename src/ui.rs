@@ -0,0 +1,6 @@
+pub mod controls;
+pub mod debug;
+pub mod equations;
+pub mod memory;
+pub mod synth;
+pub mod waveform;
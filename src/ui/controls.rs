@@ -0,0 +1,85 @@
+use macroquad::prelude::*;
+use macroquad::ui::{root_ui, hash, widgets};
+use crate::core::module::{ModuleEnv, DEFAULT_SPEED};
+
+/// The preferred size of the control panel.
+pub const PANEL_SIZE: (f32, f32) = (300.0, 130.0);
+/// The range of the speed slider as powers of ten steps per second.
+pub const SPEED_RANGE: (f32, f32) = (0.0, 5.0);
+
+/// A panel to run, pause and step the simulation and to
+/// change its speed.
+#[derive(Debug, Clone)]
+pub struct ControlPanel {
+    /// The number of cycles to run (text entered by the user).
+    cycles: String,
+    /// The speed as power of ten steps per second.
+    speed: f32,
+}
+
+impl Default for ControlPanel {
+    fn default() -> Self {
+        Self {
+            cycles: "10".to_string(),
+            speed: DEFAULT_SPEED.log10(),
+        }
+    }
+}
+
+impl ControlPanel {
+    /// Create a new panel.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Draw the panel into `rect`.
+    pub fn draw(&mut self, env: &mut ModuleEnv, rect: Rect) {
+        let paused = env.is_paused();
+        let (mut toggle, mut step, mut event, mut cycle, mut run) = (false, false, false, false, false);
+
+        widgets::Window::new(
+            hash!(),
+            vec2(rect.x, rect.y),
+            vec2(rect.w, rect.h),
+        )
+            .label("Simulation")
+            .movable(false)
+            .ui(&mut *root_ui(), |ui| {
+                toggle = ui.button(None, if paused { "Run" } else { "Pause" });
+                ui.same_line(0.0);
+                step = ui.button(None, "Step");
+                ui.same_line(0.0);
+                event = ui.button(None, "Event");
+                ui.same_line(0.0);
+                cycle = ui.button(None, "Cycle");
+
+                ui.editbox(hash!(), vec2(60.0, 20.0), &mut self.cycles);
+                ui.same_line(0.0);
+                run = ui.button(None, "Run cycles");
+
+                ui.slider(hash!(), "speed", SPEED_RANGE.0..SPEED_RANGE.1, &mut self.speed);
+                ui.label(None, &format!(
+                    "t = {}, {:.0} steps/s", env.simulator().steps(), 10f32.powf(self.speed)
+                ));
+            });
+
+        env.set_speed(10f32.powf(self.speed));
+        if toggle {
+            if paused { env.run() } else { env.pause() }
+        }
+        if step {
+            env.step();
+        }
+        if event {
+            env.step_event();
+        }
+        if cycle {
+            env.run_cycles(1);
+        }
+        if run {
+            if let Ok(n) = self.cycles.trim().parse() {
+                env.run_cycles(n);
+            }
+        }
+    }
+}
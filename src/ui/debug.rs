@@ -0,0 +1,134 @@
+use macroquad::prelude::*;
+use macroquad::ui::{root_ui, hash, widgets};
+use crate::core::module::ModuleEnv;
+use crate::core::debug::Trigger;
+
+/// The preferred size of the debug panel.
+pub const PANEL_SIZE: (f32, f32) = (300.0, 320.0);
+
+/// A panel that lists all breakpoints and watches together with
+/// their hit counts.
+///
+/// Breakpoints are entered as `<probe> <trigger>`, e.g. `CLK rising`,
+/// `RESET low` or `ADDR = 1f` (see [`Trigger::parse`]). Watches are
+/// Boolean expressions over the probe names, e.g. `EN & !RESET`.
+#[derive(Debug, Clone, Default)]
+pub struct DebugPanel {
+    /// The breakpoint to add (text entered by the user).
+    breakpoint: String,
+    /// The watch expression to add (text entered by the user).
+    watch: String,
+    /// The result of the last action.
+    message: String,
+}
+
+impl DebugPanel {
+    /// Create a new panel.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    fn add_breakpoint(&mut self, env: &mut ModuleEnv) {
+        let text = self.breakpoint.trim();
+        let (name, trigger) = match text.split_once(char::is_whitespace) {
+            Some((name, trigger)) => (name, trigger),
+            None => {
+                self.message = "enter `<probe> <trigger>`".to_string();
+                return;
+            },
+        };
+        let pin = env.history().probe(name).map(|p| p.pin);
+
+        self.message = match (pin, Trigger::parse(trigger)) {
+            (Some(pin), Some(trigger)) => {
+                env.debugger_mut().add_breakpoint(name, pin, trigger);
+                self.breakpoint.clear();
+                String::new()
+            },
+            (None, _) => format!("there is no probe `{}`", name),
+            (_, None) => format!("unknown trigger `{}`", trigger.trim()),
+        };
+    }
+
+    fn add_watch(&mut self, env: &mut ModuleEnv, breaks: bool) {
+        self.message = match env.debugger_mut().add_watch(&self.watch, breaks) {
+            Ok(()) => {
+                self.watch.clear();
+                String::new()
+            },
+            Err(e) => format!("column {}: {}", e.column, e.message),
+        };
+    }
+
+    /// Draw the panel into `rect`.
+    pub fn draw(&mut self, env: &mut ModuleEnv, rect: Rect) {
+        let (mut add_bp, mut add_watch, mut add_break) = (false, false, false);
+        let (mut remove_bp, mut remove_watch, mut toggle) = (None, None, None);
+
+        widgets::Window::new(
+            hash!(),
+            vec2(rect.x, rect.y),
+            vec2(rect.w, rect.h),
+        )
+            .label("Debug")
+            .movable(false)
+            .ui(&mut *root_ui(), |ui| {
+                let debugger = env.debugger();
+                if let Some(hit) = debugger.hit() {
+                    ui.label(None, &format!("stopped: {}", hit));
+                }
+
+                ui.label(None, "Breakpoints");
+                for (i, bp) in debugger.breakpoints().iter().enumerate() {
+                    ui.label(None, &format!("{} ({} hits)", bp, bp.hits));
+                    ui.same_line(0.0);
+                    if ui.button(None, if bp.enabled { "off" } else { "on" }) {
+                        toggle = Some(i);
+                    }
+                    ui.same_line(0.0);
+                    if ui.button(None, "x") {
+                        remove_bp = Some(i);
+                    }
+                }
+                ui.editbox(hash!(), vec2(rect.w - 60.0, 20.0), &mut self.breakpoint);
+                ui.same_line(0.0);
+                add_bp = ui.button(None, "Add");
+                ui.separator();
+
+                ui.label(None, "Watches");
+                for (i, watch) in debugger.watches().iter().enumerate() {
+                    ui.label(None, &format!(
+                        "{} = {}{} ({} hits)",
+                        watch.expr, watch.value.symbol(), if watch.breaks { " [break]" } else { "" }, watch.hits
+                    ));
+                    ui.same_line(0.0);
+                    if ui.button(None, "x") {
+                        remove_watch = Some(i);
+                    }
+                }
+                ui.editbox(hash!(), vec2(rect.w - 110.0, 20.0), &mut self.watch);
+                ui.same_line(0.0);
+                add_watch = ui.button(None, "Add");
+                ui.same_line(0.0);
+                add_break = ui.button(None, "Break");
+                ui.label(None, &self.message);
+            });
+
+        if add_bp {
+            self.add_breakpoint(env);
+        }
+        if add_watch || add_break {
+            self.add_watch(env, add_break);
+        }
+        if let Some(i) = toggle {
+            let bp = &mut env.debugger_mut().breakpoints_mut()[i];
+            bp.enabled = !bp.enabled;
+        }
+        if let Some(i) = remove_bp {
+            env.debugger_mut().remove_breakpoint(i);
+        }
+        if let Some(i) = remove_watch {
+            env.debugger_mut().remove_watch(i);
+        }
+    }
+}
@@ -0,0 +1,94 @@
+use macroquad::prelude::*;
+use macroquad::ui::{root_ui, hash, widgets};
+use crate::core::module::ModuleEnv;
+use crate::core::sim::Probe;
+use crate::analysis::boolean::Equation;
+
+/// The preferred width of the equation panel.
+pub const PANEL_WIDTH: f32 = 320.0;
+
+/// A panel that derives the equations of the circuit between a set of probes and lists them
+/// in canonical and minimised form.
+///
+/// Inputs and outputs are entered as comma separated probe names,
+/// e.g. `A, B, C` and `Q`.
+#[derive(Debug, Clone, Default)]
+pub struct EquationPanel {
+    /// The names of the input probes (text entered by the user).
+    inputs: String,
+    /// The names of the output probes (text entered by the user).
+    outputs: String,
+    /// The equations of the last analysis.
+    equations: Vec<Equation>,
+    /// The result of the last analysis.
+    message: String,
+}
+
+/// Get the probes of the comma separated probe names `names`.
+fn probes(env: &ModuleEnv, names: &str) -> Result<Vec<Probe>, String> {
+    env.history().probes(names).map_err(|name| format!("there is no probe `{}`", name))
+}
+
+impl EquationPanel {
+    /// Create a new, empty panel.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Derive the equations of the outputs by generating the truth
+    /// table of the circuit.
+    fn analyse(&mut self, env: &mut ModuleEnv) {
+        let result = probes(env, &self.inputs).and_then(|inputs| {
+            let outputs = probes(env, &self.outputs)?;
+            if outputs.is_empty() {
+                return Err("enter at least one output".to_string());
+            }
+            Equation::from_circuit(env, &inputs, &outputs).map_err(|e| e.to_string())
+        });
+
+        match result {
+            Ok(equations) => {
+                self.equations = equations;
+                self.message.clear();
+            },
+            Err(e) => self.message = e,
+        }
+    }
+
+    /// Draw the panel into `rect`.
+    pub fn draw(&mut self, env: &mut ModuleEnv, rect: Rect) {
+        let mut clicked = false;
+
+        widgets::Window::new(
+            hash!(),
+            vec2(rect.x, rect.y),
+            vec2(rect.w, rect.h),
+        )
+            .label("Equations")
+            .movable(false)
+            .ui(&mut *root_ui(), |ui| {
+                ui.label(None, "inputs");
+                ui.editbox(hash!(), vec2(rect.w - 10.0, 20.0), &mut self.inputs);
+                ui.label(None, "outputs");
+                ui.editbox(hash!(), vec2(rect.w - 10.0, 20.0), &mut self.outputs);
+                clicked = ui.button(None, "Analyse");
+                ui.label(None, &self.message);
+                ui.separator();
+
+                if self.equations.is_empty() {
+                    ui.label(None, "no circuit analysed");
+                }
+
+                for eq in &self.equations {
+                    ui.label(None, &format!("{}:", eq.output));
+                    ui.label(None, &format!("  SOP: {}", eq.sop));
+                    ui.label(None, &format!("  min: {}", eq.minimal));
+                    ui.separator();
+                }
+            });
+
+        if clicked {
+            self.analyse(env);
+        }
+    }
+}
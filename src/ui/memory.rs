@@ -0,0 +1,149 @@
+use macroquad::prelude::*;
+use macroquad::ui::{root_ui, hash, widgets};
+use uuid::Uuid;
+use std::path::Path;
+use crate::core::module::ModuleEnv;
+
+/// The preferred size of the memory panel.
+pub const PANEL_SIZE: (f32, f32) = (360.0, 420.0);
+/// The number of words shown per row.
+pub const WORDS_PER_ROW: usize = 8;
+/// The height of a single row of words.
+const ROW_HEIGHT: f32 = 20.0;
+/// The height taken by the title, the file and the edit controls.
+const CONTROLS_HEIGHT: f32 = 160.0;
+
+/// A panel that shows the contents of a ROM or RAM instance.
+///
+/// The contents can be loaded from a file at any time, single
+/// words can be edited while the simulation is paused. Only the
+/// rows that fit into the panel are read, the mouse wheel scrolls
+/// through the rest.
+#[derive(Debug, Clone, Default)]
+pub struct MemoryPanel {
+    /// The memory instance to show.
+    instance: Option<Uuid>,
+    /// The path of the file to load.
+    path: String,
+    /// The address of the word to edit (hexadecimal).
+    address: String,
+    /// The new value of the word (hexadecimal).
+    value: String,
+    /// The result of the last action.
+    message: String,
+    /// The first row shown.
+    first_row: usize,
+}
+
+impl MemoryPanel {
+    /// Create a new panel without an instance.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Show the memory instance `id`, or hide the panel if `None`.
+    pub fn select(&mut self, id: Option<Uuid>) {
+        if self.instance != id {
+            self.instance = id;
+            self.message.clear();
+            self.first_row = 0;
+        }
+    }
+
+    /// Check if the panel shows an instance.
+    pub fn is_shown(&self) -> bool {
+        self.instance.is_some()
+    }
+
+    fn load(&mut self, env: &mut ModuleEnv, id: Uuid) {
+        self.message = match env.load_memory(id, Path::new(self.path.trim())) {
+            Ok(n) => format!("loaded {} words", n),
+            Err(e) => e.to_string(),
+        };
+    }
+
+    fn write(&mut self, env: &mut ModuleEnv, id: Uuid) {
+        let parse = |s: &str| usize::from_str_radix(s.trim().trim_start_matches("0x"), 16).ok();
+        self.message = match (parse(&self.address), parse(&self.value)) {
+            (Some(addr), Some(value)) if value <= u16::MAX as usize => {
+                if env.write_memory(id, addr, value as u16) {
+                    format!("{:04x} = {:x}", addr, value)
+                } else if !env.is_paused() {
+                    "pause the simulation to edit".to_string()
+                } else {
+                    "address out of range".to_string()
+                }
+            },
+            _ => "enter a hexadecimal address and value".to_string(),
+        };
+    }
+
+    /// Draw the panel into `rect`, if it shows an instance.
+    pub fn draw(&mut self, env: &mut ModuleEnv, rect: Rect) {
+        let id = match self.instance {
+            Some(id) => id,
+            None => return,
+        };
+        let size = match env.memory_size(id) {
+            Some(size) => size,
+            None => {
+                // The instance was removed.
+                self.instance = None;
+                return;
+            },
+        };
+
+        let rows = size.div_ceil(WORDS_PER_ROW);
+        let visible = ((rect.h - CONTROLS_HEIGHT) / ROW_HEIGHT).max(1.0) as usize;
+        let (mx, my) = mouse_position();
+        let (_, wheel) = mouse_wheel();
+        if rect.contains(vec2(mx, my)) {
+            if wheel > 0.0 {
+                self.first_row = self.first_row.saturating_sub(1);
+            } else if wheel < 0.0 {
+                self.first_row += 1;
+            }
+        }
+        self.first_row = self.first_row.min(rows.saturating_sub(visible));
+
+        let start = self.first_row * WORDS_PER_ROW;
+        let words = env.read_memory(id, start..start + visible * WORDS_PER_ROW).unwrap_or_default();
+
+        let (mut load, mut write) = (false, false);
+
+        widgets::Window::new(
+            hash!(),
+            vec2(rect.x, rect.y),
+            vec2(rect.w, rect.h),
+        )
+            .label("Memory")
+            .movable(false)
+            .ui(&mut *root_ui(), |ui| {
+                ui.editbox(hash!(), vec2(rect.w - 70.0, 20.0), &mut self.path);
+                ui.same_line(0.0);
+                load = ui.button(None, "Load");
+                ui.separator();
+
+                for (row, chunk) in words.chunks(WORDS_PER_ROW).enumerate() {
+                    let line: Vec<String> = chunk.iter().map(|w| format!("{:04x}", w)).collect();
+                    ui.label(None, &format!("{:04x}: {}", start + row * WORDS_PER_ROW, line.join(" ")));
+                }
+                ui.separator();
+
+                ui.label(None, "Address / Value");
+                ui.editbox(hash!(), vec2(80.0, 20.0), &mut self.address);
+                ui.same_line(0.0);
+                ui.editbox(hash!(), vec2(80.0, 20.0), &mut self.value);
+                ui.same_line(0.0);
+                write = ui.button(None, "Write");
+                ui.label(None, &self.message);
+            });
+
+        if load {
+            self.load(env, id);
+        }
+        if write {
+            self.write(env, id);
+        }
+    }
+}
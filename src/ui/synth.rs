@@ -0,0 +1,67 @@
+use macroquad::prelude::*;
+use macroquad::ui::{root_ui, hash, widgets};
+use crate::core::module::ModuleEnv;
+use crate::analysis::truth_table::TruthTable;
+use crate::misc::Point;
+use crate::synth::{synthesize_equation, synthesize_table, SynthError};
+
+/// The preferred size of the synthesis panel.
+pub const PANEL_SIZE: (f32, f32) = (320.0, 200.0);
+
+/// A panel that lets the user enter an equation like
+/// `Q = (A & B) | !C` or paste a truth table with a single
+/// output and builds the corresponding circuit.
+#[derive(Debug, Clone, Default)]
+pub struct SynthPanel {
+    /// The text entered by the user.
+    text: String,
+    /// The result of the last synthesis.
+    message: String,
+}
+
+impl SynthPanel {
+    /// Create a new, empty panel.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Synthesize the entered text and place the circuit at `origin`.
+    fn synthesize(&mut self, env: &mut ModuleEnv, origin: Point) {
+        let result = if self.text.contains('=') {
+            synthesize_equation(env, &self.text, origin).map(|s| s.instances.len())
+        } else {
+            TruthTable::parse(&self.text, 1)
+                .map_err(SynthError::from)
+                .and_then(|table| synthesize_table(env, &table, origin))
+                .map(|v| v.iter().map(|s| s.instances.len()).sum())
+        };
+
+        self.message = match result {
+            Ok(n) => format!("placed {} gates", n),
+            Err(e) => e.to_string(),
+        };
+    }
+
+    /// Draw the panel into `rect`, synthesized circuits are placed
+    /// at `origin`.
+    pub fn draw(&mut self, env: &mut ModuleEnv, rect: Rect, origin: Point) {
+        let mut clicked = false;
+
+        widgets::Window::new(
+            hash!(),
+            vec2(rect.x, rect.y),
+            vec2(rect.w, rect.h),
+        )
+            .label("Synthesize")
+            .movable(false)
+            .ui(&mut *root_ui(), |ui| {
+                ui.editbox(hash!(), vec2(rect.w - 10.0, (rect.h - 80.0).max(20.0)), &mut self.text);
+                clicked = ui.button(None, "Synthesize");
+                ui.label(None, &self.message);
+            });
+
+        if clicked {
+            self.synthesize(env, origin);
+        }
+    }
+}
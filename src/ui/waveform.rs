@@ -0,0 +1,229 @@
+use macroquad::prelude::*;
+use crate::core::history::History;
+use crate::contract::{State, Bits};
+
+/// The width of the column containing the probe names.
+pub const LABEL_WIDTH: f32 = 90.0;
+/// The height of a single trace.
+pub const ROW_HEIGHT: f32 = 28.0;
+/// The height of the time axis.
+pub const AXIS_HEIGHT: f32 = 20.0;
+
+const BACKGROUND: Color = Color::new(0.1, 0.1, 0.12, 1.0);
+const SIGNAL: Color = Color::new(0.3, 0.9, 0.4, 1.0);
+const UNDEFINED: Color = Color::new(0.9, 0.6, 0.1, 0.6);
+const HIGH_IMPEDANCE: Color = Color::new(0.3, 0.5, 1.0, 1.0);
+const CONFLICT: Color = Color::new(0.9, 0.1, 0.1, 0.8);
+const CURSOR_A: Color = YELLOW;
+const CURSOR_B: Color = SKYBLUE;
+
+/// A timing diagram of all traces of a [`History`].
+///
+/// * Scroll the mouse wheel to zoom, press the arrow keys to move.
+/// * Left click to place cursor A, Shift + left click to place cursor B.
+///   The time between both cursors is shown in the top left corner.
+#[derive(Debug, Clone)]
+pub struct WaveformView {
+    /// Pixels per time unit.
+    zoom: f32,
+    /// The first visible point in time.
+    offset: f32,
+    /// Follow the end of the history while new samples arrive.
+    follow: bool,
+    /// The measurement cursors.
+    cursors: [Option<u64>; 2],
+}
+
+impl Default for WaveformView {
+    fn default() -> Self {
+        Self {
+            zoom: 8.0,
+            offset: 0.0,
+            follow: true,
+            cursors: [None, None],
+        }
+    }
+}
+
+impl WaveformView {
+    /// Create a new view.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Get the time between cursor A and cursor B, if both are set.
+    pub fn measurement(&self) -> Option<u64> {
+        match self.cursors {
+            [Some(a), Some(b)] => Some(a.max(b) - a.min(b)),
+            _ => None,
+        }
+    }
+
+    /// Get the height required to show `history`.
+    pub fn height(history: &History) -> f32 {
+        AXIS_HEIGHT + history.traces().len() as f32 * ROW_HEIGHT
+    }
+
+    fn time_to_x(&self, rect: &Rect, t: f32) -> f32 {
+        rect.x + LABEL_WIDTH + (t - self.offset) * self.zoom
+    }
+
+    fn x_to_time(&self, rect: &Rect, x: f32) -> f32 {
+        (x - rect.x - LABEL_WIDTH) / self.zoom + self.offset
+    }
+
+    /// React to mouse and keyboard input.
+    fn handle_input(&mut self, history: &History, rect: &Rect) {
+        let (mx, my) = mouse_position();
+        let inside = rect.contains(vec2(mx, my)) && mx > rect.x + LABEL_WIDTH;
+
+        let (_, wheel) = mouse_wheel();
+        if inside && wheel != 0.0 {
+            // Zoom around the time under the mouse pointer.
+            let t = self.x_to_time(rect, mx);
+            self.zoom = (self.zoom * if wheel > 0.0 { 1.25 } else { 0.8 }).clamp(0.05, 200.0);
+            self.offset = t - (mx - rect.x - LABEL_WIDTH) / self.zoom;
+            self.follow = false;
+        }
+
+        let step = 40.0 / self.zoom;
+        if is_key_down(KeyCode::Left) {
+            self.offset -= step;
+            self.follow = false;
+        }
+        if is_key_down(KeyCode::Right) {
+            self.offset += step;
+        }
+        if is_key_pressed(KeyCode::End) {
+            self.follow = true;
+        }
+
+        let visible = (rect.w - LABEL_WIDTH) / self.zoom;
+        if self.follow {
+            self.offset = history.end() as f32 - visible;
+        } else if self.offset + visible >= history.end() as f32 && is_key_down(KeyCode::Right) {
+            self.follow = true;
+        }
+        self.offset = self.offset.max(0.0);
+
+        if inside && is_mouse_button_pressed(MouseButton::Left) {
+            let t = self.x_to_time(rect, mx).round().max(0.0) as u64;
+            if is_key_down(KeyCode::LeftShift) || is_key_down(KeyCode::RightShift) {
+                self.cursors[1] = Some(t);
+            } else {
+                self.cursors[0] = Some(t);
+            }
+        }
+    }
+
+    /// Draw the diagram into `rect` and handle user input.
+    pub fn draw(&mut self, history: &History, rect: Rect) {
+        self.handle_input(history, &rect);
+
+        draw_rectangle(rect.x, rect.y, rect.w, rect.h, BACKGROUND);
+
+        let from = self.offset.floor() as u64;
+        let to = (self.x_to_time(&rect, rect.x + rect.w).ceil() as u64).min(history.end() + 1);
+
+        // Time axis with about one tick every 80 pixels.
+        let tick = ((80.0 / self.zoom).ceil() as u64).max(1);
+        let mut t = from - from % tick;
+        while t <= to {
+            let x = self.time_to_x(&rect, t as f32);
+            if x >= rect.x + LABEL_WIDTH {
+                draw_line(x, rect.y, x, rect.y + rect.h, 1.0, DARKGRAY);
+                draw_text(&t.to_string(), x + 2.0, rect.y + 14.0, 16.0, LIGHTGRAY);
+            }
+            t += tick;
+        }
+
+        for (i, trace) in history.traces().iter().enumerate() {
+            let top = rect.y + AXIS_HEIGHT + i as f32 * ROW_HEIGHT;
+            let (high, low) = (top + 4.0, top + ROW_HEIGHT - 4.0);
+            draw_text(&trace.probe.name, rect.x + 4.0, top + ROW_HEIGHT * 0.65, 18.0, WHITE);
+
+            let mut last: Option<f32> = None;
+            for (start, end, bits) in trace.segments(from, to) {
+                let x1 = self.time_to_x(&rect, start as f32).max(rect.x + LABEL_WIDTH);
+                let x2 = self.time_to_x(&rect, end as f32).min(rect.x + rect.w);
+
+                // Buses are drawn as a band with the value written into it,
+                // crossing over at every change.
+                if trace.width > 1 {
+                    let (mid, slope) = ((high + low) / 2.0, ((x2 - x1) / 2.0).min(3.0));
+                    let color = match bits.value() {
+                        Some(_) => SIGNAL,
+                        None if bits.has_conflict() => CONFLICT,
+                        None => UNDEFINED,
+                    };
+                    draw_line(x1, mid, x1 + slope, high, 1.5, color);
+                    draw_line(x1, mid, x1 + slope, low, 1.5, color);
+                    draw_line(x1 + slope, high, x2 - slope, high, 1.5, color);
+                    draw_line(x1 + slope, low, x2 - slope, low, 1.5, color);
+                    draw_line(x2 - slope, high, x2, mid, 1.5, color);
+                    draw_line(x2 - slope, low, x2, mid, 1.5, color);
+
+                    let text = bus_text(&bits);
+                    if measure_text(&text, None, 14, 1.0).width + 2.0 * slope < x2 - x1 {
+                        draw_text(&text, x1 + slope + 1.0, low - 5.0, 14.0, WHITE);
+                    }
+                    continue;
+                }
+
+                let y = match bits.get(0) {
+                    State::High => high,
+                    State::Low => low,
+                    State::Undefined => {
+                        draw_rectangle(x1, high, x2 - x1, low - high, UNDEFINED);
+                        (high + low) / 2.0
+                    },
+                    State::Conflict => {
+                        draw_rectangle(x1, high, x2 - x1, low - high, CONFLICT);
+                        (high + low) / 2.0
+                    },
+                    State::HighImpedance => {
+                        draw_line(x1, (high + low) / 2.0, x2, (high + low) / 2.0, 1.5, HIGH_IMPEDANCE);
+                        (high + low) / 2.0
+                    },
+                };
+
+                if let Some(prev) = last {
+                    draw_line(x1, prev, x1, y, 1.5, SIGNAL);
+                }
+                draw_line(x1, y, x2, y, 1.5, SIGNAL);
+                last = Some(y);
+            }
+
+            if let Some(a) = self.cursors[0] {
+                let text = match trace.width {
+                    1 => trace.state_at(a).symbol().to_string(),
+                    _ => bus_text(&trace.bits_at(a)),
+                };
+                let w = measure_text(&text, None, 18, 1.0).width;
+                draw_text(&text, rect.x + LABEL_WIDTH - 6.0 - w, top + ROW_HEIGHT * 0.65, 18.0, CURSOR_A);
+            }
+        }
+
+        for (cursor, color) in self.cursors.iter().zip([CURSOR_A, CURSOR_B]) {
+            if let Some(t) = cursor {
+                let x = self.time_to_x(&rect, *t as f32);
+                if x >= rect.x + LABEL_WIDTH && x <= rect.x + rect.w {
+                    draw_line(x, rect.y, x, rect.y + rect.h, 1.0, color);
+                }
+            }
+        }
+
+        if let Some(dt) = self.measurement() {
+            draw_text(&format!("dt = {}", dt), rect.x + 4.0, rect.y + 14.0, 16.0, CURSOR_B);
+        }
+    }
+}
+
+/// Get the value of a bus as hexadecimal number, or bit by bit if
+/// not all bits are at a defined level.
+fn bus_text(bits: &Bits) -> String {
+    match bits.value() {
+        Some(v) => format!("{:x}", v),
+        None => bits.to_string(),
+    }
+}
@@ -0,0 +1,318 @@
+//! A minimal, sandboxed subset of WASI (`wasi_snapshot_preview1`).
+//!
+//! Modules compiled from C, Zig or AssemblyScript import a few WASI
+//! functions from their start-up code, even if they never use them.
+//! The functions below are enough for those modules to run, without
+//! giving them access to the host:
+//!
+//! * there are no arguments, no environment variables and no
+//!   preopened directories, so there is no file system;
+//! * `stdout` and `stderr` end up on the host's `stderr`, prefixed
+//!   with the name of the module, `stdin` is always empty;
+//! * `random_get` is a seeded PRNG, so simulations are reproducible;
+//! * `proc_exit` traps.
+//!
+//! Every instance gets a [`WasiEnv`] of its own, see
+//! [`crate::core::module::ModuleEnv::enable_wasi`].
+
+use wasmer::{
+    AsStoreMut, Function, FunctionEnv, FunctionEnvMut, Imports, Instance, Memory, MemoryView,
+    MemoryAccessError, RuntimeError,
+};
+use std::time::{Instant, SystemTime, UNIX_EPOCH};
+use megs_abi::WASI_MODULE;
+
+/// No error occurred.
+pub const ERRNO_SUCCESS: i32 = 0;
+/// Bad file descriptor.
+pub const ERRNO_BADF: i32 = 8;
+/// Bad address, e.g. a pointer outside of the memory.
+pub const ERRNO_FAULT: i32 = 21;
+/// Invalid argument.
+pub const ERRNO_INVAL: i32 = 28;
+/// Invalid seek.
+pub const ERRNO_SPIPE: i32 = 70;
+
+/// Output without a line break is printed once it grows this long.
+const OUTPUT_MAX: usize = 4096;
+
+/// The seed of the PRNG behind `random_get`.
+const SEED: u64 = 0x4d45_4753_5741_5349;
+
+/// The state of the WASI functions of a single instance.
+#[derive(Debug, Clone)]
+pub struct WasiEnv {
+    /// The name of the module, used to prefix its output.
+    pub name: String,
+    /// The exported memory of the instance, if any.
+    pub memory: Option<Memory>,
+    /// Written but not yet terminated output.
+    output: Vec<u8>,
+    /// The state of the PRNG.
+    rng: u64,
+    /// The origin of the monotonic clock.
+    start: Instant,
+}
+
+impl WasiEnv {
+    pub fn new(name: &str) -> Self {
+        Self {
+            name: name.to_string(),
+            memory: None,
+            output: Vec::new(),
+            rng: SEED,
+            start: Instant::now(),
+        }
+    }
+
+    /// Get the next value of the PRNG (xorshift64*).
+    fn next_random(&mut self) -> u64 {
+        self.rng ^= self.rng >> 12;
+        self.rng ^= self.rng << 25;
+        self.rng ^= self.rng >> 27;
+        self.rng.wrapping_mul(0x2545_f491_4f6c_dd1d)
+    }
+
+    /// Print all complete lines of output, as well as overly long
+    /// unterminated ones, so a module can't make the buffer grow without
+    /// bounds.
+    fn flush_lines(&mut self) {
+        while let Some(i) = self.output.iter().position(|b| *b == b'\n') {
+            let line: Vec<u8> = self.output.drain(..=i).collect();
+            eprintln!("[{}] {}", self.name, String::from_utf8_lossy(&line[..i]));
+        }
+        while self.output.len() >= OUTPUT_MAX {
+            let line: Vec<u8> = self.output.drain(..OUTPUT_MAX).collect();
+            eprintln!("[{}] {}", self.name, String::from_utf8_lossy(&line));
+        }
+    }
+}
+
+/// Add the WASI functions, backed by `env`, to `imports`.
+pub fn define_imports(imports: &mut Imports, store: &mut impl AsStoreMut, env: &FunctionEnv<WasiEnv>) {
+    imports.define(WASI_MODULE, "args_get", Function::new_typed_with_env(store, env, args_get));
+    imports.define(WASI_MODULE, "args_sizes_get", Function::new_typed_with_env(store, env, args_sizes_get));
+    imports.define(WASI_MODULE, "environ_get", Function::new_typed_with_env(store, env, args_get));
+    imports.define(WASI_MODULE, "environ_sizes_get", Function::new_typed_with_env(store, env, args_sizes_get));
+    imports.define(WASI_MODULE, "clock_time_get", Function::new_typed_with_env(store, env, clock_time_get));
+    imports.define(WASI_MODULE, "fd_close", Function::new_typed_with_env(store, env, fd_close));
+    imports.define(WASI_MODULE, "fd_fdstat_get", Function::new_typed_with_env(store, env, fd_fdstat_get));
+    imports.define(WASI_MODULE, "fd_prestat_get", Function::new_typed_with_env(store, env, fd_prestat_get));
+    imports.define(WASI_MODULE, "fd_prestat_dir_name", Function::new_typed_with_env(store, env, fd_prestat_dir_name));
+    imports.define(WASI_MODULE, "fd_read", Function::new_typed_with_env(store, env, fd_read));
+    imports.define(WASI_MODULE, "fd_seek", Function::new_typed_with_env(store, env, fd_seek));
+    imports.define(WASI_MODULE, "fd_write", Function::new_typed_with_env(store, env, fd_write));
+    imports.define(WASI_MODULE, "proc_exit", Function::new_typed_with_env(store, env, proc_exit));
+    imports.define(WASI_MODULE, "random_get", Function::new_typed_with_env(store, env, random_get));
+    imports.define(WASI_MODULE, "sched_yield", Function::new_typed_with_env(store, env, sched_yield));
+}
+
+/// Hand the memory of `instance` to its WASI functions and run the
+/// initialisation of reactor modules (`_initialize`), if exported.
+pub fn initialize(store: &mut impl AsStoreMut, env: &FunctionEnv<WasiEnv>, instance: &Instance) -> Result<(), RuntimeError> {
+    env.as_mut(store).memory = instance.exports.get_memory("memory").ok().cloned();
+
+    if let Ok(init) = instance.exports.get_function("_initialize") {
+        init.call(store, &[])?;
+    }
+    Ok(())
+}
+
+/// Check that the `len` bytes at `ptr` lie within the memory, before
+/// allocating a buffer of a length chosen by the module.
+fn in_bounds(view: &MemoryView, ptr: u64, len: u64) -> bool {
+    ptr.checked_add(len).is_some_and(|end| end <= view.data_size())
+}
+
+fn write_u32(view: &MemoryView, ptr: u64, v: u32) -> Result<(), MemoryAccessError> {
+    view.write(ptr, &v.to_le_bytes())
+}
+
+fn read_u32(view: &MemoryView, ptr: u64) -> Result<u32, MemoryAccessError> {
+    let mut buf = [0; 4];
+    view.read(ptr, &mut buf)?;
+    Ok(u32::from_le_bytes(buf))
+}
+
+/// There are no arguments (and no environment variables).
+pub fn args_get(_env: FunctionEnvMut<WasiEnv>, _argv: i32, _argv_buf: i32) -> i32 {
+    ERRNO_SUCCESS
+}
+
+/// Report zero arguments (or environment variables) of zero bytes.
+pub fn args_sizes_get(mut env: FunctionEnvMut<WasiEnv>, count: i32, size: i32) -> i32 {
+    let (data, store) = env.data_and_store_mut();
+    let memory = match &data.memory {
+        Some(memory) => memory,
+        None => return ERRNO_FAULT,
+    };
+
+    let view = memory.view(&store);
+    match (write_u32(&view, count as u32 as u64, 0), write_u32(&view, size as u32 as u64, 0)) {
+        (Ok(()), Ok(())) => ERRNO_SUCCESS,
+        _ => ERRNO_FAULT,
+    }
+}
+
+/// Write the time of clock `id` in nanoseconds to `time`.
+///
+/// The realtime clock (`0`) uses the host's time, all other clocks
+/// count from the creation of the instance.
+pub fn clock_time_get(mut env: FunctionEnvMut<WasiEnv>, id: i32, _precision: i64, time: i32) -> i32 {
+    let (data, store) = env.data_and_store_mut();
+    let nanos = match id {
+        0 => SystemTime::now().duration_since(UNIX_EPOCH).map(|d| d.as_nanos()).unwrap_or(0),
+        1..=3 => data.start.elapsed().as_nanos(),
+        _ => return ERRNO_INVAL,
+    };
+    let memory = match &data.memory {
+        Some(memory) => memory,
+        None => return ERRNO_FAULT,
+    };
+
+    match memory.view(&store).write(time as u32 as u64, &(nanos as u64).to_le_bytes()) {
+        Ok(()) => ERRNO_SUCCESS,
+        Err(_) => ERRNO_FAULT,
+    }
+}
+
+/// Only `stdin`, `stdout` and `stderr` exist and they can't be closed.
+pub fn fd_close(_env: FunctionEnvMut<WasiEnv>, fd: i32) -> i32 {
+    if (0..=2).contains(&fd) { ERRNO_SUCCESS } else { ERRNO_BADF }
+}
+
+/// Describe `stdin`, `stdout` and `stderr` as character devices.
+pub fn fd_fdstat_get(mut env: FunctionEnvMut<WasiEnv>, fd: i32, buf: i32) -> i32 {
+    if !(0..=2).contains(&fd) {
+        return ERRNO_BADF;
+    }
+    let (data, store) = env.data_and_store_mut();
+    let memory = match &data.memory {
+        Some(memory) => memory,
+        None => return ERRNO_FAULT,
+    };
+
+    // filetype (u8), flags (u16), rights (u64), inherited rights (u64)
+    let mut stat = [0u8; 24];
+    stat[0] = 2;
+    let rights: u64 = if fd == 0 { 1 << 1 } else { 1 << 6 };
+    stat[8..16].copy_from_slice(&rights.to_le_bytes());
+    match memory.view(&store).write(buf as u32 as u64, &stat) {
+        Ok(()) => ERRNO_SUCCESS,
+        Err(_) => ERRNO_FAULT,
+    }
+}
+
+/// There are no preopened directories.
+pub fn fd_prestat_get(_env: FunctionEnvMut<WasiEnv>, _fd: i32, _buf: i32) -> i32 {
+    ERRNO_BADF
+}
+
+/// There are no preopened directories.
+pub fn fd_prestat_dir_name(_env: FunctionEnvMut<WasiEnv>, _fd: i32, _path: i32, _len: i32) -> i32 {
+    ERRNO_BADF
+}
+
+/// `stdin` is always at its end.
+pub fn fd_read(mut env: FunctionEnvMut<WasiEnv>, fd: i32, _iovs: i32, _iovs_len: i32, nread: i32) -> i32 {
+    if fd != 0 {
+        return ERRNO_BADF;
+    }
+    let (data, store) = env.data_and_store_mut();
+    let memory = match &data.memory {
+        Some(memory) => memory,
+        None => return ERRNO_FAULT,
+    };
+
+    match write_u32(&memory.view(&store), nread as u32 as u64, 0) {
+        Ok(()) => ERRNO_SUCCESS,
+        Err(_) => ERRNO_FAULT,
+    }
+}
+
+/// None of the existing files is seekable.
+pub fn fd_seek(_env: FunctionEnvMut<WasiEnv>, fd: i32, _offset: i64, _whence: i32, _new_offset: i32) -> i32 {
+    if (0..=2).contains(&fd) { ERRNO_SPIPE } else { ERRNO_BADF }
+}
+
+/// Write to `stdout` or `stderr`, which are printed line by line.
+pub fn fd_write(mut env: FunctionEnvMut<WasiEnv>, fd: i32, iovs: i32, iovs_len: i32, nwritten: i32) -> i32 {
+    if fd != 1 && fd != 2 {
+        return ERRNO_BADF;
+    }
+    let (data, store) = env.data_and_store_mut();
+    let memory = match &data.memory {
+        Some(memory) => memory.clone(),
+        None => return ERRNO_FAULT,
+    };
+
+    let view = memory.view(&store);
+    let mut n: u64 = 0;
+    for i in 0..iovs_len.max(0) as u64 {
+        // Every `iovec` is a pointer followed by a length.
+        let iov = iovs as u32 as u64 + i * 8;
+        let (ptr, len) = match (read_u32(&view, iov), read_u32(&view, iov + 4)) {
+            (Ok(ptr), Ok(len)) => (ptr, len),
+            _ => return ERRNO_FAULT,
+        };
+
+        if !in_bounds(&view, ptr as u64, len as u64) {
+            return ERRNO_FAULT;
+        }
+        let mut buf = vec![0; len as usize];
+        if view.read(ptr as u64, &mut buf).is_err() {
+            return ERRNO_FAULT;
+        }
+        data.output.extend_from_slice(&buf);
+        data.flush_lines();
+        n = match n.checked_add(len as u64) {
+            Some(n) => n,
+            None => return ERRNO_INVAL,
+        };
+    }
+
+    // The number of bytes written must fit into the `u32` at `nwritten`.
+    let n = match u32::try_from(n) {
+        Ok(n) => n,
+        Err(_) => return ERRNO_INVAL,
+    };
+    match write_u32(&view, nwritten as u32 as u64, n) {
+        Ok(()) => ERRNO_SUCCESS,
+        Err(_) => ERRNO_FAULT,
+    }
+}
+
+/// Terminate the module by trapping.
+pub fn proc_exit(_env: FunctionEnvMut<WasiEnv>, code: i32) -> Result<(), RuntimeError> {
+    Err(RuntimeError::new(format!("module exited with code {}", code)))
+}
+
+/// Fill `len` bytes at `buf` with pseudo random bytes.
+pub fn random_get(mut env: FunctionEnvMut<WasiEnv>, buf: i32, len: i32) -> i32 {
+    let (data, store) = env.data_and_store_mut();
+    let memory = match &data.memory {
+        Some(memory) => memory.clone(),
+        None => return ERRNO_FAULT,
+    };
+
+    let view = memory.view(&store);
+    let len = len as u32 as usize;
+    if !in_bounds(&view, buf as u32 as u64, len as u64) {
+        return ERRNO_FAULT;
+    }
+
+    let mut bytes = Vec::with_capacity(len + 8);
+    while bytes.len() < len {
+        bytes.extend_from_slice(&data.next_random().to_le_bytes());
+    }
+    bytes.truncate(len);
+    match view.write(buf as u32 as u64, &bytes) {
+        Ok(()) => ERRNO_SUCCESS,
+        Err(_) => ERRNO_FAULT,
+    }
+}
+
+/// There is nothing to yield to.
+pub fn sched_yield(_env: FunctionEnvMut<WasiEnv>) -> i32 {
+    ERRNO_SUCCESS
+}